@@ -0,0 +1,1213 @@
+// bodies.rs
+// Los cuerpos del sistema solar (planetas, lunas, anillos y estrellas) con sus
+// ruidos y mallas, y la secuencia de pasadas de cada cuadro armada como una
+// `Scene` para `Renderer`. El binario decide qué mostrar con un `SolarFrame` y
+// dibuja encima lo que no forma parte de una escena (estelas, halos, HUD).
+
+use crate::cli::ScenePreset;
+use crate::color::Color;
+use crate::error::RenderError;
+use crate::generator::GeneratedSystem;
+use crate::mesh;
+use crate::obj::{IndexedMesh, Obj, ObjCache, EMBEDDED_RING_OBJ};
+use crate::planet::{orbit_translation, Planet};
+use crate::render::{
+    create_model_matrix, projected_radius, BodyStyle, CutawayLayer, FragmentShader, Light, Scene,
+    SceneBody, ShadowCaster, Uniforms,
+};
+use crate::seed::SceneSeed;
+use crate::shaders::{
+    fragment_shader, lava_base_color, shader_companion_star, shader_earth, shader_eris,
+    shader_flat, shader_jupiter, shader_mars, shader_mercury, shader_moon, shader_neptune,
+    shader_phobos, shader_pluto, shader_ring, shader_saturn, shader_sedna, shader_uranus,
+    shader_uranus_ring, shader_venus, with_required_noises, ShaderTweak, EARTH_TWEAK, ERIS_TWEAK,
+    JUPITER_TWEAK, MARS_TWEAK, MERCURY_TWEAK, NEPTUNE_TWEAK, PLUTO_TWEAK, SATURN_TWEAK,
+    SEDNA_TWEAK, URANUS_TWEAK, VENUS_TWEAK,
+};
+use crate::texture::Texture;
+use crate::transform::ModelTransform;
+use crate::triangle::FillRule;
+use crate::{log_info, log_warn};
+use fastnoise_lite::{CellularDistanceFunction, FastNoiseLite, FractalType, NoiseType};
+use nalgebra_glm::{Mat4, Vec3};
+use std::collections::HashMap;
+use std::f32::consts::PI;
+
+pub const PLANET_COUNT: usize = 11;
+
+// Centro de masa del sistema: los planetas orbitan alrededor de él, no del Sol
+pub const BARYCENTER: Vec3 = Vec3::new(0.0, 0.0, 0.0);
+
+pub const SUN_SCALE: f32 = 5.0;
+// El Sol emite la base rojiza de su lava en toda la superficie, también en el
+// borde; su shader solo agrega las manchas brillantes encima
+const SUN_EMISSIVE_STRENGTH: f32 = 1.0;
+
+// Shader de cada planeta, en el mismo orden que `SolarSystem::planets`
+pub const PLANET_SHADERS: [FragmentShader; PLANET_COUNT] = [
+    shader_mercury,
+    shader_venus,
+    shader_earth,
+    shader_mars,
+    shader_jupiter,
+    shader_saturn,
+    shader_uranus,
+    shader_neptune,
+    shader_pluto,
+    shader_eris,
+    shader_sedna,
+];
+
+// Parámetro ajustable del shader de cada planeta (mismo orden que `planets`);
+// el '+' y el '-' del teclado numérico lo cambian en el planeta enfocado
+pub const PLANET_TWEAKS: [ShaderTweak; PLANET_COUNT] = [
+    MERCURY_TWEAK,
+    VENUS_TWEAK,
+    EARTH_TWEAK,
+    MARS_TWEAK,
+    JUPITER_TWEAK,
+    SATURN_TWEAK,
+    URANUS_TWEAK,
+    NEPTUNE_TWEAK,
+    PLUTO_TWEAK,
+    ERIS_TWEAK,
+    SEDNA_TWEAK,
+];
+
+// Inclinación del eje de cada planeta en grados; Urano gira prácticamente de lado
+const AXIAL_TILT_DEGREES: [f32; PLANET_COUNT] =
+    [0.0, 0.0, 23.44, 0.0, 0.0, 0.0, 97.77, 0.0, 0.0, 0.0, 0.0];
+
+// Shaders que se pueden comparar lado a lado ('1' y '2' los recorren)
+pub const SHADER_REGISTRY_LEN: usize = 14;
+
+// Luna (con la Tierra): distancia a la Tierra, tamaño y velocidad de su órbita, y
+// los dos anillos que la rodean, girando en radianes por segundo
+const MOON_DISTANCE: f32 = 1.0;
+const MOON_SCALE: f32 = 0.5;
+const MOON_ORBIT_SPEED: f32 = 0.005;
+const MOON_RING_SCALE: f32 = MOON_SCALE * 0.75;
+const MOON_RING_SPEEDS: [f32; 2] = [1.0, -1.45];
+
+// Fobos (con Marte) es irregular: usa su propio modelo en lugar de la esfera.
+// Tamaño relativo comparado con la Luna, distancia a Marte y velocidad de su órbita.
+const PHOBOS_MODEL: &str = "assets/models/phobos.obj";
+const PHOBOS_SCALE: f32 = 0.33;
+const PHOBOS_DISTANCE: f32 = 1.5;
+const PHOBOS_ORBIT_SPEED: f32 = 0.0002;
+const PHOBOS_ROTATION: Vec3 = Vec3::new(5.0, 0.0, 0.0);
+
+// Anillos de Saturno: cantidad, escala del primero, incremento de escala entre
+// anillos consecutivos y de la rotación en Y, alternando el sentido
+const SATURN_RING_COUNT: usize = 6;
+const SATURN_RING_BASE_SCALE: f32 = 2.0;
+const SATURN_RING_SCALE_STEP: f32 = 0.1;
+const SATURN_RING_ROTATION_STEP: f32 = 0.015;
+
+// Anillo de Urano, notablemente inclinado; escala respecto a Urano
+const URANUS_RING_ROTATION: Vec3 = Vec3::new(0.0, 0.1, 1.0);
+const URANUS_RING_SCALE: f32 = 2.4;
+
+// Estrella compañera (--scene binary): orbita por fuera de Sedna. El Sol gira en el
+// lado opuesto del baricentro a `COMPANION_MASS_RATIO` de esa distancia, lo bastante
+// poco para no tocar la órbita de Mercurio.
+const COMPANION_ORBIT_RADIUS: f32 = 50.0;
+const COMPANION_MASS_RATIO: f32 = 0.03;
+const COMPANION_ORBIT_SPEED: f32 = 0.0008;
+const COMPANION_SCALE: f32 = 2.0;
+
+// Aumento de los planetas en el minimapa, que a escala real serían de un píxel
+const MINIMAP_BODY_SCALE: f32 = 3.0;
+
+// Ruido adaptativo: los planetas con un radio en pantalla menor que
+// NOISE_LOD_RADIUS píxeles de la ventana solo evalúan sus primeras
+// NOISE_LOD_LAYERS capas de ruido; el detalle de las demás no se alcanza a ver
+const NOISE_LOD_RADIUS: f32 = 12.0;
+const NOISE_LOD_LAYERS: usize = 1;
+
+// Qué mostrar en un cuadro: el instante, dónde está cada cuerpo y los modos que
+// la aplicación tiene activos
+pub struct SolarFrame<'a> {
+    pub time: f32,
+    // Segundos simulados, para lo que gira a ritmo fijo (los anillos de la Luna)
+    pub elapsed_seconds: f32,
+    pub positions: [Vec3; PLANET_COUNT],
+    // Estrellas de `SolarSystem::stars`
+    pub sun: Vec3,
+    pub companion: Option<Vec3>,
+    // Solo se dibujan los primeros cuerpos; las lunas y anillos van con su planeta
+    pub active_bodies: usize,
+    // Vista individual: solo ese planeta, sin el resto del sistema
+    pub solo: Option<usize>,
+    // Cada cuerpo en un color plano, sin luz ni ruido (ver `shader_flat`)
+    pub flat_shading: bool,
+    // Sombra de Saturno y Urano sobre sus anillos
+    pub ring_shadows: bool,
+    // Anillos de Saturno como mallas; apagado, la aplicación dibuja las partículas
+    pub saturn_rings: bool,
+    pub focus: Option<Focus>,
+    // Corte transversal por planeta, con las capas a mostrar
+    pub cutaway: &'a [bool],
+    pub cutaway_layers: &'a [CutawayLayer],
+    // Capas de ruido de cada planeta (ver `SolarSystem::noise_layers`)
+    pub noise_layers: [Option<usize>; PLANET_COUNT],
+    // Modo exploración: el sistema generado y la posición de cada cuerpo
+    pub sandbox: Option<(&'a GeneratedSystem, &'a [Vec3])>,
+}
+
+// Planeta enfocado y cómo se muestra
+pub struct Focus {
+    pub index: usize,
+    // Giro sobre su eje, en su espacio de modelo
+    pub turntable: Mat4,
+    pub compare: Option<ShaderCompare>,
+    // Canal de ruido crudo que se muestra en lugar del shader
+    pub noise_channel: Option<usize>,
+}
+
+// Comparación en pantalla dividida: un shader del registro por mitad de un
+// framebuffer de `width` x `height`
+pub struct ShaderCompare {
+    pub shaders: [usize; 2],
+    pub width: usize,
+    pub height: usize,
+}
+
+// Datos, ruidos y mallas de todos los cuerpos. Los ruidos de cada conjunto se
+// validan contra los que pide su shader: si faltan se avisa por consola y se
+// completan, en lugar de entrar en pánico al dibujar.
+pub struct SolarSystem {
+    // Cuerpos en órbita alrededor del Sol, en orden de distancia
+    pub planets: [Planet; PLANET_COUNT],
+    // Valor actual de cada ajuste por nombre, listo para `Uniforms::params`
+    pub params: [(&'static str, f32); PLANET_COUNT],
+    binary: bool,
+    sphere: IndexedMesh,
+    ring: IndexedMesh,
+    models: HashMap<String, Option<IndexedMesh>>,
+    sun_noises: Vec<FastNoiseLite>,
+    moon_noises: Vec<FastNoiseLite>,
+    phobos_noises: Vec<FastNoiseLite>,
+    uranus_ring_noises: Vec<FastNoiseLite>,
+    // Son de un arreglo y no de campos sueltos para poder cambiar sus semillas
+    planet_noises: [Vec<FastNoiseLite>; PLANET_COUNT],
+    // Relieve de cráteres como mapa de normales: más detalle sin más geometría
+    mercury_normal_map: Texture,
+    phobos_normal_map: Texture,
+}
+
+impl SolarSystem {
+    // La esfera se genera por código con `sphere_stacks` anillos de latitud. El
+    // anillo se carga del OBJ; fuera de la raíz del proyecto se usa la copia embebida.
+    pub fn new(
+        seeds: &SceneSeed,
+        preset: ScenePreset,
+        sphere_stacks: u32,
+    ) -> Result<Self, RenderError> {
+        let ring_obj = Obj::load_or_embedded("assets/models/ring.obj", EMBEDDED_RING_OBJ)
+            .map_err(RenderError::Model)?;
+        let sphere = mesh::uv_sphere(sphere_stacks, sphere_stacks * 2);
+        let ring = ring_obj.to_indexed_mesh();
+        log_info!(
+            "meshes ready: sphere_vertices={} sphere_triangles={} ring_vertices={}",
+            sphere.vertices.len(),
+            sphere.indices.len() / 3,
+            ring.indices.len()
+        );
+
+        let planet_noises = [
+            with_required_noises("Mercurio", shader_mercury, create_mercury_noises(seeds)),
+            with_required_noises("Venus", shader_venus, create_venus_noises(seeds)),
+            with_required_noises("Tierra", shader_earth, create_earth_noises(seeds)),
+            with_required_noises("Marte", shader_mars, create_mars_noises(seeds)),
+            with_required_noises("Júpiter", shader_jupiter, create_jupiter_noise(seeds)),
+            with_required_noises("Saturno", shader_saturn, create_saturn_noises(seeds)),
+            with_required_noises("Urano", shader_uranus, create_uranus_noises(seeds)),
+            with_required_noises("Neptuno", shader_neptune, create_neptune_noises(seeds)),
+            with_required_noises("Plutón", shader_pluto, create_pluto_noises(seeds)),
+            with_required_noises("Eris", shader_eris, create_eris_noises(seeds)),
+            with_required_noises("Sedna", shader_sedna, create_sedna_noises(seeds)),
+        ];
+        let phobos_noises =
+            with_required_noises("Fobos", shader_phobos, create_phobos_noises(seeds));
+        let planets = create_planets(seeds);
+
+        // Modelos propios de los cuerpos, cargados una vez por ruta
+        let mut model_cache = ObjCache::new();
+        let models = load_body_models(
+            &mut model_cache,
+            planets
+                .iter()
+                .filter_map(|planet| planet.model.as_deref())
+                .chain([PHOBOS_MODEL]),
+        );
+
+        Ok(SolarSystem {
+            params: PLANET_TWEAKS.map(|tweak| (tweak.name, tweak.default)),
+            planets,
+            binary: preset == ScenePreset::Binary,
+            sphere,
+            ring,
+            models,
+            sun_noises: with_required_noises("Sol", fragment_shader, create_lava_noise(seeds)),
+            moon_noises: with_required_noises("Luna", shader_moon, create_moon_noises(seeds)),
+            uranus_ring_noises: with_required_noises(
+                "Anillo de Urano",
+                shader_uranus_ring,
+                create_uranus_ring_noises(seeds),
+            ),
+            mercury_normal_map: create_crater_normal_map(&planet_noises[0][0]),
+            phobos_normal_map: create_crater_normal_map(&phobos_noises[0]),
+            phobos_noises,
+            planet_noises,
+        })
+    }
+
+    pub fn planet_noises(&self, index: usize) -> &[FastNoiseLite] {
+        &self.planet_noises[index]
+    }
+
+    // Nuevas semillas para los ruidos de un planeta; el tipo y la frecuencia de
+    // cada capa se conservan
+    pub fn reseed(&mut self, index: usize) {
+        for noise in &mut self.planet_noises[index] {
+            noise.set_seed(Some(rand::random()));
+        }
+        // El relieve de Mercurio sale de su ruido de cráteres
+        if index == 0 {
+            self.mercury_normal_map = create_crater_normal_map(&self.planet_noises[0][0]);
+        }
+        let seeds: Vec<i32> = self.planet_noises[index]
+            .iter()
+            .map(|noise| noise.seed)
+            .collect();
+        log_info!("reseeded {}: seeds={:?}", self.planets[index].name, seeds);
+    }
+
+    // Vuelve a generar la esfera, p. ej. al cambiar la calidad
+    pub fn set_sphere_detail(&mut self, stacks: u32) {
+        self.sphere = mesh::uv_sphere(stacks, stacks * 2);
+    }
+
+    pub fn sphere(&self) -> &IndexedMesh {
+        &self.sphere
+    }
+
+    // Malla de un planeta: su modelo si se pudo cargar, si no la esfera
+    pub fn planet_mesh(&self, index: usize) -> &IndexedMesh {
+        body_mesh(
+            &self.models,
+            self.planets[index].model.as_deref(),
+            &self.sphere,
+        )
+    }
+
+    // Shaders disponibles para comparar lado a lado, cada uno con los ruidos que espera
+    pub fn shader_registry(
+        &self,
+    ) -> [(&str, FragmentShader, &[FastNoiseLite]); SHADER_REGISTRY_LEN] {
+        let noises = &self.planet_noises;
+        [
+            ("Sol", fragment_shader, &self.sun_noises),
+            ("Mercurio", shader_mercury, &noises[0]),
+            ("Venus", shader_venus, &noises[1]),
+            ("Tierra", shader_earth, &noises[2]),
+            ("Luna", shader_moon, &self.moon_noises),
+            ("Marte", shader_mars, &noises[3]),
+            ("Fobos", shader_phobos, &self.phobos_noises),
+            ("Júpiter", shader_jupiter, &noises[4]),
+            ("Saturno", shader_saturn, &noises[5]),
+            ("Urano", shader_uranus, &noises[6]),
+            ("Neptuno", shader_neptune, &noises[7]),
+            ("Plutón", shader_pluto, &noises[8]),
+            ("Eris", shader_eris, &noises[9]),
+            ("Sedna", shader_sedna, &noises[10]),
+        ]
+    }
+
+    // Posición del Sol y de la compañera, si la escena es binaria: giran en lados
+    // opuestos del baricentro
+    pub fn stars(&self, time: f32) -> (Vec3, Option<Vec3>) {
+        let companion = self.binary.then(|| {
+            orbit_translation(
+                BARYCENTER,
+                COMPANION_ORBIT_RADIUS,
+                COMPANION_ORBIT_SPEED,
+                0.0,
+                time,
+            )
+        });
+        let sun = match companion {
+            Some(companion) => BARYCENTER - (companion - BARYCENTER) * COMPANION_MASS_RATIO,
+            None => BARYCENTER,
+        };
+        (sun, companion)
+    }
+
+    // Cada estrella ilumina la escena desde su posición actual; el Sol va primero.
+    // En la escena binaria el Sol da una luz cálida y la compañera una azulada;
+    // sola, la luz del Sol es blanca como siempre.
+    pub fn lights(&self, sun: Vec3, companion: Option<Vec3>) -> Vec<Light> {
+        let (sun_color, companion_color) = if self.binary {
+            (Color::new(255, 200, 140), Color::new(140, 180, 255))
+        } else {
+            (Color::white(), Color::white())
+        };
+        std::iter::once(Light {
+            position: sun,
+            color: sun_color,
+        })
+        .chain(companion.map(|position| Light {
+            position,
+            color: companion_color,
+        }))
+        .collect()
+    }
+
+    // Posición de cada planeta en `time` sobre órbitas de los radios dados (los
+    // configurados o los de la animación de explotar órbitas)
+    pub fn planet_positions(&self, orbit_radii: &[f32], time: f32) -> [Vec3; PLANET_COUNT] {
+        std::array::from_fn(|index| {
+            orbit_translation(
+                BARYCENTER,
+                orbit_radii[index],
+                self.planets[index].signed_orbit_speed(),
+                self.planets[index].phase_offset,
+                time,
+            )
+        })
+    }
+
+    // Traslación a la órbita, inclinación del eje, giro y escala del planeta
+    pub fn planet_model_matrix(&self, index: usize, position: Vec3, time: f32) -> Mat4 {
+        let planet = &self.planets[index];
+        ModelTransform::new()
+            .translation(position)
+            .axial_tilt(AXIAL_TILT_DEGREES[index].to_radians())
+            .spin(planet.spin_angle(time))
+            .scale(planet.scale)
+            .build()
+    }
+
+    // Capas de ruido de cada planeta según su radio en pantalla con la cámara de
+    // `uniforms`; `pixel_scale` pasa de píxeles de la ventana a los del framebuffer
+    pub fn noise_layers(
+        &self,
+        positions: &[Vec3; PLANET_COUNT],
+        uniforms: &Uniforms,
+        pixel_scale: f32,
+    ) -> [Option<usize>; PLANET_COUNT] {
+        std::array::from_fn(|index| {
+            projected_radius(positions[index], self.planets[index].scale, uniforms)
+                .filter(|&radius| radius < NOISE_LOD_RADIUS * pixel_scale)
+                .map(|_| NOISE_LOD_LAYERS)
+        })
+    }
+
+    // Los cuerpos del cuadro en el orden de las pasadas: la Luna y sus anillos,
+    // los planetas, Fobos, los anillos de Saturno y Urano, y por último las
+    // estrellas. En el modo exploración los cuerpos generados reemplazan a todo
+    // lo que no es una estrella.
+    pub fn scene<'a>(&'a self, frame: &SolarFrame<'a>) -> Scene<'a> {
+        // En modo plano cada cuerpo se dibuja con su color, sin luz ni ruido
+        let body_shader = |shader: FragmentShader| -> FragmentShader {
+            if frame.flat_shading {
+                shader_flat
+            } else {
+                shader
+            }
+        };
+        let is_active = |index: usize| index < frame.active_bodies && frame.solo.is_none();
+        let mut bodies = Vec::new();
+
+        match frame.sandbox {
+            Some((system, positions)) => {
+                for ((planet, body), position) in
+                    system.planets.iter().zip(&system.bodies).zip(positions)
+                {
+                    let model_matrix = ModelTransform::new()
+                        .translation(*position)
+                        .spin(planet.spin_angle(frame.time))
+                        .scale(planet.scale)
+                        .build();
+                    bodies.push(SceneBody {
+                        emissive: planet.emission(),
+                        ambient_tint: planet.ambient_tint,
+                        albedo: planet.albedo,
+                        ..SceneBody::new(
+                            &self.sphere,
+                            model_matrix,
+                            body_shader(body.shader),
+                            body.noises.iter().collect(),
+                        )
+                    });
+                }
+            }
+            None => {
+                if is_active(2) {
+                    self.push_moon(&mut bodies, frame, body_shader(shader_moon));
+                }
+                self.push_planets(&mut bodies, frame, body_shader);
+                if is_active(3) {
+                    self.push_phobos(&mut bodies, frame, body_shader(shader_phobos));
+                }
+                if is_active(5) && frame.saturn_rings {
+                    self.push_saturn_rings(&mut bodies, frame);
+                }
+                if is_active(6) {
+                    bodies.push(SceneBody {
+                        shadow_caster: frame.ring_shadows.then_some(ShadowCaster {
+                            light_position: frame.sun,
+                            center: frame.positions[6],
+                            radius: self.planets[6].scale,
+                        }),
+                        fill_rule: Some(FillRule::Conservative),
+                        ..SceneBody::new(
+                            &self.ring,
+                            create_model_matrix(
+                                frame.positions[6],
+                                URANUS_RING_SCALE,
+                                URANUS_RING_ROTATION,
+                            ),
+                            shader_uranus_ring,
+                            self.uranus_ring_noises.iter().collect(),
+                        )
+                    });
+                }
+            }
+        }
+
+        if frame.solo.is_none() {
+            bodies.push(SceneBody {
+                // En modo plano se ve solo el color del cuerpo, como los planetas
+                emissive: if frame.flat_shading {
+                    Color::black()
+                } else {
+                    sun_emission()
+                },
+                albedo: Color::new(255, 210, 60),
+                ..SceneBody::new(
+                    &self.sphere,
+                    sun_model_matrix(frame.sun),
+                    body_shader(fragment_shader),
+                    self.sun_noises.iter().collect(),
+                )
+            });
+            if let Some(companion) = frame.companion {
+                // Su shader ya incluye la base azulada: no lleva la emisión del Sol
+                bodies.push(SceneBody {
+                    albedo: Color::new(150, 190, 255),
+                    ..SceneBody::new(
+                        &self.sphere,
+                        create_model_matrix(companion, COMPANION_SCALE, Vec3::zeros()),
+                        body_shader(shader_companion_star),
+                        self.sun_noises.iter().collect(),
+                    )
+                });
+            }
+        }
+
+        Scene {
+            bodies,
+            lights: self.lights(frame.sun, frame.companion),
+            ..Scene::default()
+        }
+    }
+
+    // Vista cenital pequeña: el Sol y los planetas (o los cuerpos generados),
+    // agrandados para que se distingan, sin lunas ni anillos
+    pub fn minimap_scene<'a>(&'a self, frame: &SolarFrame<'a>) -> Scene<'a> {
+        let sun = SceneBody {
+            emissive: sun_emission(),
+            ..SceneBody::new(
+                &self.sphere,
+                sun_model_matrix(frame.sun),
+                fragment_shader,
+                self.sun_noises.iter().collect(),
+            )
+        };
+        let minimap_matrix = |position: Vec3, scale: f32| {
+            create_model_matrix(position, scale * MINIMAP_BODY_SCALE, Vec3::zeros())
+        };
+        let bodies: Vec<SceneBody> = match frame.sandbox {
+            Some((system, positions)) => system
+                .planets
+                .iter()
+                .zip(&system.bodies)
+                .zip(positions)
+                .map(|((planet, body), &position)| {
+                    SceneBody::new(
+                        &self.sphere,
+                        minimap_matrix(position, planet.scale),
+                        body.shader,
+                        body.noises.iter().collect(),
+                    )
+                })
+                .collect(),
+            None => (0..frame.active_bodies.min(PLANET_COUNT))
+                .map(|index| SceneBody {
+                    emissive: self.planets[index].emission(),
+                    ..SceneBody::new(
+                        self.planet_mesh(index),
+                        minimap_matrix(frame.positions[index], self.planets[index].scale),
+                        PLANET_SHADERS[index],
+                        self.planet_noises[index].iter().collect(),
+                    )
+                })
+                .collect(),
+        };
+        Scene {
+            bodies: std::iter::once(sun).chain(bodies).collect(),
+            lights: self.lights(frame.sun, frame.companion),
+            ..Scene::default()
+        }
+    }
+
+    // Cuerpos de un planeta: el que corresponde a su modo (comparación de shaders,
+    // canal de ruido, corte transversal o normal), con el giro si está enfocado
+    fn push_planets<'a>(
+        &'a self,
+        bodies: &mut Vec<SceneBody<'a>>,
+        frame: &SolarFrame<'a>,
+        body_shader: impl Fn(FragmentShader) -> FragmentShader,
+    ) {
+        for (index, planet) in self.planets.iter().enumerate().take(frame.active_bodies) {
+            if frame.solo.is_some_and(|solo| solo != index) {
+                continue;
+            }
+            let focus = frame.focus.as_ref().filter(|focus| focus.index == index);
+            let mut model_matrix =
+                self.planet_model_matrix(index, frame.positions[index], frame.time);
+            if let Some(focus) = focus {
+                model_matrix *= focus.turntable;
+            }
+            let body = SceneBody {
+                emissive: if frame.flat_shading {
+                    Color::black()
+                } else {
+                    planet.emission()
+                },
+                ambient_tint: planet.ambient_tint,
+                albedo: planet.albedo,
+                params: std::slice::from_ref(&self.params[index]),
+                // El relieve de Mercurio llega al shader en cualquiera de los modos
+                normal_map: (index == 0).then_some(&self.mercury_normal_map),
+                noise_layers: frame.noise_layers[index],
+                ..SceneBody::new(
+                    self.planet_mesh(index),
+                    model_matrix,
+                    PLANET_SHADERS[index],
+                    self.planet_noises[index].iter().collect(),
+                )
+            };
+
+            if let Some(compare) = focus.and_then(|focus| focus.compare.as_ref()) {
+                // Cada shader se limita a su mitad del framebuffer
+                let registry = self.shader_registry();
+                let half = compare.width / 2;
+                let halves = [(0, half), (half, compare.width - half)];
+                for ((x, width), &shader_index) in halves.into_iter().zip(&compare.shaders) {
+                    let (_, shader, noises) = registry[shader_index];
+                    bodies.push(SceneBody {
+                        shader,
+                        noises: noises.iter().collect(),
+                        scissor: Some((x, 0, width, compare.height)),
+                        ..body.clone()
+                    });
+                }
+            } else if let Some(channel) = focus.and_then(|focus| focus.noise_channel) {
+                bodies.push(SceneBody {
+                    style: BodyStyle::NoiseChannel(channel),
+                    ..body
+                });
+            } else if frame.cutaway[index] {
+                bodies.push(SceneBody {
+                    style: BodyStyle::Cutaway(frame.cutaway_layers),
+                    ..body
+                });
+            } else {
+                bodies.push(SceneBody {
+                    shader: body_shader(body.shader),
+                    ..body
+                });
+            }
+        }
+    }
+
+    // La Luna orbita la Tierra rodeada por dos anillos que giran en ejes distintos
+    fn push_moon<'a>(
+        &'a self,
+        bodies: &mut Vec<SceneBody<'a>>,
+        frame: &SolarFrame<'a>,
+        shader: FragmentShader,
+    ) {
+        let earth = frame.positions[2];
+        let angle = 0.025 * frame.time * MOON_ORBIT_SPEED;
+        let position = Vec3::new(
+            earth.x + MOON_DISTANCE * angle.cos(),
+            earth.y,
+            earth.z + MOON_DISTANCE * angle.sin(),
+        );
+        bodies.push(SceneBody {
+            albedo: Color::new(190, 190, 190),
+            ..SceneBody::new(
+                &self.sphere,
+                create_model_matrix(position, MOON_SCALE, Vec3::new(0.0, angle, 0.0)),
+                shader,
+                self.moon_noises.iter().collect(),
+            )
+        });
+
+        let [first, second] = MOON_RING_SPEEDS.map(|speed| speed * frame.elapsed_seconds);
+        for rotation in [Vec3::new(0.0, 0.0, first), Vec3::new(second, 0.0, 0.0)] {
+            bodies.push(ring_body(
+                &self.ring,
+                create_model_matrix(position, MOON_RING_SCALE, rotation),
+                shader_ring,
+                vec![],
+            ));
+        }
+    }
+
+    // Fobos orbita Marte en el plano XY
+    fn push_phobos<'a>(
+        &'a self,
+        bodies: &mut Vec<SceneBody<'a>>,
+        frame: &SolarFrame<'a>,
+        shader: FragmentShader,
+    ) {
+        let mars = frame.positions[3];
+        let angle = frame.time * PHOBOS_ORBIT_SPEED;
+        let position = Vec3::new(
+            mars.x + PHOBOS_DISTANCE * angle.cos(),
+            mars.y + PHOBOS_DISTANCE * angle.sin(),
+            mars.z,
+        );
+        bodies.push(SceneBody {
+            albedo: Color::new(140, 120, 100),
+            normal_map: Some(&self.phobos_normal_map),
+            ..SceneBody::new(
+                body_mesh(&self.models, Some(PHOBOS_MODEL), &self.sphere),
+                create_model_matrix(position, PHOBOS_SCALE, PHOBOS_ROTATION),
+                shader,
+                self.phobos_noises.iter().collect(),
+            )
+        });
+    }
+
+    fn push_saturn_rings<'a>(&'a self, bodies: &mut Vec<SceneBody<'a>>, frame: &SolarFrame<'a>) {
+        let shadow_caster = frame.ring_shadows.then_some(ShadowCaster {
+            light_position: frame.sun,
+            center: frame.positions[5],
+            radius: self.planets[5].scale,
+        });
+        for i in 0..SATURN_RING_COUNT {
+            let scale = SATURN_RING_BASE_SCALE + i as f32 * SATURN_RING_SCALE_STEP;
+            let direction = if i % 2 == 0 { 1.0 } else { -1.0 };
+            let rotation = Vec3::new(
+                0.0,
+                1.0,
+                1.0 + i as f32 * SATURN_RING_ROTATION_STEP * direction,
+            );
+            bodies.push(SceneBody {
+                shadow_caster,
+                ..ring_body(
+                    &self.ring,
+                    create_model_matrix(frame.positions[5], scale, rotation),
+                    shader_ring,
+                    vec![],
+                )
+            });
+        }
+    }
+}
+
+pub fn sun_model_matrix(position: Vec3) -> Mat4 {
+    create_model_matrix(position, SUN_SCALE, Vec3::zeros())
+}
+
+fn sun_emission() -> Color {
+    lava_base_color() * SUN_EMISSIVE_STRENGTH
+}
+
+// Los anillos son tan delgados que vistos casi de canto no cubren ningún centro
+// de píxel y se cortan; con relleno conservador no desaparecen
+fn ring_body<'a>(
+    ring: &'a IndexedMesh,
+    model_matrix: Mat4,
+    shader: FragmentShader,
+    noises: Vec<&'a FastNoiseLite>,
+) -> SceneBody<'a> {
+    SceneBody {
+        fill_rule: Some(FillRule::Conservative),
+        ..SceneBody::new(ring, model_matrix, shader, noises)
+    }
+}
+
+// Cada cuerpo empieza en un punto distinto de su órbita, derivado de la semilla
+fn create_planets(seeds: &SceneSeed) -> [Planet; PLANET_COUNT] {
+    // Escala, radio y velocidad orbital, y largo de la estela en puntos, es decir,
+    // en múltiplos de TRAIL_SAMPLE_INTERVAL de tiempo simulado
+    [
+        Planet::new("Mercurio", 0.7, 8.0, 0.02, 100).with_albedo(Color::new(160, 160, 160)),
+        // Venus gira sobre su eje al revés que casi todos los planetas
+        Planet::new("Venus", 0.9, 10.0, 0.015, 150)
+            .with_albedo(Color::new(230, 200, 120))
+            .with_spin(0.00005)
+            .with_retrograde(false, true),
+        Planet::new("Tierra", 1.2, 12.0, 0.01, 200)
+            .with_albedo(Color::new(60, 110, 220))
+            .with_spin(0.0002)
+            // De lejos se ve el celeste de la atmósfera, no el azul del océano
+            .with_glow(Color::new(130, 180, 255), 1.0),
+        Planet::new("Marte", 0.8, 14.0, 0.008, 250).with_albedo(Color::new(200, 70, 40)),
+        Planet::new("Júpiter", 3.0, 18.0, 0.005, 300).with_albedo(Color::new(210, 160, 110)),
+        Planet::new("Saturno", 2.5, 22.0, 0.004, 350).with_albedo(Color::new(220, 200, 140)),
+        Planet::new("Urano", 1.8, 26.0, 0.003, 400)
+            .with_albedo(Color::new(140, 210, 220))
+            .with_spin(0.00015),
+        Planet::new("Neptuno", 1.6, 30.0, 0.002, 450).with_albedo(Color::new(60, 90, 210)),
+        Planet::new("Plutón", 1.0, 34.0, 0.0015, 500).with_albedo(Color::new(200, 180, 160)),
+        Planet::new("Eris", 1.2, 38.0, 0.0012, 550).with_albedo(Color::new(230, 230, 235)),
+        Planet::new("Sedna", 1.3, 42.0, 0.001, 600).with_albedo(Color::new(190, 90, 70)),
+    ]
+    .map(|planet| {
+        let phase = seeds.phase(&planet.name);
+        planet.with_phase(phase)
+    })
+}
+
+fn create_lava_noise(seeds: &SceneSeed) -> Vec<FastNoiseLite> {
+    let mut noise = FastNoiseLite::with_seed(seeds.derive("sun", 0, 42));
+
+    // Use FBm for multi-layered noise, giving a "turbulent" feel
+    noise.set_noise_type(Some(NoiseType::Perlin)); // Perlin noise for smooth, natural texture
+    noise.set_fractal_type(Some(FractalType::FBm)); // FBm for layered detail
+    noise.set_fractal_octaves(Some(6)); // High octaves for rich detail
+    noise.set_fractal_lacunarity(Some(2.0)); // Higher lacunarity = more contrast between layers
+    noise.set_fractal_gain(Some(0.5)); // Higher gain = more influence of smaller details
+    noise.set_frequency(Some(0.002)); // Low frequency = large features
+
+    vec![noise]
+}
+
+fn create_earth_noises(seeds: &SceneSeed) -> Vec<FastNoiseLite> {
+    // Ruido base para el terreno (montañas)
+    let mut mountain_noise = FastNoiseLite::with_seed(seeds.derive("earth", 0, 42));
+    mountain_noise.set_noise_type(Some(NoiseType::Perlin));
+    mountain_noise.set_frequency(Some(1.0)); // Frecuencia baja para grandes características
+    mountain_noise.set_fractal_type(Some(FractalType::FBm));
+    mountain_noise.set_fractal_octaves(Some(5));
+
+    // Ruido secundario para colinas
+    let mut hill_noise = FastNoiseLite::with_seed(seeds.derive("earth", 1, 1337));
+    hill_noise.set_noise_type(Some(NoiseType::Perlin));
+    hill_noise.set_frequency(Some(2.5)); // Frecuencia media
+    hill_noise.set_fractal_type(Some(FractalType::FBm));
+    hill_noise.set_fractal_octaves(Some(4));
+
+    // Ruido terciario para detalles finos
+    let mut detail_noise = FastNoiseLite::with_seed(seeds.derive("earth", 2, 2021));
+    detail_noise.set_noise_type(Some(NoiseType::Perlin));
+    detail_noise.set_frequency(Some(5.0)); // Frecuencia alta para detalles finos
+    detail_noise.set_fractal_type(Some(FractalType::FBm));
+    detail_noise.set_fractal_octaves(Some(3));
+
+    // Ruido para las nubes (sin cambios)
+    let mut cloud_noise = FastNoiseLite::with_seed(seeds.derive("earth", 3, 40));
+    cloud_noise.set_noise_type(Some(NoiseType::Perlin));
+    cloud_noise.set_frequency(Some(5.0));
+    cloud_noise.set_fractal_type(Some(FractalType::FBm));
+    cloud_noise.set_fractal_octaves(Some(1));
+
+    // Atmosfera de la Tierra
+    let mut atmosphere_noise = FastNoiseLite::with_seed(seeds.derive("earth", 4, 40));
+    atmosphere_noise.set_noise_type(Some(NoiseType::Perlin));
+    atmosphere_noise.set_fractal_type(Some(FractalType::FBm));
+    atmosphere_noise.set_fractal_octaves(Some(2)); // Menos octavas para menos detalles
+    atmosphere_noise.set_fractal_lacunarity(Some(3.0));
+    atmosphere_noise.set_fractal_gain(Some(0.5));
+    atmosphere_noise.set_frequency(Some(0.01));
+
+    vec![
+        mountain_noise,
+        hill_noise,
+        detail_noise,
+        cloud_noise,
+        atmosphere_noise,
+    ]
+}
+
+fn create_jupiter_noise(seeds: &SceneSeed) -> Vec<FastNoiseLite> {
+    let mut band_noise = FastNoiseLite::with_seed(seeds.derive("jupiter", 0, 1337));
+    band_noise.set_noise_type(Some(NoiseType::OpenSimplex2));
+    band_noise.set_frequency(Some(5.0));
+    band_noise.set_fractal_type(Some(FractalType::FBm));
+    band_noise.set_fractal_octaves(Some(3));
+
+    let mut high_altitude_clouds = FastNoiseLite::with_seed(seeds.derive("jupiter", 1, 42));
+    high_altitude_clouds.set_noise_type(Some(NoiseType::OpenSimplex2));
+    high_altitude_clouds.set_frequency(Some(3.0));
+    high_altitude_clouds.set_fractal_type(Some(FractalType::FBm));
+    high_altitude_clouds.set_fractal_octaves(Some(2));
+
+    let mut deep_atmospheric = FastNoiseLite::with_seed(seeds.derive("jupiter", 2, 56));
+    deep_atmospheric.set_noise_type(Some(NoiseType::Perlin));
+    deep_atmospheric.set_frequency(Some(1.5));
+    deep_atmospheric.set_fractal_type(Some(FractalType::FBm));
+    deep_atmospheric.set_fractal_octaves(Some(4));
+
+    vec![band_noise, high_altitude_clouds, deep_atmospheric]
+}
+
+fn create_moon_noises(seeds: &SceneSeed) -> Vec<FastNoiseLite> {
+    // Ruido base para las características grandes
+    let mut noise1 = FastNoiseLite::with_seed(seeds.derive("moon", 0, 345));
+    noise1.set_noise_type(Some(NoiseType::Perlin));
+    noise1.set_frequency(Some(1.0)); // Frecuencia baja para manchas grandes
+    noise1.set_fractal_type(Some(FractalType::FBm));
+    noise1.set_fractal_octaves(Some(4));
+
+    // Ruido secundario para detalles adicionales
+    let mut noise2 = FastNoiseLite::with_seed(seeds.derive("moon", 1, 678));
+    noise2.set_noise_type(Some(NoiseType::Perlin));
+    noise2.set_frequency(Some(5.0)); // Frecuencia media
+    noise2.set_fractal_type(Some(FractalType::FBm));
+    noise2.set_fractal_octaves(Some(3));
+
+    // Ruido terciario para detalles finos
+    let mut noise3 = FastNoiseLite::with_seed(seeds.derive("moon", 2, 910));
+    noise3.set_noise_type(Some(NoiseType::Perlin));
+    noise3.set_frequency(Some(10.0)); // Frecuencia alta para detalles finos
+    noise3.set_fractal_type(Some(FractalType::FBm));
+    noise3.set_fractal_octaves(Some(2));
+
+    vec![noise1, noise2, noise3]
+}
+
+fn create_venus_noises(seeds: &SceneSeed) -> Vec<FastNoiseLite> {
+    let mut surface_noise = FastNoiseLite::with_seed(seeds.derive("venus", 0, 1337));
+    surface_noise.set_noise_type(Some(NoiseType::OpenSimplex2));
+    surface_noise.set_frequency(Some(5.0));
+    surface_noise.set_fractal_type(Some(FractalType::FBm));
+    surface_noise.set_fractal_octaves(Some(3));
+
+    let mut atmosphere_noise = FastNoiseLite::with_seed(seeds.derive("venus", 1, 235));
+    atmosphere_noise.set_noise_type(Some(NoiseType::Perlin));
+    atmosphere_noise.set_frequency(Some(0.5));
+    atmosphere_noise.set_fractal_type(Some(FractalType::FBm));
+    atmosphere_noise.set_fractal_octaves(Some(4));
+
+    vec![surface_noise, atmosphere_noise]
+}
+
+fn create_mercury_noises(seeds: &SceneSeed) -> Vec<FastNoiseLite> {
+    let mut crater_noise = FastNoiseLite::with_seed(seeds.derive("mercury", 0, 2341));
+    crater_noise.set_noise_type(Some(NoiseType::Cellular));
+    crater_noise.set_frequency(Some(0.5));
+    crater_noise.set_fractal_type(Some(FractalType::FBm));
+    crater_noise.set_fractal_octaves(Some(4));
+    crater_noise.set_cellular_distance_function(Some(CellularDistanceFunction::Manhattan));
+
+    // Additional noise for textural variation
+    let mut texture_noise = FastNoiseLite::with_seed(seeds.derive("mercury", 1, 4567));
+    texture_noise.set_noise_type(Some(NoiseType::Perlin));
+    texture_noise.set_frequency(Some(2.0));
+    texture_noise.set_fractal_type(Some(FractalType::Ridged));
+    texture_noise.set_fractal_octaves(Some(3));
+
+    // Another noise for subtle surface undulations
+    let mut undulation_noise = FastNoiseLite::with_seed(seeds.derive("mercury", 2, 7890));
+    undulation_noise.set_noise_type(Some(NoiseType::Perlin));
+    undulation_noise.set_frequency(Some(0.1));
+    undulation_noise.set_fractal_type(Some(FractalType::FBm));
+    undulation_noise.set_fractal_octaves(Some(2));
+
+    vec![crater_noise, texture_noise, undulation_noise]
+}
+
+// Tamaño del mapa de normales de cráteres (u alrededor, v de polo a polo), cuántas
+// veces más fino que el color es el ruido del relieve y cuánto se marcan las pendientes
+const CRATER_NORMAL_MAP_SIZE: (usize, usize) = (512, 256);
+const CRATER_RELIEF_FREQUENCY: f32 = 8.0;
+const CRATER_RELIEF_STRENGTH: f32 = 6.0;
+
+// La altura se toma del ruido de cráteres en el punto de la esfera unitaria que
+// corresponde a cada (u, v) de `mesh::uv_sphere`, así el mapa no tiene costura
+fn create_crater_normal_map(crater_noise: &FastNoiseLite) -> Texture {
+    let (width, height) = CRATER_NORMAL_MAP_SIZE;
+    Texture::normal_map_from_height(width, height, CRATER_RELIEF_STRENGTH, |u, v| {
+        let polar = v * PI;
+        let azimuth = u * 2.0 * PI;
+        let point = Vec3::new(
+            polar.sin() * azimuth.cos(),
+            polar.cos(),
+            polar.sin() * azimuth.sin(),
+        ) * CRATER_RELIEF_FREQUENCY;
+        crater_noise.get_noise_3d(point.x, point.y, point.z)
+    })
+}
+
+fn create_mars_noises(seeds: &SceneSeed) -> Vec<FastNoiseLite> {
+    let mut surface_noise = FastNoiseLite::with_seed(seeds.derive("mars", 0, 1024));
+    surface_noise.set_noise_type(Some(NoiseType::Perlin));
+    surface_noise.set_frequency(Some(0.6)); // Menor frecuencia para características más amplias
+    surface_noise.set_fractal_type(Some(FractalType::FBm));
+    surface_noise.set_fractal_octaves(Some(4));
+
+    let mut detail_noise = FastNoiseLite::with_seed(seeds.derive("mars", 1, 2048));
+    detail_noise.set_noise_type(Some(NoiseType::OpenSimplex2));
+    detail_noise.set_frequency(Some(2.0)); // Mayor frecuencia para detalles finos
+    detail_noise.set_fractal_type(Some(FractalType::FBm));
+    detail_noise.set_fractal_octaves(Some(3));
+
+    let mut atmospheric_noise = FastNoiseLite::with_seed(seeds.derive("mars", 2, 3100));
+    atmospheric_noise.set_noise_type(Some(NoiseType::Perlin));
+    atmospheric_noise.set_frequency(Some(0.5));
+    atmospheric_noise.set_fractal_type(Some(FractalType::Ridged));
+    atmospheric_noise.set_fractal_octaves(Some(2));
+
+    vec![surface_noise, detail_noise, atmospheric_noise]
+}
+
+fn create_phobos_noises(seeds: &SceneSeed) -> Vec<FastNoiseLite> {
+    let mut crater_noise = FastNoiseLite::with_seed(seeds.derive("phobos", 0, 2341));
+    crater_noise.set_noise_type(Some(NoiseType::Cellular));
+    crater_noise.set_frequency(Some(0.5));
+    crater_noise.set_fractal_type(Some(FractalType::FBm));
+    crater_noise.set_fractal_octaves(Some(4));
+    crater_noise.set_cellular_distance_function(Some(CellularDistanceFunction::Manhattan));
+
+    // Additional noise for textural variation
+    let mut texture_noise = FastNoiseLite::with_seed(seeds.derive("phobos", 1, 4567));
+    texture_noise.set_noise_type(Some(NoiseType::Perlin));
+    texture_noise.set_frequency(Some(2.0));
+    texture_noise.set_fractal_type(Some(FractalType::Ridged));
+    texture_noise.set_fractal_octaves(Some(3));
+
+    // Another noise for subtle surface undulations
+    let mut undulation_noise = FastNoiseLite::with_seed(seeds.derive("phobos", 2, 7890));
+    undulation_noise.set_noise_type(Some(NoiseType::Perlin));
+    undulation_noise.set_frequency(Some(0.1));
+    undulation_noise.set_fractal_type(Some(FractalType::FBm));
+    undulation_noise.set_fractal_octaves(Some(2));
+
+    vec![crater_noise, texture_noise, undulation_noise]
+}
+
+fn create_saturn_noises(seeds: &SceneSeed) -> Vec<FastNoiseLite> {
+    let mut band_noise = FastNoiseLite::with_seed(seeds.derive("saturn", 0, 12345));
+    band_noise.set_noise_type(Some(NoiseType::OpenSimplex2));
+    band_noise.set_frequency(Some(3.0));
+    band_noise.set_fractal_type(Some(FractalType::FBm));
+    band_noise.set_fractal_octaves(Some(4));
+
+    let mut cloud_noise = FastNoiseLite::with_seed(seeds.derive("saturn", 1, 67890));
+    cloud_noise.set_noise_type(Some(NoiseType::Perlin));
+    cloud_noise.set_frequency(Some(1.5));
+    cloud_noise.set_fractal_type(Some(FractalType::Ridged));
+    cloud_noise.set_fractal_octaves(Some(3));
+
+    vec![band_noise, cloud_noise]
+}
+
+fn create_uranus_noises(seeds: &SceneSeed) -> Vec<FastNoiseLite> {
+    let mut primary_noise = FastNoiseLite::with_seed(seeds.derive("uranus", 0, 1234));
+    primary_noise.set_noise_type(Some(NoiseType::OpenSimplex2));
+    primary_noise.set_frequency(Some(1.5));
+    primary_noise.set_fractal_type(Some(FractalType::FBm));
+    primary_noise.set_fractal_octaves(Some(3));
+
+    let mut secondary_noise = FastNoiseLite::with_seed(seeds.derive("uranus", 1, 5678));
+    secondary_noise.set_noise_type(Some(NoiseType::Perlin));
+    secondary_noise.set_frequency(Some(2.0));
+    secondary_noise.set_fractal_type(Some(FractalType::Ridged));
+    secondary_noise.set_fractal_octaves(Some(2));
+
+    vec![primary_noise, secondary_noise]
+}
+
+fn create_uranus_ring_noises(seeds: &SceneSeed) -> Vec<FastNoiseLite> {
+    let mut ring_noise1 = FastNoiseLite::with_seed(seeds.derive("uranus_ring", 0, 8910));
+    ring_noise1.set_noise_type(Some(NoiseType::Cellular));
+    ring_noise1.set_frequency(Some(0.5));
+    ring_noise1.set_fractal_type(Some(FractalType::FBm));
+    ring_noise1.set_fractal_octaves(Some(2));
+
+    let mut ring_noise2 = FastNoiseLite::with_seed(seeds.derive("uranus_ring", 1, 1112));
+    ring_noise2.set_noise_type(Some(NoiseType::Perlin));
+    ring_noise2.set_frequency(Some(1.0));
+    ring_noise2.set_fractal_type(Some(FractalType::FBm));
+    ring_noise2.set_fractal_octaves(Some(1));
+
+    vec![ring_noise1, ring_noise2]
+}
+
+fn create_neptune_noises(seeds: &SceneSeed) -> Vec<FastNoiseLite> {
+    let mut surface_noise = FastNoiseLite::with_seed(seeds.derive("neptune", 0, 501));
+    surface_noise.set_noise_type(Some(NoiseType::Perlin));
+    surface_noise.set_frequency(Some(0.8));
+    surface_noise.set_fractal_type(Some(FractalType::FBm));
+    surface_noise.set_fractal_octaves(Some(5));
+
+    let mut atmosphere_noise = FastNoiseLite::with_seed(seeds.derive("neptune", 1, 502));
+    atmosphere_noise.set_noise_type(Some(NoiseType::Perlin));
+    atmosphere_noise.set_frequency(Some(1.2));
+    atmosphere_noise.set_fractal_type(Some(FractalType::Ridged));
+    atmosphere_noise.set_fractal_octaves(Some(4));
+
+    vec![surface_noise, atmosphere_noise]
+}
+
+fn create_pluto_noises(seeds: &SceneSeed) -> Vec<FastNoiseLite> {
+    let mut surface_noise = FastNoiseLite::with_seed(seeds.derive("pluto", 0, 601));
+    surface_noise.set_noise_type(Some(NoiseType::Cellular));
+    surface_noise.set_frequency(Some(0.5));
+    surface_noise.set_cellular_distance_function(Some(CellularDistanceFunction::Euclidean));
+
+    let mut ice_noise = FastNoiseLite::with_seed(seeds.derive("pluto", 1, 602));
+    ice_noise.set_noise_type(Some(NoiseType::Perlin));
+    ice_noise.set_frequency(Some(1.0));
+    ice_noise.set_fractal_type(Some(FractalType::FBm));
+    ice_noise.set_fractal_octaves(Some(3));
+
+    vec![surface_noise, ice_noise]
+}
+
+fn create_eris_noises(seeds: &SceneSeed) -> Vec<FastNoiseLite> {
+    let mut surface_noise = FastNoiseLite::with_seed(seeds.derive("eris", 0, 701));
+    surface_noise.set_noise_type(Some(NoiseType::Perlin));
+    surface_noise.set_frequency(Some(0.7));
+    surface_noise.set_fractal_type(Some(FractalType::FBm));
+    surface_noise.set_fractal_octaves(Some(4));
+
+    let mut ice_noise = FastNoiseLite::with_seed(seeds.derive("eris", 1, 702));
+    ice_noise.set_noise_type(Some(NoiseType::Perlin));
+    ice_noise.set_frequency(Some(1.1));
+    ice_noise.set_fractal_type(Some(FractalType::Ridged));
+    ice_noise.set_fractal_octaves(Some(5));
+
+    vec![surface_noise, ice_noise]
+}
+
+fn create_sedna_noises(seeds: &SceneSeed) -> Vec<FastNoiseLite> {
+    let mut surface_noise = FastNoiseLite::with_seed(seeds.derive("sedna", 0, 801));
+    surface_noise.set_noise_type(Some(NoiseType::OpenSimplex2));
+    surface_noise.set_frequency(Some(0.6));
+    surface_noise.set_fractal_type(Some(FractalType::FBm));
+    surface_noise.set_fractal_octaves(Some(3));
+
+    let mut ice_noise = FastNoiseLite::with_seed(seeds.derive("sedna", 1, 802));
+    ice_noise.set_noise_type(Some(NoiseType::Cellular));
+    ice_noise.set_frequency(Some(0.4));
+    ice_noise.set_cellular_distance_function(Some(CellularDistanceFunction::Manhattan));
+
+    vec![surface_noise, ice_noise]
+}
+
+// Malla de cada modelo que piden los cuerpos, recentrada y con radio 1 como la
+// esfera; los archivos se leen a través de `cache`. Si uno falta se avisa y queda
+// en None.
+fn load_body_models<'a>(
+    cache: &mut ObjCache,
+    paths: impl IntoIterator<Item = &'a str>,
+) -> HashMap<String, Option<IndexedMesh>> {
+    let mut models = HashMap::new();
+    for path in paths {
+        models
+            .entry(path.to_string())
+            .or_insert_with(|| match cache.load(path) {
+                Ok(obj) => {
+                    log_info!("loaded model {}", path);
+                    Some(obj.to_indexed_mesh().normalized())
+                }
+                Err(error) => {
+                    log_warn!("could not load {} ({}), using the sphere", path, error);
+                    None
+                }
+            });
+    }
+    models
+}
+
+// Malla de un cuerpo: su modelo si se pudo cargar, si no la esfera
+fn body_mesh<'a>(
+    models: &'a HashMap<String, Option<IndexedMesh>>,
+    model: Option<&str>,
+    sphere: &'a IndexedMesh,
+) -> &'a IndexedMesh {
+    model
+        .and_then(|path| models.get(path))
+        .and_then(Option::as_ref)
+        .unwrap_or(sphere)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solar_system() -> SolarSystem {
+        SolarSystem::new(&SceneSeed::default(), ScenePreset::Solar, 8).unwrap()
+    }
+
+    // Solo Mercurio, en su órbita inicial
+    fn mercury_frame<'a>(cutaway: &'a [bool], focus: Option<Focus>) -> SolarFrame<'a> {
+        SolarFrame {
+            time: 0.0,
+            elapsed_seconds: 0.0,
+            positions: [Vec3::new(10.0, 0.0, 0.0); PLANET_COUNT],
+            sun: BARYCENTER,
+            companion: None,
+            active_bodies: PLANET_COUNT,
+            solo: Some(0),
+            flat_shading: false,
+            ring_shadows: false,
+            saturn_rings: false,
+            focus,
+            cutaway,
+            cutaway_layers: &[],
+            noise_layers: [None; PLANET_COUNT],
+            sandbox: None,
+        }
+    }
+
+    #[test]
+    fn mercury_keeps_its_normal_map_in_every_mode() {
+        let solar = solar_system();
+        let normal = [false; PLANET_COUNT];
+        let mut cutaway = [false; PLANET_COUNT];
+        cutaway[0] = true;
+        let noise_channel = Focus {
+            index: 0,
+            turntable: Mat4::identity(),
+            compare: None,
+            noise_channel: Some(0),
+        };
+
+        for frame in [
+            mercury_frame(&normal, None),
+            mercury_frame(&cutaway, None),
+            mercury_frame(&normal, Some(noise_channel)),
+        ] {
+            let scene = solar.scene(&frame);
+            assert_eq!(scene.bodies.len(), 1);
+            assert!(scene.bodies[0].normal_map.is_some());
+        }
+    }
+
+    #[test]
+    fn reseeding_mercury_rebuilds_its_normal_map() {
+        let mut solar = solar_system();
+        let normals = |solar: &SolarSystem| -> Vec<Vec3> {
+            (0..16)
+                .map(|i| {
+                    let uv = nalgebra_glm::vec2(i as f32 / 16.0, 0.3 + i as f32 / 40.0);
+                    solar.mercury_normal_map.sample_normal(uv)
+                })
+                .collect()
+        };
+        let before = normals(&solar);
+        solar.reseed(0);
+        assert_ne!(normals(&solar), before);
+    }
+}
@@ -1,11 +1,83 @@
-use nalgebra_glm::{Vec3, rotate_vec3};
+use nalgebra_glm::{Mat4, Vec3, rotate_vec3};
 use std::f32::consts::PI;
+use crate::render::create_view_matrix;
+
+// Por encima de este |coseno| entre la vista y `up`, look_at pierde precisión
+// (y en paralelo exacto da NaN), así que se usa otro vector arriba
+const PARALLEL_UP_THRESHOLD: f32 = 0.999;
+
+// Límites del multiplicador de velocidad (teclas '3' y '4')
+const MIN_SPEED_FACTOR: f32 = 0.05;
+const MAX_SPEED_FACTOR: f32 = 50.0;
+const SPEED_FACTOR_STEP: f32 = 1.5;
+// Distancia ojo-centro a la que las velocidades de desplazamiento valen tal cual;
+// más lejos crecen y más cerca se achican en proporción, hasta el mínimo
+const SPEED_REFERENCE_DISTANCE: f32 = 20.0;
+const MIN_DISTANCE_SCALE: f32 = 0.02;
+
+// Velocidades de los controles de la cámara, por cuadro. `factor` multiplica los
+// desplazamientos (movimiento, zoom y paneo); el giro tiene su propia velocidad.
+#[derive(Clone, Copy, Debug)]
+pub struct CameraSpeeds {
+  pub movement: f32,
+  pub fly: f32,
+  pub rotation: f32,
+  pub zoom: f32,
+  pub pan: f32,
+  pub factor: f32,
+}
+
+impl Default for CameraSpeeds {
+  fn default() -> Self {
+    CameraSpeeds {
+      movement: 2.0,
+      fly: 0.5,
+      rotation: PI / 400.0,
+      zoom: 0.05,
+      pan: 0.05,
+      factor: 1.0,
+    }
+  }
+}
+
+impl CameraSpeeds {
+  pub fn faster(&mut self) {
+    self.factor = (self.factor * SPEED_FACTOR_STEP).min(MAX_SPEED_FACTOR);
+  }
+
+  pub fn slower(&mut self) {
+    self.factor = (self.factor / SPEED_FACTOR_STEP).max(MIN_SPEED_FACTOR);
+  }
+
+  // Escala de los desplazamientos (vuelo y zoom) para la cámara: proporcional a la
+  // distancia entre el ojo y el centro, así acercarse a Mercurio y cruzar hasta
+  // Sedna se sienten igual. El mínimo deja mover un poco aun pegado al centro.
+  pub fn distance_scale(&self, camera: &Camera) -> f32 {
+    let distance = (camera.eye - camera.center).magnitude();
+    (distance / SPEED_REFERENCE_DISTANCE).max(MIN_DISTANCE_SCALE)
+  }
+}
 
 pub struct Camera {
   pub eye: Vec3,
   pub center: Vec3,
   pub up: Vec3,
-  pub has_changed: bool
+  pub has_changed: bool,
+  // Distancia fija al objetivo en `track`; None conserva la que haya dejado el zoom
+  pub track_distance: Option<f32>,
+  animation: Option<CameraAnimation>,
+}
+
+// Transición suave entre dos encuadres (ver `Camera::animate_to`)
+struct CameraAnimation {
+  start_eye: Vec3,
+  start_center: Vec3,
+  start_up: Vec3,
+  target_eye: Vec3,
+  target_center: Vec3,
+  target_up: Vec3,
+  duration: f32,
+  elapsed: f32,
 }
 
 impl Camera {
@@ -15,18 +87,143 @@ impl Camera {
       center,
       up,
       has_changed: true,
+      track_distance: None,
+      animation: None,
     }
   }
 
-  pub fn basis_change(&self, vector: &Vec3) -> Vec3 {
-    let forward = (self.center - self.eye).normalize();
-    let right = forward.cross(&self.up).normalize();
-    let up = right.cross(&forward).normalize();
+  // Lleva la cámara al encuadre dado en `duration` segundos (con `update_animation`).
+  // El ojo gira alrededor del centro en lugar de ir en línea recta, así que no
+  // atraviesa lo que se está mirando.
+  pub fn animate_to(&mut self, eye: Vec3, center: Vec3, up: Vec3, duration: f32) {
+    if duration <= 0.0 {
+      self.eye = eye;
+      self.center = center;
+      self.up = up;
+      self.animation = None;
+      self.has_changed = true;
+      return;
+    }
+    self.animation = Some(CameraAnimation {
+      start_eye: self.eye,
+      start_center: self.center,
+      start_up: self.up,
+      target_eye: eye,
+      target_center: center,
+      target_up: up,
+      duration,
+      elapsed: 0.0,
+    });
+  }
+
+  pub fn is_animating(&self) -> bool {
+    self.animation.is_some()
+  }
+
+  // Avanza la transición en curso `delta_time` segundos
+  pub fn update_animation(&mut self, delta_time: f32) {
+    let Some(animation) = &mut self.animation else {
+      return;
+    };
+    animation.elapsed += delta_time;
+    let t = (animation.elapsed / animation.duration).min(1.0);
+    let eased = t * t * (3.0 - 2.0 * t);
+
+    let center = animation.start_center.lerp(&animation.target_center, eased);
+    let start_offset = animation.start_eye - animation.start_center;
+    let target_offset = animation.target_eye - animation.target_center;
+    // Dirección y distancia por separado para rodear el centro
+    let direction = start_offset.normalize().lerp(&target_offset.normalize(), eased);
+    let distance = start_offset.magnitude()
+      + (target_offset.magnitude() - start_offset.magnitude()) * eased;
+    let up = animation.start_up.lerp(&animation.target_up, eased);
+
+    if direction.magnitude() > 1e-4 && up.magnitude() > 1e-4 {
+      self.center = center;
+      self.eye = center + direction.normalize() * distance;
+      self.up = up.normalize();
+    }
+    if t >= 1.0 {
+      self.eye = animation.target_eye;
+      self.center = animation.target_center;
+      self.up = animation.target_up;
+      self.animation = None;
+    }
+    self.has_changed = true;
+  }
+
+  // Desplaza el encuadre completo, incluida una transición en curso
+  // (p. ej. para seguir a un planeta mientras se anima la cámara)
+  pub fn translate(&mut self, offset: Vec3) {
+    self.eye += offset;
+    self.center += offset;
+    if let Some(animation) = &mut self.animation {
+      animation.start_eye += offset;
+      animation.start_center += offset;
+      animation.target_eye += offset;
+      animation.target_center += offset;
+    }
+    self.has_changed = true;
+  }
+
+  // Sigue a `target`: el centro pasa a ser el objetivo y el ojo conserva su
+  // desplazamiento relativo (o se acerca/aleja a `track_distance` en la misma dirección)
+  pub fn track(&mut self, target: Vec3) {
+    self.translate(target - self.center);
+    if let Some(distance) = self.track_distance {
+      let offset = self.eye - self.center;
+      if offset.magnitude() > 1e-6 {
+        self.eye = self.center + offset.normalize() * distance;
+      }
+    }
+  }
+
+  // Apunta la vista a `target` sin mover el ojo
+  pub fn look_at_target(&mut self, target: Vec3) {
+    self.center = target;
+    self.has_changed = true;
+  }
+
+  // Base ortonormal de la vista, derivada de eye/center/up
+  pub fn forward(&self) -> Vec3 {
+    (self.center - self.eye).normalize()
+  }
 
+  pub fn right(&self) -> Vec3 {
+    self.forward().cross(&self.view_up()).normalize()
+  }
+
+  // `up` si sirve para orientar la vista; si es (casi) paralelo a la dirección de
+  // vista, como al mirar justo hacia abajo, un eje del mundo que no lo sea.
+  // Mirando por el eje Y queda -Z hacia arriba en pantalla.
+  pub fn view_up(&self) -> Vec3 {
+    let forward = self.forward();
+    let is_usable =
+      |up: &Vec3| up.magnitude() > 0.0 && forward.dot(&up.normalize()).abs() < PARALLEL_UP_THRESHOLD;
+    if is_usable(&self.up) {
+      return self.up;
+    }
+    [Vec3::new(0.0, 0.0, -1.0), Vec3::new(1.0, 0.0, 0.0)]
+      .into_iter()
+      .find(is_usable)
+      .unwrap_or(Vec3::new(0.0, 1.0, 0.0))
+  }
+
+  // Matriz de vista siempre bien definida (ver `view_up`)
+  pub fn view_matrix(&self) -> Mat4 {
+    create_view_matrix(self.eye, self.center, self.view_up())
+  }
+
+  // Arriba real de la cámara (perpendicular a forward), no necesariamente `self.up`
+  pub fn up(&self) -> Vec3 {
+    self.right().cross(&self.forward()).normalize()
+  }
+
+  pub fn basis_change(&self, vector: &Vec3) -> Vec3 {
     let rotated = 
-    vector.x * right +
-    vector.y * up +
-    - vector.z * forward;
+    vector.x * self.right() +
+    vector.y * self.up() +
+    - vector.z * self.forward();
 
     rotated.normalize()
   }
@@ -75,6 +272,15 @@ impl Camera {
     self.has_changed = true;
   }
 
+  // Desplaza ojo y centro juntos en la base de la vista:
+  // x = derecha, y = arriba, z = adelante (hacia donde se mira)
+  pub fn move_relative(&mut self, offset: Vec3) {
+    let translation = offset.x * self.right() + offset.y * self.up() + offset.z * self.forward();
+    self.eye += translation;
+    self.center += translation;
+    self.has_changed = true;
+  }
+
   pub fn check_if_changed(&mut self) -> bool {
     if self.has_changed {
       self.has_changed = false;
@@ -84,3 +290,110 @@ impl Camera {
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn assert_close(actual: Vec3, expected: Vec3) {
+    assert!(
+      (actual - expected).magnitude() < 1e-4,
+      "expected {:?}, got {:?}", expected, actual
+    );
+  }
+
+  #[test]
+  fn forward_follows_orbit() {
+    let mut camera = Camera::new(
+      Vec3::new(0.0, 0.0, 10.0),
+      Vec3::new(0.0, 0.0, 0.0),
+      Vec3::new(0.0, 1.0, 0.0),
+    );
+    assert_close(camera.forward(), Vec3::new(0.0, 0.0, -1.0));
+
+    camera.orbit(PI / 2.0, 0.0);
+
+    // El ojo pasa a -X, así que ahora se mira hacia +X
+    assert_close(camera.forward(), (camera.center - camera.eye).normalize());
+    assert_close(camera.forward(), Vec3::new(1.0, 0.0, 0.0));
+    assert_close(camera.right(), Vec3::new(0.0, 0.0, 1.0));
+    assert_close(camera.up(), Vec3::new(0.0, 1.0, 0.0));
+
+    // Avanzar mueve ojo y centro a lo largo de la nueva dirección
+    let eye_before = camera.eye;
+    camera.move_relative(Vec3::new(0.0, 0.0, 2.0));
+    assert_close(camera.eye - eye_before, Vec3::new(2.0, 0.0, 0.0));
+    assert_close(camera.center, Vec3::new(2.0, 0.0, 0.0));
+  }
+
+  #[test]
+  fn track_keeps_offset_or_fixed_distance() {
+    let mut camera = Camera::new(
+      Vec3::new(0.0, 3.0, 4.0),
+      Vec3::new(0.0, 0.0, 0.0),
+      Vec3::new(0.0, 1.0, 0.0),
+    );
+    camera.track(Vec3::new(10.0, 0.0, 0.0));
+    assert_close(camera.center, Vec3::new(10.0, 0.0, 0.0));
+    assert_close(camera.eye, Vec3::new(10.0, 3.0, 4.0));
+
+    // Con distancia fija el ojo se mueve sobre la misma dirección
+    camera.track_distance = Some(10.0);
+    camera.track(Vec3::new(10.0, 0.0, 0.0));
+    assert_close(camera.eye, Vec3::new(10.0, 6.0, 8.0));
+
+    // Re-apuntar no mueve el ojo
+    camera.look_at_target(Vec3::zeros());
+    assert_close(camera.eye, Vec3::new(10.0, 6.0, 8.0));
+    assert_close(camera.center, Vec3::zeros());
+  }
+
+  #[test]
+  fn speed_factor_stays_within_limits() {
+    let mut speeds = CameraSpeeds::default();
+    speeds.faster();
+    assert!((speeds.factor - SPEED_FACTOR_STEP).abs() < 1e-6);
+    for _ in 0..100 {
+      speeds.faster();
+    }
+    assert_eq!(speeds.factor, MAX_SPEED_FACTOR);
+    for _ in 0..200 {
+      speeds.slower();
+    }
+    assert_eq!(speeds.factor, MIN_SPEED_FACTOR);
+  }
+
+  #[test]
+  fn distance_scale_grows_with_distance_to_center() {
+    let speeds = CameraSpeeds::default();
+    let camera_at = |distance: f32| Camera::new(
+      Vec3::new(0.0, 0.0, distance),
+      Vec3::zeros(),
+      Vec3::new(0.0, 1.0, 0.0),
+    );
+    let near = speeds.distance_scale(&camera_at(SPEED_REFERENCE_DISTANCE));
+    let far = speeds.distance_scale(&camera_at(SPEED_REFERENCE_DISTANCE * 5.0));
+    assert!((near - 1.0).abs() < 1e-6);
+    assert!((far - 5.0).abs() < 1e-5);
+    assert_eq!(speeds.distance_scale(&camera_at(0.0)), MIN_DISTANCE_SCALE);
+  }
+
+  #[test]
+  fn straight_down_view_matrix_is_finite() {
+    let camera = Camera::new(
+      Vec3::new(0.0, 50.0, 0.0),
+      Vec3::new(0.0, 0.0, 0.0),
+      Vec3::new(0.0, 1.0, 0.0),
+    );
+
+    let view = camera.view_matrix();
+    assert!(view.iter().all(|value| value.is_finite()));
+    assert_close(camera.forward(), Vec3::new(0.0, -1.0, 0.0));
+    assert_close(camera.right(), Vec3::new(1.0, 0.0, 0.0));
+    assert_close(camera.up(), Vec3::new(0.0, 0.0, -1.0));
+
+    // El centro queda en el origen de la vista, a 50 unidades delante
+    let center = view * nalgebra_glm::Vec4::new(0.0, 0.0, 0.0, 1.0);
+    assert_close(center.xyz(), Vec3::new(0.0, 0.0, -50.0));
+  }
+}
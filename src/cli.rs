@@ -0,0 +1,440 @@
+use crate::framebuffer::Rasterizer;
+use crate::log_warn;
+use crate::particle_ring::DEFAULT_SOFT_DISTANCE;
+use crate::postprocess::{
+    DEFAULT_EXPOSURE_RANGE, DEFAULT_EXPOSURE_SPEED, DEFAULT_EXPOSURE_TARGET, DEFAULT_FLARE_GHOSTS,
+    DEFAULT_FLARE_INTENSITY,
+};
+use crate::seed::SceneSeed;
+use std::str::FromStr;
+
+// Ventana de la aplicación. Sin `position` el sistema de ventanas decide dónde
+// abrirla (minifb no expone el tamaño del monitor para centrarla a mano), lo que
+// evita que quede fuera de pantalla en monitores pequeños.
+pub struct WindowConfig {
+    pub title: String,
+    pub width: usize,
+    pub height: usize,
+    pub position: Option<(isize, isize)>,
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        WindowConfig {
+            title: "Sistema Solar con Estelas".to_string(),
+            width: 800,
+            height: 800,
+            position: None,
+        }
+    }
+}
+
+// Escena a cargar: el sistema solar o una variante con dos estrellas que orbitan
+// un baricentro común, cada una con su propia luz de color
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ScenePreset {
+    #[default]
+    Solar,
+    Binary,
+}
+
+impl ScenePreset {
+    // Color de fondo de la escena: negro en el sistema solar y un violeta muy
+    // oscuro en la binaria, como iluminado por las dos estrellas
+    pub fn clear_color(self) -> u32 {
+        match self {
+            ScenePreset::Solar => 0x000000,
+            ScenePreset::Binary => 0x06040c,
+        }
+    }
+}
+
+// Distancia en pantalla, en píxeles, bajo la cual se marcan dos cuerpos ('H')
+pub const DEFAULT_PROXIMITY_THRESHOLD: f32 = 40.0;
+pub const DEFAULT_PROXIMITY_COLOR: u32 = 0xFF5050;
+
+// Opciones de línea de comandos
+pub struct CliOptions {
+    pub scene_seed: SceneSeed,
+    // Divisiones de polo a polo de la esfera generada (alrededor se usa el doble)
+    pub sphere_stacks: u32,
+    // Anillo de partículas de Saturno: cantidad y bordes en radios de Saturno
+    pub ring_particles: usize,
+    pub ring_inner: f32,
+    pub ring_outer: f32,
+    // Distancia entre los ojos en el modo anaglifo, en unidades de la escena
+    pub eye_separation: f32,
+    // Peso de los cuadros anteriores en el desenfoque de movimiento (0..1)
+    pub motion_blur_decay: f32,
+    // Límite de cuadros por segundo; None dibuja tan rápido como se pueda
+    pub fps_cap: Option<f32>,
+    // Distancia fija de la cámara al planeta enfocado; None conserva el zoom del usuario
+    pub follow_distance: Option<f32>,
+    // Giro del planeta enfocado con la tecla 'Inicio', en radianes por segundo real
+    pub turntable_speed: f32,
+    // Media longitud de los ejes de coordenadas ('Retroceso'); None llega hasta
+    // las órbitas más externas
+    pub axes_length: Option<f32>,
+    // Multiplicadores iniciales de la velocidad de desplazamiento y de giro de la cámara
+    pub camera_speed: f32,
+    pub rotation_speed: f32,
+    // Lado en píxeles de la ventana del minimapa (tecla '5')
+    pub minimap_size: usize,
+    // Semilla del sistema generado con el que arranca el modo exploración ('F6');
+    // None arranca con el sistema solar
+    pub sandbox_seed: Option<u64>,
+    // Distancia en la que las partículas del anillo se desvanecen contra la
+    // geometría (partículas suaves); 0 las corta en seco
+    pub particle_soft_distance: f32,
+    // Instante simulado inicial en segundos de simulación (a velocidad normal);
+    // None arranca en 0 con las estelas vacías
+    pub start_time: Option<f32>,
+    // Mantener la simulación detenida en el instante inicial
+    pub hold_time: bool,
+    // Cuántos cuerpos en órbita dibujar, de adentro hacia afuera; None dibuja todos
+    pub max_bodies: Option<usize>,
+    // Puntos de estela en total, repartidos entre los cuerpos según su período;
+    // None usa el largo ajustado de cada planeta
+    pub trail_budget: Option<usize>,
+    // Fantasmas del destello de lente ('F11') y brillo del más intenso
+    pub flare_ghosts: usize,
+    pub flare_intensity: f32,
+    // Resaltado de conjunciones ('H'): distancia en píxeles y color de los anillos
+    pub proximity_threshold: f32,
+    pub proximity_color: u32,
+    // Exposición automática ('Fin'): luminancia media buscada, rapidez de
+    // adaptación por segundo y límites de la exposición
+    pub exposure_target: f32,
+    pub exposure_speed: f32,
+    pub exposure_range: (f32, f32),
+    pub scene: ScenePreset,
+    // Color de fondo en hexadecimal; None usa el de la escena
+    pub clear_color: Option<u32>,
+    // Capturas en PNG con fondo transparente desde el inicio (tecla 'Supr')
+    pub transparent_screenshots: bool,
+    pub rasterizer: Rasterizer,
+    pub window: WindowConfig,
+}
+
+impl CliOptions {
+    pub fn parse() -> Self {
+        Self::from_args(std::env::args().skip(1))
+    }
+
+    pub fn from_args<I: Iterator<Item = String>>(mut args: I) -> Self {
+        let mut options = CliOptions {
+            scene_seed: SceneSeed::default(),
+            sphere_stacks: 16,
+            ring_particles: 6000,
+            ring_inner: 1.2,
+            ring_outer: 2.3,
+            eye_separation: 0.6,
+            motion_blur_decay: 0.8,
+            fps_cap: Some(60.0),
+            follow_distance: None,
+            turntable_speed: 0.4,
+            axes_length: None,
+            camera_speed: 1.0,
+            rotation_speed: 1.0,
+            minimap_size: 160,
+            sandbox_seed: None,
+            particle_soft_distance: DEFAULT_SOFT_DISTANCE,
+            start_time: None,
+            hold_time: false,
+            max_bodies: None,
+            trail_budget: None,
+            flare_ghosts: DEFAULT_FLARE_GHOSTS,
+            flare_intensity: DEFAULT_FLARE_INTENSITY,
+            proximity_threshold: DEFAULT_PROXIMITY_THRESHOLD,
+            proximity_color: DEFAULT_PROXIMITY_COLOR,
+            exposure_target: DEFAULT_EXPOSURE_TARGET,
+            exposure_speed: DEFAULT_EXPOSURE_SPEED,
+            exposure_range: DEFAULT_EXPOSURE_RANGE,
+            scene: ScenePreset::default(),
+            clear_color: None,
+            transparent_screenshots: false,
+            rasterizer: Rasterizer::default(),
+            window: WindowConfig::default(),
+        };
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                // --seed <número> o --seed hand-tuned
+                "--seed" => match args.next().as_deref() {
+                    Some("hand-tuned") => options.scene_seed = SceneSeed::HandTuned,
+                    Some(value) => match value.parse::<u64>() {
+                        Ok(seed) => options.scene_seed = SceneSeed::Master(seed),
+                        Err(_) => {
+                            log_warn!("Invalid --seed value '{}', using hand-tuned seeds", value)
+                        }
+                    },
+                    None => log_warn!("Missing value for --seed"),
+                },
+                // --sphere-detail <divisiones>, mínimo 2
+                "--sphere-detail" => match args.next().map(|value| value.parse::<u32>()) {
+                    Some(Ok(stacks)) if stacks >= 2 => options.sphere_stacks = stacks,
+                    Some(_) => log_warn!("Invalid --sphere-detail value, using 16"),
+                    None => log_warn!("Missing value for --sphere-detail"),
+                },
+                // --ring-particles <cantidad>
+                "--ring-particles" => match args.next().map(|value| value.parse::<usize>()) {
+                    Some(Ok(count)) => options.ring_particles = count,
+                    Some(Err(_)) => log_warn!("Invalid --ring-particles value, using 6000"),
+                    None => log_warn!("Missing value for --ring-particles"),
+                },
+                // --ring-bounds <interior>,<exterior>, en radios de Saturno
+                "--ring-bounds" => match args.next() {
+                    Some(value) => match parse_bounds(&value) {
+                        Some((inner, outer)) => {
+                            options.ring_inner = inner;
+                            options.ring_outer = outer;
+                        }
+                        None => log_warn!(
+                            "Invalid --ring-bounds value '{}', expected <inner>,<outer> with 0 < inner < outer",
+                            value
+                        ),
+                    },
+                    None => log_warn!("Missing value for --ring-bounds"),
+                },
+                // --particle-soft-distance <distancia>, 0 para desactivar
+                "--particle-soft-distance" => match args.next().map(|value| value.parse::<f32>()) {
+                    Some(Ok(distance)) if distance >= 0.0 => {
+                        options.particle_soft_distance = distance
+                    }
+                    Some(_) => log_warn!(
+                        "Invalid --particle-soft-distance value, using {}",
+                        DEFAULT_SOFT_DISTANCE
+                    ),
+                    None => log_warn!("Missing value for --particle-soft-distance"),
+                },
+                // --time <segundos>
+                "--time" => match args.next().map(|value| value.parse::<f32>()) {
+                    Some(Ok(seconds)) if seconds.is_finite() => {
+                        options.start_time = Some(seconds)
+                    }
+                    Some(_) => log_warn!("Invalid --time value, starting at 0"),
+                    None => log_warn!("Missing value for --time"),
+                },
+                "--hold-time" => options.hold_time = true,
+                // --max-bodies <cantidad>
+                "--max-bodies" => match args.next().map(|value| value.parse::<usize>()) {
+                    Some(Ok(count)) => options.max_bodies = Some(count),
+                    Some(Err(_)) => log_warn!("Invalid --max-bodies value, rendering every body"),
+                    None => log_warn!("Missing value for --max-bodies"),
+                },
+                // --trail-budget <puntos>
+                "--trail-budget" => match args.next().map(|value| value.parse::<usize>()) {
+                    Some(Ok(points)) if points > 0 => options.trail_budget = Some(points),
+                    Some(_) => log_warn!("Invalid --trail-budget value, using per-planet lengths"),
+                    None => log_warn!("Missing value for --trail-budget"),
+                },
+                // --flare-ghosts <cantidad>
+                "--flare-ghosts" => match args.next().map(|value| value.parse::<usize>()) {
+                    Some(Ok(count)) => options.flare_ghosts = count,
+                    Some(Err(_)) => log_warn!(
+                        "Invalid --flare-ghosts value, using {}",
+                        DEFAULT_FLARE_GHOSTS
+                    ),
+                    None => log_warn!("Missing value for --flare-ghosts"),
+                },
+                // --flare-intensity <brillo>
+                "--flare-intensity" => match args.next().map(|value| value.parse::<f32>()) {
+                    Some(Ok(intensity)) if intensity >= 0.0 => options.flare_intensity = intensity,
+                    Some(_) => log_warn!(
+                        "Invalid --flare-intensity value, using {}",
+                        DEFAULT_FLARE_INTENSITY
+                    ),
+                    None => log_warn!("Missing value for --flare-intensity"),
+                },
+                // --proximity-threshold <píxeles>
+                "--proximity-threshold" => match args.next().map(|value| value.parse::<f32>()) {
+                    Some(Ok(threshold)) if threshold > 0.0 => {
+                        options.proximity_threshold = threshold
+                    }
+                    Some(_) => log_warn!(
+                        "Invalid --proximity-threshold value, using {}",
+                        DEFAULT_PROXIMITY_THRESHOLD
+                    ),
+                    None => log_warn!("Missing value for --proximity-threshold"),
+                },
+                // --proximity-color <RRGGBB>, con o sin '#'
+                "--proximity-color" => match args.next() {
+                    Some(value) => match parse_hex_color(&value) {
+                        Some(color) => options.proximity_color = color,
+                        None => log_warn!(
+                            "Invalid --proximity-color value '{}', expected RRGGBB",
+                            value
+                        ),
+                    },
+                    None => log_warn!("Missing value for --proximity-color"),
+                },
+                // --exposure-target <luminancia 0..1>
+                "--exposure-target" => match args.next().map(|value| value.parse::<f32>()) {
+                    Some(Ok(target)) if target > 0.0 && target <= 1.0 => {
+                        options.exposure_target = target
+                    }
+                    Some(_) => log_warn!(
+                        "Invalid --exposure-target value, using {}",
+                        DEFAULT_EXPOSURE_TARGET
+                    ),
+                    None => log_warn!("Missing value for --exposure-target"),
+                },
+                // --exposure-speed <por segundo>
+                "--exposure-speed" => match args.next().map(|value| value.parse::<f32>()) {
+                    Some(Ok(speed)) if speed > 0.0 => options.exposure_speed = speed,
+                    Some(_) => log_warn!(
+                        "Invalid --exposure-speed value, using {}",
+                        DEFAULT_EXPOSURE_SPEED
+                    ),
+                    None => log_warn!("Missing value for --exposure-speed"),
+                },
+                // --exposure-range <mínimo>,<máximo>
+                "--exposure-range" => match args.next().as_deref().map(parse_pair::<f32>) {
+                    Some(Some((min, max))) if min > 0.0 && min <= max => {
+                        options.exposure_range = (min, max)
+                    }
+                    Some(_) => log_warn!("Invalid --exposure-range value, expected <min>,<max>"),
+                    None => log_warn!("Missing value for --exposure-range"),
+                },
+                // --turntable-speed <radianes por segundo>, negativo gira al revés
+                "--turntable-speed" => match args.next().map(|value| value.parse::<f32>()) {
+                    Some(Ok(speed)) if speed.is_finite() => options.turntable_speed = speed,
+                    Some(_) => log_warn!("Invalid --turntable-speed value, using 0.4"),
+                    None => log_warn!("Missing value for --turntable-speed"),
+                },
+                // --axes-length <distancia>
+                "--axes-length" => match args.next().map(|value| value.parse::<f32>()) {
+                    Some(Ok(length)) if length > 0.0 => options.axes_length = Some(length),
+                    Some(_) => log_warn!("Invalid --axes-length value, reaching the outer orbits"),
+                    None => log_warn!("Missing value for --axes-length"),
+                },
+                // --eye-separation <distancia>
+                "--eye-separation" => match args.next().map(|value| value.parse::<f32>()) {
+                    Some(Ok(separation)) if separation >= 0.0 => options.eye_separation = separation,
+                    Some(_) => log_warn!("Invalid --eye-separation value, using 0.6"),
+                    None => log_warn!("Missing value for --eye-separation"),
+                },
+                // --motion-blur-decay <0..1>
+                "--motion-blur-decay" => match args.next().map(|value| value.parse::<f32>()) {
+                    Some(Ok(decay)) if (0.0..1.0).contains(&decay) => {
+                        options.motion_blur_decay = decay
+                    }
+                    Some(_) => log_warn!("Invalid --motion-blur-decay value, using 0.8"),
+                    None => log_warn!("Missing value for --motion-blur-decay"),
+                },
+                // --fps-cap <fps>, 0 para desactivar el límite
+                "--fps-cap" => match args.next().map(|value| value.parse::<f32>()) {
+                    Some(Ok(0.0)) => options.fps_cap = None,
+                    Some(Ok(fps)) if fps > 0.0 => options.fps_cap = Some(fps),
+                    Some(_) => log_warn!("Invalid --fps-cap value, using 60"),
+                    None => log_warn!("Missing value for --fps-cap"),
+                },
+                // --follow-distance <distancia>, 0 para conservar el zoom
+                "--follow-distance" => match args.next().map(|value| value.parse::<f32>()) {
+                    Some(Ok(0.0)) => options.follow_distance = None,
+                    Some(Ok(distance)) if distance > 0.0 => {
+                        options.follow_distance = Some(distance)
+                    }
+                    Some(_) => log_warn!("Invalid --follow-distance value, keeping the zoom"),
+                    None => log_warn!("Missing value for --follow-distance"),
+                },
+                // --camera-speed <factor>
+                "--camera-speed" => match args.next().map(|value| value.parse::<f32>()) {
+                    Some(Ok(factor)) if factor > 0.0 => options.camera_speed = factor,
+                    Some(_) => log_warn!("Invalid --camera-speed value, using 1"),
+                    None => log_warn!("Missing value for --camera-speed"),
+                },
+                // --rotation-speed <factor>
+                "--rotation-speed" => match args.next().map(|value| value.parse::<f32>()) {
+                    Some(Ok(factor)) if factor > 0.0 => options.rotation_speed = factor,
+                    Some(_) => log_warn!("Invalid --rotation-speed value, using 1"),
+                    None => log_warn!("Missing value for --rotation-speed"),
+                },
+                // --minimap-size <píxeles>
+                "--minimap-size" => match args.next().map(|value| value.parse::<usize>()) {
+                    Some(Ok(size)) if size > 0 => options.minimap_size = size,
+                    Some(_) => log_warn!("Invalid --minimap-size value, using 160"),
+                    None => log_warn!("Missing value for --minimap-size"),
+                },
+                // --sandbox <semilla>
+                "--sandbox" => match args.next().map(|value| value.parse::<u64>()) {
+                    Some(Ok(seed)) => options.sandbox_seed = Some(seed),
+                    Some(Err(_)) => log_warn!("Invalid --sandbox value, starting in the solar system"),
+                    None => log_warn!("Missing value for --sandbox"),
+                },
+                // --scene solar o --scene binary
+                "--scene" => match args.next().as_deref() {
+                    Some("solar") => options.scene = ScenePreset::Solar,
+                    Some("binary") => options.scene = ScenePreset::Binary,
+                    Some(value) => log_warn!(
+                        "Invalid --scene value '{}', expected solar or binary",
+                        value
+                    ),
+                    None => log_warn!("Missing value for --scene"),
+                },
+                // --rasterizer tiled o --rasterizer simple
+                "--rasterizer" => match args.next().as_deref() {
+                    Some("tiled") => options.rasterizer = Rasterizer::Tiled,
+                    Some("simple") => options.rasterizer = Rasterizer::Simple,
+                    Some(value) => log_warn!(
+                        "Invalid --rasterizer value '{}', expected tiled or simple",
+                        value
+                    ),
+                    None => log_warn!("Missing value for --rasterizer"),
+                },
+                // --clear-color <RRGGBB>, con o sin '#'
+                "--clear-color" => match args.next() {
+                    Some(value) => match parse_hex_color(&value) {
+                        Some(color) => options.clear_color = Some(color),
+                        None => log_warn!(
+                            "Invalid --clear-color value '{}', expected RRGGBB",
+                            value
+                        ),
+                    },
+                    None => log_warn!("Missing value for --clear-color"),
+                },
+                "--transparent-screenshots" => options.transparent_screenshots = true,
+                // --title <texto>
+                "--title" => match args.next() {
+                    Some(title) => options.window.title = title,
+                    None => log_warn!("Missing value for --title"),
+                },
+                // --window-size <ancho>,<alto>
+                "--window-size" => match args.next().as_deref().map(parse_pair::<usize>) {
+                    Some(Some((width, height))) if width > 0 && height > 0 => {
+                        options.window.width = width;
+                        options.window.height = height;
+                    }
+                    Some(_) => log_warn!("Invalid --window-size value, expected <width>,<height>"),
+                    None => log_warn!("Missing value for --window-size"),
+                },
+                // --window-pos <x>,<y>
+                "--window-pos" => match args.next().as_deref().map(parse_pair::<isize>) {
+                    Some(Some(position)) => options.window.position = Some(position),
+                    Some(None) => log_warn!("Invalid --window-pos value, expected <x>,<y>"),
+                    None => log_warn!("Missing value for --window-pos"),
+                },
+                other => log_warn!("Ignoring unknown argument '{}'", other),
+            }
+        }
+
+        options
+    }
+}
+
+// Color RRGGBB en hexadecimal, con o sin '#'
+fn parse_hex_color(value: &str) -> Option<u32> {
+    u32::from_str_radix(value.trim_start_matches('#'), 16)
+        .ok()
+        .filter(|&color| color <= 0xFFFFFF)
+}
+
+fn parse_pair<T: FromStr>(value: &str) -> Option<(T, T)> {
+    let (first, second) = value.split_once(',')?;
+    Some((first.trim().parse().ok()?, second.trim().parse().ok()?))
+}
+
+fn parse_bounds(value: &str) -> Option<(f32, f32)> {
+    let (inner, outer) = parse_pair::<f32>(value)?;
+    (inner > 0.0 && inner < outer).then_some((inner, outer))
+}
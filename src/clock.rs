@@ -0,0 +1,61 @@
+// clock.rs
+// Fuente del tiempo real que mueve la simulación. La ventana usa el reloj del
+// sistema; las pruebas usan uno manual para avanzar el tiempo a voluntad.
+
+use std::time::Instant;
+
+pub trait Clock {
+    // Segundos reales transcurridos desde la llamada anterior (en la primera,
+    // desde que se creó el reloj)
+    fn delta_time(&mut self) -> f32;
+}
+
+pub struct SystemClock {
+    previous: Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> Self {
+        SystemClock {
+            previous: Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn delta_time(&mut self) -> f32 {
+        let now = Instant::now();
+        let delta = (now - self.previous).as_secs_f32();
+        self.previous = now;
+        delta
+    }
+}
+
+// Reloj que solo avanza cuando se le pide: cada `delta_time` devuelve lo avanzado
+// desde la lectura anterior
+#[derive(Default)]
+pub struct ManualClock {
+    pending: f32,
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn advance(&mut self, seconds: f32) {
+        self.pending += seconds;
+    }
+}
+
+impl Clock for ManualClock {
+    fn delta_time(&mut self) -> f32 {
+        std::mem::take(&mut self.pending)
+    }
+}
@@ -1,3 +1,4 @@
+use nalgebra_glm::Vec3;
 use std::fmt;
 
 #[derive(Debug, Clone, Copy)]
@@ -18,6 +19,14 @@ impl Color {
         Color { r: 0, g: 0, b: 0 }
     }
 
+    pub fn white() -> Self {
+        Color {
+            r: 255,
+            g: 255,
+            b: 255,
+        }
+    }
+
     // New constructor to initialize the color using r, g, b values as f32 (0.0 to 1.0)
     pub fn from_float(r: f32, g: f32, b: f32) -> Self {
         Color {
@@ -102,6 +111,36 @@ impl Color {
             b: self.b.min(255).max(0),
         }
     }
+
+    // Intensidades lineales (0..1) de los canales, que se guardan con la curva sRGB
+    pub fn to_linear(&self) -> Vec3 {
+        let channel = |value: u8| srgb_to_linear(value as f32 / 255.0);
+        Vec3::new(channel(self.r), channel(self.g), channel(self.b))
+    }
+
+    pub fn from_linear(linear: Vec3) -> Self {
+        let channel = |value: f32| (linear_to_srgb(value) * 255.0).round() as u8;
+        Color::new(channel(linear.x), channel(linear.y), channel(linear.z))
+    }
+}
+
+// Curva de transferencia sRGB: de un canal codificado (0..1) a intensidad lineal
+pub fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+// Inversa de `srgb_to_linear`; recorta a [0, 1]
+pub fn linear_to_srgb(value: f32) -> f32 {
+    let value = value.clamp(0.0, 1.0);
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
 }
 
 // Implement addition for Color
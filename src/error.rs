@@ -0,0 +1,57 @@
+// error.rs
+// Errores que detienen la aplicación. `main` los muestra en una línea (más una
+// sugerencia si la hay) en lugar de abortar con un backtrace.
+
+use std::fmt;
+
+#[derive(Debug)]
+pub enum RenderError {
+    // No se pudo abrir la ventana, típicamente porque no hay pantalla
+    Window(minifb::Error),
+    // Falló la presentación de un cuadro en la ventana
+    Present(minifb::Error),
+    // No se pudo cargar un modelo OBJ
+    Model(tobj::LoadError),
+}
+
+impl RenderError {
+    // Qué probar para resolverlo, si se sabe
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            RenderError::Window(_) if !display_available() => Some(
+                "No display found (DISPLAY and WAYLAND_DISPLAY are unset). To run headless, \
+                 start a virtual display, e.g. `xvfb-run cargo run --release`.",
+            ),
+            RenderError::Window(_) | RenderError::Present(_) => {
+                Some("Check that a graphical session is running and reachable from this terminal.")
+            }
+            RenderError::Model(_) => {
+                Some("Run from the project root so the assets/ folder can be found.")
+            }
+        }
+    }
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RenderError::Window(error) => write!(f, "could not open the window: {}", error),
+            RenderError::Present(error) => write!(f, "could not present the frame: {}", error),
+            RenderError::Model(error) => write!(f, "could not load a model: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for RenderError {}
+
+// En Linux sin X11 ni Wayland no hay dónde abrir la ventana; en los demás
+// sistemas se supone que sí
+fn display_available() -> bool {
+    if cfg!(target_os = "linux") {
+        ["DISPLAY", "WAYLAND_DISPLAY"]
+            .iter()
+            .any(|name| std::env::var_os(name).is_some_and(|value| !value.is_empty()))
+    } else {
+        true
+    }
+}
@@ -0,0 +1,47 @@
+// events.rs
+// Avisos para quien incruste el renderizador en otra aplicación. Cada evento
+// guarda a lo sumo un callback; sin callback, avisar es solo revisar un `None`.
+//
+// Eventos de `Renderer`:
+// - `on_select`: cambia la selección con `Renderer::select` (None al soltarla). El
+//   índice lo define la aplicación; el binario usa el índice del planeta enfocado.
+//   No se avisa si se vuelve a elegir el mismo.
+// - `on_frame`: el cuadro quedó listo, al final de `Renderer::render_frame` o en
+//   `Renderer::finish_frame` (tras lo que la aplicación dibuje encima); recibe el
+//   framebuffer completo y el instante dibujado.
+//
+// Todavía no hay un recorrido guiado, así que no existe un evento de cambio de paso.
+
+use crate::framebuffer::Framebuffer;
+
+pub type SelectCallback = Box<dyn FnMut(Option<usize>)>;
+pub type FrameCallback = Box<dyn FnMut(&Framebuffer, f32)>;
+
+#[derive(Default)]
+pub struct Events {
+    select: Option<SelectCallback>,
+    frame: Option<FrameCallback>,
+}
+
+impl Events {
+    // Reemplaza el callback anterior del mismo evento, si lo había
+    pub fn on_select(&mut self, callback: SelectCallback) {
+        self.select = Some(callback);
+    }
+
+    pub fn on_frame(&mut self, callback: FrameCallback) {
+        self.frame = Some(callback);
+    }
+
+    pub fn selected(&mut self, index: Option<usize>) {
+        if let Some(callback) = &mut self.select {
+            callback(index);
+        }
+    }
+
+    pub fn frame_rendered(&mut self, framebuffer: &Framebuffer, time: f32) {
+        if let Some(callback) = &mut self.frame {
+            callback(framebuffer, time);
+        }
+    }
+}
@@ -8,6 +8,9 @@ pub struct Fragment {
     pub normal: Vec3,
     pub intensity: f32,
     pub vertex_position: Vec3,
+    // Interpolados de los vértices; los usan los shaders con mapa de normales
+    pub tex_coords: Vec2,
+    pub tangent: Vec3,
 }
 
 impl Fragment {
@@ -26,6 +29,8 @@ impl Fragment {
             normal,
             intensity,
             vertex_position,
+            tex_coords: Vec2::zeros(),
+            tangent: Vec3::zeros(),
         }
     }
 }
@@ -1,12 +1,60 @@
 // framebuffer.rs
 
+use crate::color::Color;
+use crate::triangle::FillRule;
+use nalgebra_glm::Vec3;
+
+// Buffers geométricos opcionales para efectos en espacio de pantalla
+// (contornos, SSAO, reiluminación diferida). Cuestan 24 bytes por píxel
+// (~14.6 MB a 800x800), por eso solo existen cuando se habilitan.
+struct GBuffer {
+    normals: Vec<Vec3>,
+    world_positions: Vec<Vec3>,
+}
+
+impl GBuffer {
+    fn new(size: usize) -> Self {
+        GBuffer {
+            normals: vec![Vec3::zeros(); size],
+            world_positions: vec![Vec3::zeros(); size],
+        }
+    }
+
+    fn clear(&mut self) {
+        for normal in self.normals.iter_mut() {
+            *normal = Vec3::zeros();
+        }
+        for position in self.world_positions.iter_mut() {
+            *position = Vec3::zeros();
+        }
+    }
+}
+
+// Cómo `render` y `render_indexed` recorren los triángulos. Ambos dan la misma
+// imagen. El simple es el de por defecto: el de bloques paga hilos y copias por
+// cada llamada, y solo conviene con varios núcleos y cuerpos grandes en pantalla.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Rasterizer {
+    // Triángulo por triángulo sobre todo el framebuffer
+    #[default]
+    Simple,
+    // Los triángulos se reparten en bloques de pantalla que se dibujan en paralelo
+    Tiled,
+}
+
 pub struct Framebuffer {
     pub width: usize,
     pub height: usize,
     pub buffer: Vec<u32>,
     pub zbuffer: Vec<f32>,
+    gbuffer: Option<GBuffer>,
     background_color: u32,
     current_color: u32,
+    // Rectángulo (x, y, ancho, alto) fuera del cual no se escribe nada; ya
+    // recortado a los límites del framebuffer
+    scissor: Option<(usize, usize, usize, usize)>,
+    rasterizer: Rasterizer,
+    fill_rule: FillRule,
 }
 
 impl Framebuffer {
@@ -16,8 +64,12 @@ impl Framebuffer {
             height,
             buffer: vec![0; width * height],
             zbuffer: vec![f32::INFINITY; width * height],
+            gbuffer: None,
             background_color: 0x000000,
             current_color: 0xFFFFFF,
+            scissor: None,
+            rasterizer: Rasterizer::default(),
+            fill_rule: FillRule::default(),
         }
     }
 
@@ -28,10 +80,155 @@ impl Framebuffer {
         for depth in self.zbuffer.iter_mut() {
             *depth = f32::INFINITY;
         }
+        if let Some(gbuffer) = self.gbuffer.as_mut() {
+            gbuffer.clear();
+        }
+    }
+
+    // Cobertura de la geometría: true donde se escribió profundidad desde el último
+    // `clear` (lo que nadie tocó conserva la profundidad infinita del fondo)
+    pub fn coverage(&self) -> Vec<bool> {
+        self.zbuffer
+            .iter()
+            .map(|&depth| depth < f32::INFINITY)
+            .collect()
+    }
+
+    pub fn set_gbuffer_enabled(&mut self, enabled: bool) {
+        if enabled && self.gbuffer.is_none() {
+            self.gbuffer = Some(GBuffer::new(self.width * self.height));
+        } else if !enabled {
+            self.gbuffer = None;
+        }
+    }
+
+    // Limita todas las escrituras (geometría, puntos, líneas y HUD) al rectángulo
+    // dado; None vuelve a permitir todo el framebuffer
+    pub fn set_scissor(&mut self, scissor: Option<(usize, usize, usize, usize)>) {
+        self.scissor = scissor.map(|(x, y, width, height)| {
+            let x = x.min(self.width);
+            let y = y.min(self.height);
+            (x, y, width.min(self.width - x), height.min(self.height - y))
+        });
+    }
+
+    pub fn scissor(&self) -> Option<(usize, usize, usize, usize)> {
+        self.scissor
+    }
+
+    fn in_scissor(&self, x: usize, y: usize) -> bool {
+        match self.scissor {
+            Some((sx, sy, width, height)) => {
+                x >= sx && y >= sy && x < sx + width && y < sy + height
+            }
+            None => true,
+        }
+    }
+
+    pub fn set_rasterizer(&mut self, rasterizer: Rasterizer) {
+        self.rasterizer = rasterizer;
+    }
+
+    pub fn rasterizer(&self) -> Rasterizer {
+        self.rasterizer
+    }
+
+    // Regla de relleno de los triángulos que se dibujen a partir de ahora
+    pub fn set_fill_rule(&mut self, fill_rule: FillRule) {
+        self.fill_rule = fill_rule;
+    }
+
+    pub fn fill_rule(&self) -> FillRule {
+        self.fill_rule
+    }
+
+    // Copia del rectángulo (x, y, ancho, alto) como framebuffer propio, con el
+    // recorte trasladado a sus coordenadas; se dibuja aparte y vuelve con
+    // `write_region`. El rectángulo debe estar dentro del framebuffer.
+    pub(crate) fn region(&self, x: usize, y: usize, width: usize, height: usize) -> Framebuffer {
+        let rect = (x, y, width, height);
+        // Intersección del recorte con la región; vacía si no se tocan
+        let scissor = self.scissor.map(|(sx, sy, sw, sh)| {
+            let (left, top) = (sx.max(x), sy.max(y));
+            let right = (sx + sw).min(x + width).max(left);
+            let bottom = (sy + sh).min(y + height).max(top);
+            (left - x, top - y, right - left, bottom - top)
+        });
+        Framebuffer {
+            width,
+            height,
+            buffer: copy_rect(&self.buffer, self.width, rect),
+            zbuffer: copy_rect(&self.zbuffer, self.width, rect),
+            gbuffer: self.gbuffer.as_ref().map(|gbuffer| GBuffer {
+                normals: copy_rect(&gbuffer.normals, self.width, rect),
+                world_positions: copy_rect(&gbuffer.world_positions, self.width, rect),
+            }),
+            background_color: self.background_color,
+            current_color: self.current_color,
+            scissor,
+            rasterizer: self.rasterizer,
+            fill_rule: self.fill_rule,
+        }
+    }
+
+    pub(crate) fn write_region(&mut self, region: &Framebuffer, x: usize, y: usize) {
+        for row in 0..region.height {
+            let source = row * region.width..(row + 1) * region.width;
+            let start = (y + row) * self.width + x;
+            let target = start..start + region.width;
+            self.buffer[target.clone()].copy_from_slice(&region.buffer[source.clone()]);
+            self.zbuffer[target.clone()].copy_from_slice(&region.zbuffer[source.clone()]);
+            if let (Some(gbuffer), Some(region_gbuffer)) = (&mut self.gbuffer, &region.gbuffer) {
+                gbuffer.normals[target.clone()]
+                    .copy_from_slice(&region_gbuffer.normals[source.clone()]);
+                gbuffer.world_positions[target]
+                    .copy_from_slice(&region_gbuffer.world_positions[source]);
+            }
+        }
+    }
+
+    pub fn has_gbuffer(&self) -> bool {
+        self.gbuffer.is_some()
+    }
+
+    // Normal en espacio mundo del píxel; None si no hay G-buffer o el píxel está vacío
+    pub fn normal_at(&self, x: usize, y: usize) -> Option<Vec3> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let normal = self.gbuffer.as_ref()?.normals[y * self.width + x];
+        (normal.magnitude_squared() > 0.0).then_some(normal)
+    }
+
+    pub fn world_position_at(&self, x: usize, y: usize) -> Option<Vec3> {
+        self.normal_at(x, y)?;
+        Some(self.gbuffer.as_ref()?.world_positions[y * self.width + x])
+    }
+
+    // Igual que `point`, pero también escribe el G-buffer si está activo
+    pub fn point_with_attributes(
+        &mut self,
+        x: usize,
+        y: usize,
+        depth: f32,
+        normal: Vec3,
+        world_position: Vec3,
+    ) {
+        if x < self.width && y < self.height && self.in_scissor(x, y) {
+            let index = y * self.width + x;
+            if self.zbuffer[index] > depth {
+                self.buffer[index] = self.current_color;
+                self.zbuffer[index] = depth;
+                if let Some(gbuffer) = self.gbuffer.as_mut() {
+                    gbuffer.normals[index] = normal;
+                    gbuffer.world_positions[index] = world_position;
+                }
+            }
+        }
     }
 
     pub fn point(&mut self, x: usize, y: usize, depth: f32) {
-        if x < self.width && y < self.height {
+        if x < self.width && y < self.height && self.in_scissor(x, y) {
             let index = y * self.width + x;
             if self.zbuffer[index] > depth {
                 self.buffer[index] = self.current_color;
@@ -40,6 +237,63 @@ impl Framebuffer {
         }
     }
 
+    // Mezcla el color actual con el existente según la cobertura (alpha)
+    pub fn blend_point(&mut self, x: usize, y: usize, depth: f32, alpha: f32) {
+        if x < self.width && y < self.height && alpha > 0.0 && self.in_scissor(x, y) {
+            let index = y * self.width + x;
+            if self.zbuffer[index] > depth {
+                let existing = Color::from_hex(self.buffer[index]);
+                let current = Color::from_hex(self.current_color);
+                self.buffer[index] = existing.lerp(&current, alpha).to_hex();
+                self.zbuffer[index] = depth;
+            }
+        }
+    }
+
+    // Escritura directa para superposiciones (HUD): no consulta ni modifica el zbuffer
+    pub fn set_pixel(&mut self, x: usize, y: usize, color: u32) {
+        if x < self.width && y < self.height && self.in_scissor(x, y) {
+            self.buffer[y * self.width + x] = color;
+        }
+    }
+
+    // Copia el color de `source` con su esquina superior izquierda en (x, y), como
+    // superposición (sin profundidad); lo que cae fuera de la pantalla se recorta
+    pub fn blit(&mut self, source: &Framebuffer, x: usize, y: usize) {
+        for row in 0..source.height {
+            for column in 0..source.width {
+                self.set_pixel(
+                    x + column,
+                    y + row,
+                    source.buffer[row * source.width + column],
+                );
+            }
+        }
+    }
+
+    // Píxeles en los que algún canal difiere en más de `tolerance` de `other`
+    // (del mismo tamaño)
+    pub fn count_differences(&self, other: &Framebuffer, tolerance: u8) -> usize {
+        self.buffer
+            .iter()
+            .zip(&other.buffer)
+            .filter(|&(&a, &b)| {
+                let (a, b) = (Color::from_hex(a), Color::from_hex(b));
+                a.r.abs_diff(b.r) > tolerance
+                    || a.g.abs_diff(b.g) > tolerance
+                    || a.b.abs_diff(b.b) > tolerance
+            })
+            .count()
+    }
+
+    pub fn blend_pixel(&mut self, x: usize, y: usize, color: u32, alpha: f32) {
+        if x < self.width && y < self.height && self.in_scissor(x, y) {
+            let index = y * self.width + x;
+            let existing = Color::from_hex(self.buffer[index]);
+            self.buffer[index] = existing.lerp(&Color::from_hex(color), alpha).to_hex();
+        }
+    }
+
     pub fn set_background_color(&mut self, color: u32) {
         self.background_color = color;
     }
@@ -87,6 +341,206 @@ impl Framebuffer {
         }
     }
 
+    // Línea antialiasada (algoritmo de Xiaolin Wu) sobre coordenadas de subpíxel
+    pub fn draw_line_antialiased(&mut self, x0: f32, y0: f32, x1: f32, y1: f32, depth: f32) {
+        if !(x0.is_finite() && y0.is_finite() && x1.is_finite() && y1.is_finite()) {
+            return;
+        }
+
+        let steep = (y1 - y0).abs() > (x1 - x0).abs();
+        let (mut x0, mut y0, mut x1, mut y1) = if steep {
+            (y0, x0, y1, x1)
+        } else {
+            (x0, y0, x1, y1)
+        };
+        if x0 > x1 {
+            std::mem::swap(&mut x0, &mut x1);
+            std::mem::swap(&mut y0, &mut y1);
+        }
+
+        let dx = x1 - x0;
+        let dy = y1 - y0;
+        let gradient = if dx == 0.0 { 1.0 } else { dy / dx };
+
+        // Primer extremo
+        let xend = x0.round();
+        let yend = y0 + gradient * (xend - x0);
+        let xgap = 1.0 - fract(x0 + 0.5);
+        let xpxl1 = xend as isize;
+        let ypxl1 = yend.floor() as isize;
+        self.plot_aa(xpxl1, ypxl1, depth, (1.0 - fract(yend)) * xgap, steep);
+        self.plot_aa(xpxl1, ypxl1 + 1, depth, fract(yend) * xgap, steep);
+        let first_y = yend;
+
+        // Segundo extremo
+        let xend = x1.round();
+        let yend = y1 + gradient * (xend - x1);
+        let xgap = fract(x1 + 0.5);
+        let xpxl2 = xend as isize;
+        let ypxl2 = yend.floor() as isize;
+        self.plot_aa(xpxl2, ypxl2, depth, (1.0 - fract(yend)) * xgap, steep);
+        self.plot_aa(xpxl2, ypxl2 + 1, depth, fract(yend) * xgap, steep);
+
+        // Solo se recorre la parte del segmento que cae dentro del framebuffer
+        let limit = if steep { self.height } else { self.width } as isize;
+        let start = (xpxl1 + 1).max(0);
+        let end = xpxl2.min(limit);
+        for x in start..end {
+            let intery = first_y + gradient * (x - xpxl1) as f32;
+            let y = intery.floor() as isize;
+            self.plot_aa(x, y, depth, 1.0 - fract(intery), steep);
+            self.plot_aa(x, y + 1, depth, fract(intery), steep);
+        }
+    }
+
+    fn plot_aa(&mut self, x: isize, y: isize, depth: f32, coverage: f32, steep: bool) {
+        let (x, y) = if steep { (y, x) } else { (x, y) };
+        if x >= 0 && y >= 0 {
+            self.blend_point(x as usize, y as usize, depth, coverage);
+        }
+    }
+
+    // Circunferencia (algoritmo del punto medio)
+    pub fn draw_circle(&mut self, cx: isize, cy: isize, radius: isize, depth: f32) {
+        let mut x = radius;
+        let mut y = 0;
+        let mut err = 1 - radius;
+
+        while x >= y {
+            for (dx, dy) in [
+                (x, y),
+                (y, x),
+                (-y, x),
+                (-x, y),
+                (-x, -y),
+                (-y, -x),
+                (y, -x),
+                (x, -y),
+            ] {
+                self.draw_thick_point(cx + dx, cy + dy, depth, 1);
+            }
+
+            y += 1;
+            if err < 0 {
+                err += 2 * y + 1;
+            } else {
+                x -= 1;
+                err += 2 * (y - x) + 1;
+            }
+        }
+    }
+
+    // Punto como disco suave con prueba de profundidad, centrado en coordenadas de
+    // subpíxel. `softness` (0..1) es la fracción del radio en la que la opacidad
+    // cae a cero hacia el borde. Con radio <= 0.5 se escribe un solo píxel opaco,
+    // el camino rápido para cuando hay demasiados puntos.
+    pub fn draw_point(
+        &mut self,
+        x: f32,
+        y: f32,
+        depth: f32,
+        color: u32,
+        radius: f32,
+        softness: f32,
+    ) {
+        if !(x.is_finite() && y.is_finite()) {
+            return;
+        }
+        self.current_color = color;
+
+        if radius <= 0.5 {
+            if x >= 0.0 && y >= 0.0 {
+                self.point(x as usize, y as usize, depth);
+            }
+            return;
+        }
+
+        self.draw_soft_point(x, y, depth, color, radius, softness, |_, _| 1.0);
+    }
+
+    // Como `draw_point`, pero la opacidad de cada píxel se multiplica además por
+    // `fade(x, y)`; las partículas suaves la bajan cerca de la geometría ya dibujada
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_soft_point<F: Fn(usize, usize) -> f32>(
+        &mut self,
+        x: f32,
+        y: f32,
+        depth: f32,
+        color: u32,
+        radius: f32,
+        softness: f32,
+        fade: F,
+    ) {
+        if !(x.is_finite() && y.is_finite()) {
+            return;
+        }
+        self.current_color = color;
+
+        if radius <= 0.5 {
+            if x >= 0.0 && y >= 0.0 {
+                let (px, py) = (x as usize, y as usize);
+                if px < self.width && py < self.height {
+                    self.blend_point(px, py, depth, fade(px, py));
+                }
+            }
+            return;
+        }
+
+        let fade_width = (radius * softness.clamp(0.0, 1.0)).max(f32::EPSILON);
+        let min_x = (x - radius).floor().max(0.0) as usize;
+        let min_y = (y - radius).floor().max(0.0) as usize;
+        let max_x = ((x + radius).ceil().max(0.0) as usize).min(self.width);
+        let max_y = ((y + radius).ceil().max(0.0) as usize).min(self.height);
+
+        for py in min_y..max_y {
+            for px in min_x..max_x {
+                let dx = px as f32 + 0.5 - x;
+                let dy = py as f32 + 0.5 - y;
+                let distance = (dx * dx + dy * dy).sqrt();
+                let alpha = ((radius - distance) / fade_width).clamp(0.0, 1.0);
+                self.blend_point(px, py, depth, alpha * fade(px, py));
+            }
+        }
+    }
+
+    // Halo aditivo: suma `color` con una caída cuadrática desde el centro hasta
+    // `radius`. Respeta la geometría que tenga delante, pero no escribe
+    // profundidad, así que varios halos se acumulan.
+    pub fn add_glow(
+        &mut self,
+        x: f32,
+        y: f32,
+        depth: f32,
+        color: Color,
+        radius: f32,
+        intensity: f32,
+    ) {
+        if !(x.is_finite() && y.is_finite()) || radius <= 0.0 || intensity <= 0.0 {
+            return;
+        }
+        let min_x = (x - radius).floor().max(0.0) as usize;
+        let min_y = (y - radius).floor().max(0.0) as usize;
+        let max_x = ((x + radius).ceil().max(0.0) as usize).min(self.width);
+        let max_y = ((y + radius).ceil().max(0.0) as usize).min(self.height);
+
+        for py in min_y..max_y {
+            for px in min_x..max_x {
+                let index = py * self.width + px;
+                if self.zbuffer[index] <= depth || !self.in_scissor(px, py) {
+                    continue;
+                }
+                let dx = px as f32 + 0.5 - x;
+                let dy = py as f32 + 0.5 - y;
+                let falloff = (1.0 - (dx * dx + dy * dy).sqrt() / radius).max(0.0);
+                if falloff > 0.0 {
+                    let existing = Color::from_hex(self.buffer[index]);
+                    self.buffer[index] =
+                        (existing + color * (falloff * falloff * intensity)).to_hex();
+                }
+            }
+        }
+    }
+
     fn draw_thick_point(&mut self, x: isize, y: isize, depth: f32, thickness: usize) {
         let radius = (thickness as isize) / 2;
         for dx in -radius..=radius {
@@ -100,3 +554,33 @@ impl Framebuffer {
         }
     }
 }
+
+fn fract(value: f32) -> f32 {
+    value - value.floor()
+}
+
+// Filas del rectángulo (x, y, ancho, alto) de un búfer de `stride` columnas
+fn copy_rect<T: Copy>(source: &[T], stride: usize, rect: (usize, usize, usize, usize)) -> Vec<T> {
+    let (x, y, width, height) = rect;
+    (y..y + height)
+        .flat_map(|row| &source[row * stride + x..row * stride + x + width])
+        .copied()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scissor_is_clamped_to_framebuffer() {
+        let mut framebuffer = Framebuffer::new(10, 8);
+        framebuffer.set_scissor(Some((6, 4, 100, 100)));
+        assert_eq!(framebuffer.scissor(), Some((6, 4, 4, 4)));
+
+        framebuffer.set_scissor(Some((50, 50, 5, 5)));
+        assert_eq!(framebuffer.scissor(), Some((10, 8, 0, 0)));
+        framebuffer.point(9, 7, 0.0);
+        assert!(framebuffer.buffer.iter().all(|&pixel| pixel == 0));
+    }
+}
@@ -0,0 +1,213 @@
+// generator.rs
+// Sistemas planetarios generados a partir de una semilla para el modo de
+// exploración: misma semilla, mismo sistema.
+
+use crate::color::Color;
+use crate::log_info;
+use crate::planet::Planet;
+use crate::render::FragmentShader;
+use crate::shaders::{required_noises, SHADER_NOISES};
+use fastnoise_lite::{FastNoiseLite, FractalType, NoiseType};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::f32::consts::TAU;
+
+// Radio de la primera órbita posible, por fuera del Sol (escala 5)
+const FIRST_ORBIT_RADIUS: f32 = 8.0;
+// Espacio libre mínimo entre las franjas que barren dos planetas vecinos
+pub const MIN_ORBIT_GAP: f32 = 1.0;
+// Velocidad de una órbita en FIRST_ORBIT_RADIUS; las demás siguen la tercera ley
+// de Kepler (velocidad angular proporcional a r^-1.5), como en el sistema real
+const FIRST_ORBIT_SPEED: f32 = 0.02;
+// Se mezcla con la semilla del sistema para el generador de las fases iniciales
+const PHASE_SEED_MASK: u64 = 0x0b17_5eed;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BodyKind {
+    Rocky,
+    Gas,
+    Ice,
+}
+
+impl BodyKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BodyKind::Rocky => "rocoso",
+            BodyKind::Gas => "gaseoso",
+            BodyKind::Ice => "helado",
+        }
+    }
+
+    // Shaders (de `SHADER_NOISES`) que sirven para este tipo de cuerpo
+    fn shaders(&self) -> &'static [&'static str] {
+        match self {
+            BodyKind::Rocky => &["shader_mercury", "shader_venus", "shader_mars"],
+            BodyKind::Gas => &["shader_jupiter", "shader_saturn"],
+            BodyKind::Ice => &[
+                "shader_uranus",
+                "shader_neptune",
+                "shader_pluto",
+                "shader_eris",
+                "shader_sedna",
+            ],
+        }
+    }
+
+    // Rango de escalas (radio en el mundo) plausible para el tipo
+    fn scale_range(&self) -> (f32, f32) {
+        match self {
+            BodyKind::Rocky => (0.5, 1.2),
+            BodyKind::Gas => (1.6, 3.0),
+            BodyKind::Ice => (0.8, 1.8),
+        }
+    }
+
+    // Rango de frecuencias de sus ruidos: las bandas de los gigantes son más
+    // anchas que el detalle de una superficie rocosa
+    fn frequency_range(&self) -> (f32, f32) {
+        match self {
+            BodyKind::Rocky => (1.5, 8.0),
+            BodyKind::Gas => (1.0, 5.0),
+            BodyKind::Ice => (0.5, 3.0),
+        }
+    }
+
+    // Cerca del Sol dominan los rocosos, en medio los gaseosos y afuera los helados
+    fn pick(rng: &mut StdRng, position: f32) -> Self {
+        let roll = rng.gen::<f32>() * 0.5 + position * 0.75;
+        if roll < 0.4 {
+            BodyKind::Rocky
+        } else if roll < 0.85 {
+            BodyKind::Gas
+        } else {
+            BodyKind::Ice
+        }
+    }
+}
+
+pub struct GeneratedBody {
+    pub kind: BodyKind,
+    pub shader_name: &'static str,
+    pub shader: FragmentShader,
+    pub noises: Vec<FastNoiseLite>,
+}
+
+// Los cuerpos generados van en el mismo orden que `planets`, de adentro hacia afuera
+pub struct GeneratedSystem {
+    pub seed: u64,
+    pub planets: Vec<Planet>,
+    pub bodies: Vec<GeneratedBody>,
+}
+
+pub fn generate_system(seed: u64, count: usize) -> GeneratedSystem {
+    let mut rng = StdRng::seed_from_u64(seed);
+    // Las fases iniciales salen de otro generador para no alterar los sistemas que
+    // ya producía cada semilla
+    let mut phase_rng = StdRng::seed_from_u64(seed ^ PHASE_SEED_MASK);
+    let mut planets = Vec::with_capacity(count);
+    let mut bodies = Vec::with_capacity(count);
+    // Borde exterior de la franja que barre el planeta anterior
+    let mut previous_edge = FIRST_ORBIT_RADIUS - MIN_ORBIT_GAP;
+
+    for index in 0..count {
+        let kind = BodyKind::pick(&mut rng, index as f32 / count.max(1) as f32);
+        let (min_scale, max_scale) = kind.scale_range();
+        let scale = rng.gen_range(min_scale..max_scale);
+        // La franja de la órbita es [radio - escala, radio + escala]
+        let orbit_radius = previous_edge + MIN_ORBIT_GAP + scale + rng.gen_range(0.0..2.0);
+        previous_edge = orbit_radius + scale;
+        let orbit_speed = FIRST_ORBIT_SPEED * (FIRST_ORBIT_RADIUS / orbit_radius).powf(1.5);
+
+        let shaders = kind.shaders();
+        let shader_name = shaders[rng.gen_range(0..shaders.len())];
+        let shader = SHADER_NOISES
+            .iter()
+            .find(|(name, _, _)| *name == shader_name)
+            .map(|&(_, shader, _)| shader)
+            .expect("los shaders de cada tipo están registrados");
+        let noises = (0..required_noises(shader))
+            .map(|_| random_noise(&mut rng, kind))
+            .collect();
+
+        let albedo = Color::new(rng.gen(), rng.gen(), rng.gen());
+        planets.push(
+            Planet::new(
+                &format!("Cuerpo {}", index + 1),
+                scale,
+                orbit_radius,
+                orbit_speed,
+                (orbit_radius * 12.0) as usize,
+            )
+            .with_albedo(albedo)
+            .with_spin(rng.gen_range(0.00005..0.0003))
+            .with_retrograde(false, rng.gen_bool(0.15))
+            .with_phase(phase_rng.gen_range(0.0..TAU)),
+        );
+        bodies.push(GeneratedBody {
+            kind,
+            shader_name,
+            shader,
+            noises,
+        });
+    }
+
+    log_info!(
+        "generated system: seed={} bodies={} kinds=[{}]",
+        seed,
+        bodies.len(),
+        bodies
+            .iter()
+            .map(|body| body.kind.name())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    GeneratedSystem {
+        seed,
+        planets,
+        bodies,
+    }
+}
+
+fn random_noise(rng: &mut StdRng, kind: BodyKind) -> FastNoiseLite {
+    let mut noise = FastNoiseLite::with_seed(rng.gen());
+    let noise_type = if rng.gen_bool(0.5) {
+        NoiseType::OpenSimplex2
+    } else {
+        NoiseType::Perlin
+    };
+    noise.set_noise_type(Some(noise_type));
+    let (min_frequency, max_frequency) = kind.frequency_range();
+    noise.set_frequency(Some(rng.gen_range(min_frequency..max_frequency)));
+    noise.set_fractal_type(Some(FractalType::FBm));
+    noise.set_fractal_octaves(Some(rng.gen_range(2..6)));
+    noise
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_orbits_are_reproducible_and_do_not_overlap() {
+        for seed in 0..20 {
+            let system = generate_system(seed, 10);
+            assert_eq!(system.planets.len(), 10);
+            for pair in system.planets.windows(2) {
+                let inner_edge = pair[0].orbit_radius + pair[0].scale;
+                let outer_edge = pair[1].orbit_radius - pair[1].scale;
+                assert!(outer_edge - inner_edge >= MIN_ORBIT_GAP - 1e-4);
+            }
+            for (planet, body) in system.planets.iter().zip(&system.bodies) {
+                assert_eq!(body.noises.len(), required_noises(body.shader));
+                assert!(planet.orbit_radius - planet.scale >= FIRST_ORBIT_RADIUS - 1e-4);
+            }
+
+            let again = generate_system(seed, 10);
+            for (first, second) in system.planets.iter().zip(&again.planets) {
+                assert_eq!(first.orbit_radius, second.orbit_radius);
+                assert_eq!(first.albedo.to_hex(), second.albedo.to_hex());
+                assert_eq!(first.phase_offset, second.phase_offset);
+            }
+        }
+    }
+}
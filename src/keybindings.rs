@@ -0,0 +1,524 @@
+use crate::{log_debug, log_info, log_warn};
+use minifb::Key;
+use std::fs;
+
+// Cómo interpretan las teclas de movimiento la cámara
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MovementScheme {
+    // WASD se desplaza relativo a la vista (adelante/atrás/lados), flechas orbitan
+    Fly,
+    // Esquema original: W/S orbitan en vertical, A/D/Q/E giran el punto de mira
+    // y las flechas arriba/abajo hacen zoom. `move_forward`/`move_backward` no se usan.
+    Legacy,
+}
+
+// Asignación de teclas a acciones. Se carga de un archivo de texto con líneas
+// `accion = Tecla` (se ignoran líneas vacías y comentarios con '#'); las acciones
+// que no aparecen conservan su tecla por defecto. `preset = legacy` (o `fly`)
+// carga un esquema completo, y las líneas posteriores lo modifican.
+pub struct KeyBindings {
+    pub scheme: MovementScheme,
+    pub quit: Key,
+    pub orbit_left: Key,
+    pub orbit_right: Key,
+    pub orbit_up: Key,
+    pub orbit_down: Key,
+    pub move_forward: Key,
+    pub move_backward: Key,
+    pub move_left: Key,
+    pub move_right: Key,
+    pub move_up: Key,
+    pub move_down: Key,
+    pub zoom_in: Key,
+    pub zoom_out: Key,
+    pub bird_eye: Key,
+    pub cycle_focus: Key,
+    pub toggle_wireframe: Key,
+    pub toggle_antialiasing: Key,
+    pub toggle_proximity: Key,
+    pub toggle_outlines: Key,
+    pub toggle_god_rays: Key,
+    pub trail_shorter: Key,
+    pub trail_longer: Key,
+    pub toggle_dither: Key,
+    pub toggle_explode: Key,
+    pub toggle_cutaway: Key,
+    pub reverse_time: Key,
+    pub toggle_particle_ring: Key,
+    pub toggle_auto_quality: Key,
+    pub toggle_anaglyph: Key,
+    pub toggle_shader_compare: Key,
+    pub compare_left_next: Key,
+    pub compare_right_next: Key,
+    pub toggle_motion_blur: Key,
+    pub toggle_star_parallax: Key,
+    pub view_top_down: Key,
+    pub view_side: Key,
+    pub ambient_down: Key,
+    pub ambient_up: Key,
+    pub toggle_ring_shadows: Key,
+    pub cycle_debug_view: Key,
+    pub screenshot: Key,
+    pub toggle_trail_ribbon: Key,
+    pub cycle_noise_channel: Key,
+    pub toggle_flat_shading: Key,
+    pub camera_slower: Key,
+    pub camera_faster: Key,
+    pub toggle_minimap: Key,
+    pub tweak_down: Key,
+    pub tweak_up: Key,
+    pub toggle_sandbox: Key,
+    pub regenerate_sandbox: Key,
+    pub toggle_surface_animation: Key,
+    pub cycle_color_blindness: Key,
+    pub toggle_glow: Key,
+    pub fewer_bodies: Key,
+    pub more_bodies: Key,
+    pub toggle_lens_flare: Key,
+    pub reseed_planet: Key,
+    pub toggle_sky_gradient: Key,
+    pub dump_scene: Key,
+    pub cycle_lighting: Key,
+    pub toggle_solo: Key,
+    pub toggle_turntable: Key,
+    pub exposure_down: Key,
+    pub exposure_up: Key,
+    pub toggle_auto_exposure: Key,
+    pub toggle_axes: Key,
+    pub toggle_transparent_capture: Key,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings {
+            scheme: MovementScheme::Fly,
+            quit: Key::Escape,
+            orbit_left: Key::Left,
+            orbit_right: Key::Right,
+            orbit_up: Key::Up,
+            orbit_down: Key::Down,
+            move_forward: Key::W,
+            move_backward: Key::S,
+            move_left: Key::A,
+            move_right: Key::D,
+            move_up: Key::Q,
+            move_down: Key::E,
+            zoom_in: Key::Equal,
+            zoom_out: Key::Minus,
+            bird_eye: Key::B,
+            cycle_focus: Key::Tab,
+            toggle_wireframe: Key::F,
+            toggle_antialiasing: Key::L,
+            toggle_proximity: Key::H,
+            toggle_outlines: Key::O,
+            toggle_god_rays: Key::G,
+            trail_shorter: Key::LeftBracket,
+            trail_longer: Key::RightBracket,
+            toggle_dither: Key::T,
+            toggle_explode: Key::X,
+            toggle_cutaway: Key::C,
+            reverse_time: Key::R,
+            toggle_particle_ring: Key::P,
+            toggle_auto_quality: Key::K,
+            toggle_anaglyph: Key::N,
+            toggle_shader_compare: Key::V,
+            compare_left_next: Key::Key1,
+            compare_right_next: Key::Key2,
+            toggle_motion_blur: Key::M,
+            toggle_star_parallax: Key::J,
+            view_top_down: Key::Y,
+            view_side: Key::U,
+            ambient_down: Key::Comma,
+            ambient_up: Key::Period,
+            toggle_ring_shadows: Key::I,
+            cycle_debug_view: Key::F3,
+            screenshot: Key::F2,
+            toggle_trail_ribbon: Key::Z,
+            cycle_noise_channel: Key::F4,
+            toggle_flat_shading: Key::F5,
+            camera_slower: Key::Key3,
+            camera_faster: Key::Key4,
+            toggle_minimap: Key::Key5,
+            tweak_down: Key::NumPadMinus,
+            tweak_up: Key::NumPadPlus,
+            toggle_sandbox: Key::F6,
+            regenerate_sandbox: Key::F7,
+            toggle_surface_animation: Key::F8,
+            cycle_color_blindness: Key::F9,
+            toggle_glow: Key::F10,
+            fewer_bodies: Key::Key6,
+            more_bodies: Key::Key7,
+            toggle_lens_flare: Key::F11,
+            reseed_planet: Key::F12,
+            toggle_sky_gradient: Key::Key8,
+            dump_scene: Key::Key9,
+            cycle_lighting: Key::Key0,
+            toggle_solo: Key::Enter,
+            toggle_turntable: Key::Home,
+            exposure_down: Key::PageDown,
+            exposure_up: Key::PageUp,
+            toggle_auto_exposure: Key::End,
+            toggle_axes: Key::Backspace,
+            toggle_transparent_capture: Key::Delete,
+        }
+    }
+}
+
+impl KeyBindings {
+    // Controles originales del proyecto, para quien ya se acostumbró a ellos
+    pub fn legacy() -> Self {
+        KeyBindings {
+            scheme: MovementScheme::Legacy,
+            orbit_up: Key::W,
+            orbit_down: Key::S,
+            zoom_in: Key::Up,
+            zoom_out: Key::Down,
+            ..Self::default()
+        }
+    }
+
+    // Usa los valores por defecto si el archivo no existe
+    pub fn load(path: &str) -> Self {
+        match fs::read_to_string(path) {
+            Ok(contents) => {
+                log_info!("key bindings loaded from {}", path);
+                Self::parse(&contents)
+            }
+            Err(_) => {
+                log_debug!("no {}, using the default key bindings", path);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn parse(contents: &str) -> Self {
+        let mut bindings = Self::default();
+
+        for (line_number, line) in contents.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let Some((action, key_name)) = line.split_once('=') else {
+                log_warn!("Ignoring malformed binding on line {}", line_number + 1);
+                continue;
+            };
+            let (action, key_name) = (action.trim(), key_name.trim());
+
+            if action == "preset" {
+                match key_name.to_ascii_lowercase().as_str() {
+                    "fly" => bindings = Self::default(),
+                    "legacy" => bindings = Self::legacy(),
+                    other => log_warn!("Unknown preset '{}', expected 'fly' or 'legacy'", other),
+                }
+                continue;
+            }
+
+            let Some(key) = parse_key(key_name) else {
+                log_warn!("Unknown key '{}' for action '{}'", key_name, action);
+                continue;
+            };
+            match bindings.slot(action) {
+                Some(slot) => *slot = key,
+                None => {
+                    let actions: Vec<&str> =
+                        bindings.entries().iter().map(|(name, _)| *name).collect();
+                    log_warn!(
+                        "Unknown action '{}', expected one of: {}",
+                        action,
+                        actions.join(", ")
+                    );
+                }
+            }
+        }
+
+        bindings
+    }
+
+    // Lista (acción, tecla) en el orden de la documentación, para mostrar ayuda
+    pub fn entries(&self) -> Vec<(&'static str, Key)> {
+        vec![
+            ("quit", self.quit),
+            ("orbit_left", self.orbit_left),
+            ("orbit_right", self.orbit_right),
+            ("orbit_up", self.orbit_up),
+            ("orbit_down", self.orbit_down),
+            ("move_forward", self.move_forward),
+            ("move_backward", self.move_backward),
+            ("move_left", self.move_left),
+            ("move_right", self.move_right),
+            ("move_up", self.move_up),
+            ("move_down", self.move_down),
+            ("zoom_in", self.zoom_in),
+            ("zoom_out", self.zoom_out),
+            ("bird_eye", self.bird_eye),
+            ("cycle_focus", self.cycle_focus),
+            ("toggle_wireframe", self.toggle_wireframe),
+            ("toggle_antialiasing", self.toggle_antialiasing),
+            ("toggle_proximity", self.toggle_proximity),
+            ("toggle_outlines", self.toggle_outlines),
+            ("toggle_god_rays", self.toggle_god_rays),
+            ("trail_shorter", self.trail_shorter),
+            ("trail_longer", self.trail_longer),
+            ("toggle_dither", self.toggle_dither),
+            ("toggle_explode", self.toggle_explode),
+            ("toggle_cutaway", self.toggle_cutaway),
+            ("reverse_time", self.reverse_time),
+            ("toggle_particle_ring", self.toggle_particle_ring),
+            ("toggle_auto_quality", self.toggle_auto_quality),
+            ("toggle_anaglyph", self.toggle_anaglyph),
+            ("toggle_shader_compare", self.toggle_shader_compare),
+            ("compare_left_next", self.compare_left_next),
+            ("compare_right_next", self.compare_right_next),
+            ("toggle_motion_blur", self.toggle_motion_blur),
+            ("toggle_star_parallax", self.toggle_star_parallax),
+            ("view_top_down", self.view_top_down),
+            ("view_side", self.view_side),
+            ("ambient_down", self.ambient_down),
+            ("ambient_up", self.ambient_up),
+            ("toggle_ring_shadows", self.toggle_ring_shadows),
+            ("cycle_debug_view", self.cycle_debug_view),
+            ("screenshot", self.screenshot),
+            ("toggle_trail_ribbon", self.toggle_trail_ribbon),
+            ("cycle_noise_channel", self.cycle_noise_channel),
+            ("toggle_flat_shading", self.toggle_flat_shading),
+            ("camera_slower", self.camera_slower),
+            ("camera_faster", self.camera_faster),
+            ("toggle_minimap", self.toggle_minimap),
+            ("tweak_down", self.tweak_down),
+            ("tweak_up", self.tweak_up),
+            ("toggle_sandbox", self.toggle_sandbox),
+            ("regenerate_sandbox", self.regenerate_sandbox),
+            ("toggle_surface_animation", self.toggle_surface_animation),
+            ("cycle_color_blindness", self.cycle_color_blindness),
+            ("toggle_glow", self.toggle_glow),
+            ("fewer_bodies", self.fewer_bodies),
+            ("more_bodies", self.more_bodies),
+            ("toggle_lens_flare", self.toggle_lens_flare),
+            ("reseed_planet", self.reseed_planet),
+            ("toggle_sky_gradient", self.toggle_sky_gradient),
+            ("dump_scene", self.dump_scene),
+            ("cycle_lighting", self.cycle_lighting),
+            ("toggle_solo", self.toggle_solo),
+            ("toggle_turntable", self.toggle_turntable),
+            ("exposure_down", self.exposure_down),
+            ("exposure_up", self.exposure_up),
+            ("toggle_auto_exposure", self.toggle_auto_exposure),
+            ("toggle_axes", self.toggle_axes),
+            (
+                "toggle_transparent_capture",
+                self.toggle_transparent_capture,
+            ),
+        ]
+    }
+
+    fn slot(&mut self, action: &str) -> Option<&mut Key> {
+        let slot = match action {
+            "quit" => &mut self.quit,
+            "orbit_left" => &mut self.orbit_left,
+            "orbit_right" => &mut self.orbit_right,
+            "orbit_up" => &mut self.orbit_up,
+            "orbit_down" => &mut self.orbit_down,
+            "move_forward" => &mut self.move_forward,
+            "move_backward" => &mut self.move_backward,
+            "move_left" => &mut self.move_left,
+            "move_right" => &mut self.move_right,
+            "move_up" => &mut self.move_up,
+            "move_down" => &mut self.move_down,
+            "zoom_in" => &mut self.zoom_in,
+            "zoom_out" => &mut self.zoom_out,
+            "bird_eye" => &mut self.bird_eye,
+            "cycle_focus" => &mut self.cycle_focus,
+            "toggle_wireframe" => &mut self.toggle_wireframe,
+            "toggle_antialiasing" => &mut self.toggle_antialiasing,
+            "toggle_proximity" => &mut self.toggle_proximity,
+            "toggle_outlines" => &mut self.toggle_outlines,
+            "toggle_god_rays" => &mut self.toggle_god_rays,
+            "trail_shorter" => &mut self.trail_shorter,
+            "trail_longer" => &mut self.trail_longer,
+            "toggle_dither" => &mut self.toggle_dither,
+            "toggle_explode" => &mut self.toggle_explode,
+            "toggle_cutaway" => &mut self.toggle_cutaway,
+            "reverse_time" => &mut self.reverse_time,
+            "toggle_particle_ring" => &mut self.toggle_particle_ring,
+            "toggle_auto_quality" => &mut self.toggle_auto_quality,
+            "toggle_anaglyph" => &mut self.toggle_anaglyph,
+            "toggle_shader_compare" => &mut self.toggle_shader_compare,
+            "compare_left_next" => &mut self.compare_left_next,
+            "compare_right_next" => &mut self.compare_right_next,
+            "toggle_motion_blur" => &mut self.toggle_motion_blur,
+            "toggle_star_parallax" => &mut self.toggle_star_parallax,
+            "view_top_down" => &mut self.view_top_down,
+            "view_side" => &mut self.view_side,
+            "ambient_down" => &mut self.ambient_down,
+            "ambient_up" => &mut self.ambient_up,
+            "toggle_ring_shadows" => &mut self.toggle_ring_shadows,
+            "cycle_debug_view" => &mut self.cycle_debug_view,
+            "screenshot" => &mut self.screenshot,
+            "toggle_trail_ribbon" => &mut self.toggle_trail_ribbon,
+            "cycle_noise_channel" => &mut self.cycle_noise_channel,
+            "toggle_flat_shading" => &mut self.toggle_flat_shading,
+            "camera_slower" => &mut self.camera_slower,
+            "camera_faster" => &mut self.camera_faster,
+            "toggle_minimap" => &mut self.toggle_minimap,
+            "tweak_down" => &mut self.tweak_down,
+            "tweak_up" => &mut self.tweak_up,
+            "toggle_sandbox" => &mut self.toggle_sandbox,
+            "regenerate_sandbox" => &mut self.regenerate_sandbox,
+            "toggle_surface_animation" => &mut self.toggle_surface_animation,
+            "cycle_color_blindness" => &mut self.cycle_color_blindness,
+            "toggle_glow" => &mut self.toggle_glow,
+            "fewer_bodies" => &mut self.fewer_bodies,
+            "more_bodies" => &mut self.more_bodies,
+            "toggle_lens_flare" => &mut self.toggle_lens_flare,
+            "reseed_planet" => &mut self.reseed_planet,
+            "toggle_sky_gradient" => &mut self.toggle_sky_gradient,
+            "dump_scene" => &mut self.dump_scene,
+            "cycle_lighting" => &mut self.cycle_lighting,
+            "toggle_solo" => &mut self.toggle_solo,
+            "toggle_turntable" => &mut self.toggle_turntable,
+            "exposure_down" => &mut self.exposure_down,
+            "exposure_up" => &mut self.exposure_up,
+            "toggle_auto_exposure" => &mut self.toggle_auto_exposure,
+            "toggle_axes" => &mut self.toggle_axes,
+            "toggle_transparent_capture" => &mut self.toggle_transparent_capture,
+            _ => return None,
+        };
+        Some(slot)
+    }
+}
+
+// Nombre de tecla sin distinguir mayúsculas: letras, dígitos, F1-F12, flechas y especiales
+pub fn parse_key(name: &str) -> Option<Key> {
+    let name = name.to_ascii_lowercase();
+    let key = match name.as_str() {
+        "a" => Key::A,
+        "b" => Key::B,
+        "c" => Key::C,
+        "d" => Key::D,
+        "e" => Key::E,
+        "f" => Key::F,
+        "g" => Key::G,
+        "h" => Key::H,
+        "i" => Key::I,
+        "j" => Key::J,
+        "k" => Key::K,
+        "l" => Key::L,
+        "m" => Key::M,
+        "n" => Key::N,
+        "o" => Key::O,
+        "p" => Key::P,
+        "q" => Key::Q,
+        "r" => Key::R,
+        "s" => Key::S,
+        "t" => Key::T,
+        "u" => Key::U,
+        "v" => Key::V,
+        "w" => Key::W,
+        "x" => Key::X,
+        "y" => Key::Y,
+        "z" => Key::Z,
+        "0" => Key::Key0,
+        "1" => Key::Key1,
+        "2" => Key::Key2,
+        "3" => Key::Key3,
+        "4" => Key::Key4,
+        "5" => Key::Key5,
+        "6" => Key::Key6,
+        "7" => Key::Key7,
+        "8" => Key::Key8,
+        "9" => Key::Key9,
+        "f1" => Key::F1,
+        "f2" => Key::F2,
+        "f3" => Key::F3,
+        "f4" => Key::F4,
+        "f5" => Key::F5,
+        "f6" => Key::F6,
+        "f7" => Key::F7,
+        "f8" => Key::F8,
+        "f9" => Key::F9,
+        "f10" => Key::F10,
+        "f11" => Key::F11,
+        "f12" => Key::F12,
+        "left" => Key::Left,
+        "right" => Key::Right,
+        "up" => Key::Up,
+        "down" => Key::Down,
+        "space" => Key::Space,
+        "tab" => Key::Tab,
+        "escape" | "esc" => Key::Escape,
+        "enter" => Key::Enter,
+        "backspace" => Key::Backspace,
+        "delete" => Key::Delete,
+        "home" => Key::Home,
+        "end" => Key::End,
+        "pageup" => Key::PageUp,
+        "pagedown" => Key::PageDown,
+        "leftshift" => Key::LeftShift,
+        "rightshift" => Key::RightShift,
+        "leftctrl" => Key::LeftCtrl,
+        "rightctrl" => Key::RightCtrl,
+        "minus" => Key::Minus,
+        "equal" => Key::Equal,
+        "comma" => Key::Comma,
+        "period" => Key::Period,
+        "leftbracket" => Key::LeftBracket,
+        "rightbracket" => Key::RightBracket,
+        "numpadminus" => Key::NumPadMinus,
+        "numpadplus" => Key::NumPadPlus,
+        _ => return None,
+    };
+    Some(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_lines_override_only_their_action() {
+        let bindings = KeyBindings::parse(
+            "# controles propios\n\nquit = q\n  screenshot=F5   # con comentario\nzoom_in = PageUp\n",
+        );
+        assert_eq!(bindings.quit, Key::Q);
+        assert_eq!(bindings.screenshot, Key::F5);
+        assert_eq!(bindings.zoom_in, Key::PageUp);
+        assert_eq!(bindings.zoom_out, KeyBindings::default().zoom_out);
+        assert_eq!(bindings.scheme, MovementScheme::Fly);
+    }
+
+    #[test]
+    fn presets_load_a_scheme_that_later_lines_modify() {
+        let bindings = KeyBindings::parse("preset = Legacy\nzoom_in = z\n");
+        assert_eq!(bindings.scheme, MovementScheme::Legacy);
+        assert_eq!(bindings.orbit_up, Key::W);
+        assert_eq!(bindings.zoom_in, Key::Z);
+        assert_eq!(bindings.zoom_out, Key::Down);
+    }
+
+    #[test]
+    fn unknown_and_malformed_lines_are_skipped() {
+        let defaults = KeyBindings::default();
+        let bindings = KeyBindings::parse(
+            "quit = NotAKey\nfly_to_mars = m\nscreenshot F5\npreset = arcade\n= x\ncycle_focus = 7\n",
+        );
+        assert_eq!(bindings.quit, defaults.quit);
+        assert_eq!(bindings.screenshot, defaults.screenshot);
+        assert_eq!(bindings.scheme, MovementScheme::Fly);
+        // Las líneas que sí son válidas se aplican aunque haya errores antes
+        assert_eq!(bindings.cycle_focus, Key::Key7);
+    }
+
+    #[test]
+    fn every_action_name_can_be_rebound() {
+        for (action, _) in KeyBindings::default().entries() {
+            let bindings = KeyBindings::parse(&format!("{} = F1", action));
+            let key = bindings
+                .entries()
+                .into_iter()
+                .find(|(name, _)| *name == action)
+                .map(|(_, key)| key);
+            assert_eq!(key, Some(Key::F1), "{}", action);
+        }
+    }
+}
@@ -0,0 +1,39 @@
+// lib.rs
+// Renderizador por software del sistema solar como biblioteca; `main.rs` es el
+// binario con la ventana, la simulación y los controles.
+
+pub mod bodies;
+pub mod camera;
+pub mod cli;
+pub mod clock;
+pub mod color;
+pub mod error;
+pub mod events;
+pub mod fragment;
+pub mod framebuffer;
+pub mod generator;
+pub mod hud;
+pub mod keybindings;
+pub mod logging;
+pub mod mesh;
+pub mod mousestate;
+pub mod obj;
+pub mod particle_ring;
+pub mod planet;
+pub mod planet_trail;
+pub mod postprocess;
+pub mod quality;
+pub mod render;
+pub mod scene_dump;
+pub mod screenshot;
+pub mod seed;
+pub mod shaders;
+pub mod simulation;
+pub mod skybox;
+pub mod texture;
+pub mod transform;
+pub mod triangle;
+pub mod vertex;
+
+pub use framebuffer::Framebuffer;
+pub use render::{Light, Renderer, Scene, SceneBody, ShadowCaster, Uniforms};
@@ -0,0 +1,161 @@
+// logging.rs
+// Registro de diagnóstico a stderr, sin dependencias. La verbosidad se elige con
+// RUST_LOG como en env_logger: `RUST_LOG=info`, `RUST_LOG=debug`, o por módulo,
+// `RUST_LOG=warn,solar_system::render=debug`. Sin la variable se muestran los
+// errores y las advertencias.
+
+use std::fmt;
+use std::sync::OnceLock;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    fn label(self) -> &'static str {
+        match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        }
+    }
+}
+
+// Nivel máximo que se muestra; None es "off"
+fn parse_level(name: &str) -> Option<Option<Level>> {
+    match name.trim().to_ascii_lowercase().as_str() {
+        "off" => Some(None),
+        "error" => Some(Some(Level::Error)),
+        "warn" => Some(Some(Level::Warn)),
+        "info" => Some(Some(Level::Info)),
+        "debug" => Some(Some(Level::Debug)),
+        "trace" => Some(Some(Level::Trace)),
+        _ => None,
+    }
+}
+
+// Directivas de RUST_LOG separadas por comas: un nivel suelto es el de todos los
+// módulos y `modulo=nivel` vale para ese módulo y sus hijos (gana el más específico)
+pub struct Filter {
+    default: Option<Level>,
+    modules: Vec<(String, Option<Level>)>,
+}
+
+impl Filter {
+    pub fn parse(spec: &str) -> Self {
+        let mut filter = Filter {
+            default: Some(Level::Warn),
+            modules: Vec::new(),
+        };
+        for directive in spec.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            match directive.split_once('=') {
+                Some((module, level)) => {
+                    // `modulo=` sin nivel lo muestra todo, como env_logger
+                    let level = if level.trim().is_empty() {
+                        Some(Some(Level::Trace))
+                    } else {
+                        parse_level(level)
+                    };
+                    if let Some(level) = level {
+                        filter.modules.push((module.trim().to_string(), level));
+                    }
+                }
+                None => match parse_level(directive) {
+                    Some(level) => filter.default = level,
+                    // Un nombre sin nivel también es un módulo con todo activado
+                    None => filter
+                        .modules
+                        .push((directive.to_string(), Some(Level::Trace))),
+                },
+            }
+        }
+        filter
+    }
+
+    pub fn enabled(&self, level: Level, target: &str) -> bool {
+        let max = self
+            .modules
+            .iter()
+            .filter(|(module, _)| target == module || target.starts_with(&format!("{}::", module)))
+            .max_by_key(|(module, _)| module.len())
+            .map_or(self.default, |(_, level)| *level);
+        max.is_some_and(|max| level <= max)
+    }
+}
+
+// Se lee RUST_LOG una sola vez, en el primer mensaje
+fn filter() -> &'static Filter {
+    static FILTER: OnceLock<Filter> = OnceLock::new();
+    FILTER.get_or_init(|| Filter::parse(&std::env::var("RUST_LOG").unwrap_or_default()))
+}
+
+// Para no calcular estadísticas que nadie va a ver
+pub fn enabled(level: Level, target: &str) -> bool {
+    filter().enabled(level, target)
+}
+
+pub fn log(level: Level, target: &str, args: fmt::Arguments) {
+    if enabled(level, target) {
+        eprintln!("[{:<5} {}] {}", level.label(), target, args);
+    }
+}
+
+#[macro_export]
+macro_rules! log_error {
+    ($($arg:tt)+) => {
+        $crate::logging::log($crate::logging::Level::Error, module_path!(), format_args!($($arg)+))
+    };
+}
+
+#[macro_export]
+macro_rules! log_warn {
+    ($($arg:tt)+) => {
+        $crate::logging::log($crate::logging::Level::Warn, module_path!(), format_args!($($arg)+))
+    };
+}
+
+#[macro_export]
+macro_rules! log_info {
+    ($($arg:tt)+) => {
+        $crate::logging::log($crate::logging::Level::Info, module_path!(), format_args!($($arg)+))
+    };
+}
+
+#[macro_export]
+macro_rules! log_debug {
+    ($($arg:tt)+) => {
+        $crate::logging::log($crate::logging::Level::Debug, module_path!(), format_args!($($arg)+))
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rust_log_directives_pick_the_most_specific_module() {
+        let unset = Filter::parse("");
+        assert!(unset.enabled(Level::Warn, "solar_system::cli"));
+        assert!(!unset.enabled(Level::Info, "solar_system::cli"));
+
+        let filter =
+            Filter::parse("info, solar_system::render=debug,solar_system::render::trail=off");
+        assert!(filter.enabled(Level::Info, "Lab4_Graficas"));
+        assert!(!filter.enabled(Level::Debug, "solar_system::cli"));
+        assert!(filter.enabled(Level::Debug, "solar_system::render"));
+        assert!(!filter.enabled(Level::Error, "solar_system::render::trail"));
+        // Un prefijo de texto que no es un módulo padre no cuenta
+        assert!(!filter.enabled(Level::Debug, "solar_system::renderer"));
+
+        let off = Filter::parse("off,solar_system");
+        assert!(!off.enabled(Level::Error, "Lab4_Graficas"));
+        assert!(off.enabled(Level::Trace, "solar_system::shaders"));
+    }
+}
@@ -1,498 +1,178 @@
 // main.rs
 
-use minifb::{Key, MouseMode, Window, WindowOptions};
-use nalgebra_glm::{look_at, perspective, Mat4, Vec2, Vec3, Vec4};
-use std::f32::consts::PI;
-use std::time::Instant;
-
-mod camera;
-mod color;
-mod fragment;
-mod framebuffer;
-mod mousestate;
-mod obj;
-mod planet;
-mod planet_trail;
-mod shaders;
-mod skybox;
-mod triangle;
-mod vertex;
-
-use camera::Camera;
-use color::Color;
-use fastnoise_lite::{CellularDistanceFunction, FastNoiseLite, FractalType, NoiseType};
-use fragment::Fragment;
-use framebuffer::Framebuffer;
-use mousestate::MouseState;
-use obj::Obj;
-use planet_trail::PlanetTrail;
-use shaders::{
-    fragment_shader, shader_earth, shader_eris, shader_jupiter, shader_mars, shader_mercury,
-    shader_moon, shader_neptune, shader_phobos, shader_pluto, shader_ring, shader_saturn,
-    shader_sedna, shader_uranus, shader_uranus_ring, shader_venus, vertex_shader,
+use minifb::{MouseMode, ScaleMode, Window, WindowOptions};
+use nalgebra_glm::{rotation, Mat4, Vec2, Vec3};
+use std::f32::consts::TAU;
+use std::time::{Duration, Instant};
+
+use solar_system::bodies::{
+    sun_model_matrix, Focus, ShaderCompare, SolarFrame, SolarSystem, BARYCENTER, PLANET_TWEAKS,
+    SHADER_REGISTRY_LEN, SUN_SCALE,
 };
-use skybox::Skybox;
-use triangle::triangle;
-use vertex::Vertex;
-
-pub struct Uniforms<'a> {
-    pub model_matrix: Mat4,
-    pub view_matrix: Mat4,
-    pub projection_matrix: Mat4,
-    pub viewport_matrix: Mat4,
-    pub time: f32,
-    pub noises: Vec<&'a FastNoiseLite>,
-}
-
-fn create_default_noise() -> FastNoiseLite {
-    FastNoiseLite::with_seed(0)
-}
-
-fn create_lava_noise() -> Vec<FastNoiseLite> {
-    let mut noise = FastNoiseLite::with_seed(42);
-
-    // Use FBm for multi-layered noise, giving a "turbulent" feel
-    noise.set_noise_type(Some(NoiseType::Perlin)); // Perlin noise for smooth, natural texture
-    noise.set_fractal_type(Some(FractalType::FBm)); // FBm for layered detail
-    noise.set_fractal_octaves(Some(6)); // High octaves for rich detail
-    noise.set_fractal_lacunarity(Some(2.0)); // Higher lacunarity = more contrast between layers
-    noise.set_fractal_gain(Some(0.5)); // Higher gain = more influence of smaller details
-    noise.set_frequency(Some(0.002)); // Low frequency = large features
-
-    vec![noise]
-}
-
-fn create_earth_noises() -> Vec<FastNoiseLite> {
-    // Ruido base para el terreno (montañas)
-    let mut mountain_noise = FastNoiseLite::with_seed(42);
-    mountain_noise.set_noise_type(Some(NoiseType::Perlin));
-    mountain_noise.set_frequency(Some(1.0)); // Frecuencia baja para grandes características
-    mountain_noise.set_fractal_type(Some(FractalType::FBm));
-    mountain_noise.set_fractal_octaves(Some(5));
-
-    // Ruido secundario para colinas
-    let mut hill_noise = FastNoiseLite::with_seed(1337);
-    hill_noise.set_noise_type(Some(NoiseType::Perlin));
-    hill_noise.set_frequency(Some(2.5)); // Frecuencia media
-    hill_noise.set_fractal_type(Some(FractalType::FBm));
-    hill_noise.set_fractal_octaves(Some(4));
-
-    // Ruido terciario para detalles finos
-    let mut detail_noise = FastNoiseLite::with_seed(2021);
-    detail_noise.set_noise_type(Some(NoiseType::Perlin));
-    detail_noise.set_frequency(Some(5.0)); // Frecuencia alta para detalles finos
-    detail_noise.set_fractal_type(Some(FractalType::FBm));
-    detail_noise.set_fractal_octaves(Some(3));
-
-    // Ruido para las nubes (sin cambios)
-    let mut cloud_noise = FastNoiseLite::with_seed(40);
-    cloud_noise.set_noise_type(Some(NoiseType::Perlin));
-    cloud_noise.set_frequency(Some(5.0));
-    cloud_noise.set_fractal_type(Some(FractalType::FBm));
-    cloud_noise.set_fractal_octaves(Some(1));
-
-    // Atmosfera de la Tierra
-    let mut atmosphere_noise = FastNoiseLite::with_seed(40);
-    atmosphere_noise.set_noise_type(Some(NoiseType::Perlin));
-    atmosphere_noise.set_fractal_type(Some(FractalType::FBm));
-    atmosphere_noise.set_fractal_octaves(Some(2)); // Menos octavas para menos detalles
-    atmosphere_noise.set_fractal_lacunarity(Some(3.0));
-    atmosphere_noise.set_fractal_gain(Some(0.5));
-    atmosphere_noise.set_frequency(Some(0.01));
-
-    vec![
-        mountain_noise,
-        hill_noise,
-        detail_noise,
-        cloud_noise,
-        atmosphere_noise,
-    ]
-}
-
-fn create_jupiter_noise() -> Vec<FastNoiseLite> {
-    let mut band_noise = FastNoiseLite::with_seed(1337);
-    band_noise.set_noise_type(Some(NoiseType::OpenSimplex2));
-    band_noise.set_frequency(Some(5.0));
-    band_noise.set_fractal_type(Some(FractalType::FBm));
-    band_noise.set_fractal_octaves(Some(3));
-
-    let mut high_altitude_clouds = FastNoiseLite::with_seed(42);
-    high_altitude_clouds.set_noise_type(Some(NoiseType::OpenSimplex2));
-    high_altitude_clouds.set_frequency(Some(3.0));
-    high_altitude_clouds.set_fractal_type(Some(FractalType::FBm));
-    high_altitude_clouds.set_fractal_octaves(Some(2));
-
-    let mut deep_atmospheric = FastNoiseLite::with_seed(56);
-    deep_atmospheric.set_noise_type(Some(NoiseType::Perlin));
-    deep_atmospheric.set_frequency(Some(1.5));
-    deep_atmospheric.set_fractal_type(Some(FractalType::FBm));
-    deep_atmospheric.set_fractal_octaves(Some(4));
-
-    vec![band_noise, high_altitude_clouds, deep_atmospheric]
-}
-
-fn create_moon_noises() -> Vec<FastNoiseLite> {
-    // Ruido base para las características grandes
-    let mut noise1 = FastNoiseLite::with_seed(345);
-    noise1.set_noise_type(Some(NoiseType::Perlin));
-    noise1.set_frequency(Some(1.0)); // Frecuencia baja para manchas grandes
-    noise1.set_fractal_type(Some(FractalType::FBm));
-    noise1.set_fractal_octaves(Some(4));
-
-    // Ruido secundario para detalles adicionales
-    let mut noise2 = FastNoiseLite::with_seed(678);
-    noise2.set_noise_type(Some(NoiseType::Perlin));
-    noise2.set_frequency(Some(5.0)); // Frecuencia media
-    noise2.set_fractal_type(Some(FractalType::FBm));
-    noise2.set_fractal_octaves(Some(3));
-
-    // Ruido terciario para detalles finos
-    let mut noise3 = FastNoiseLite::with_seed(910);
-    noise3.set_noise_type(Some(NoiseType::Perlin));
-    noise3.set_frequency(Some(10.0)); // Frecuencia alta para detalles finos
-    noise3.set_fractal_type(Some(FractalType::FBm));
-    noise3.set_fractal_octaves(Some(2));
-
-    vec![noise1, noise2, noise3]
-}
-
-fn create_venus_noises() -> Vec<FastNoiseLite> {
-    let mut surface_noise = FastNoiseLite::with_seed(1337);
-    surface_noise.set_noise_type(Some(NoiseType::OpenSimplex2));
-    surface_noise.set_frequency(Some(5.0));
-    surface_noise.set_fractal_type(Some(FractalType::FBm));
-    surface_noise.set_fractal_octaves(Some(3));
-
-    let mut atmosphere_noise = FastNoiseLite::with_seed(235);
-    atmosphere_noise.set_noise_type(Some(NoiseType::Perlin));
-    atmosphere_noise.set_frequency(Some(0.5));
-    atmosphere_noise.set_fractal_type(Some(FractalType::FBm));
-    atmosphere_noise.set_fractal_octaves(Some(4));
-
-    vec![surface_noise, atmosphere_noise]
-}
-
-fn create_mercury_noises() -> Vec<FastNoiseLite> {
-    let mut crater_noise = FastNoiseLite::with_seed(2341);
-    crater_noise.set_noise_type(Some(NoiseType::Cellular));
-    crater_noise.set_frequency(Some(0.5));
-    crater_noise.set_fractal_type(Some(FractalType::FBm));
-    crater_noise.set_fractal_octaves(Some(4));
-    crater_noise.set_cellular_distance_function(Some(CellularDistanceFunction::Manhattan));
-
-    // Additional noise for textural variation
-    let mut texture_noise = FastNoiseLite::with_seed(4567);
-    texture_noise.set_noise_type(Some(NoiseType::Perlin));
-    texture_noise.set_frequency(Some(2.0));
-    texture_noise.set_fractal_type(Some(FractalType::Ridged));
-    texture_noise.set_fractal_octaves(Some(3));
-
-    // Another noise for subtle surface undulations
-    let mut undulation_noise = FastNoiseLite::with_seed(7890);
-    undulation_noise.set_noise_type(Some(NoiseType::Perlin));
-    undulation_noise.set_frequency(Some(0.1));
-    undulation_noise.set_fractal_type(Some(FractalType::FBm));
-    undulation_noise.set_fractal_octaves(Some(2));
-
-    vec![crater_noise, texture_noise, undulation_noise]
-}
-
-fn create_mars_noises() -> Vec<FastNoiseLite> {
-    let mut surface_noise = FastNoiseLite::with_seed(1024);
-    surface_noise.set_noise_type(Some(NoiseType::Perlin));
-    surface_noise.set_frequency(Some(0.6)); // Menor frecuencia para características más amplias
-    surface_noise.set_fractal_type(Some(FractalType::FBm));
-    surface_noise.set_fractal_octaves(Some(4));
-
-    let mut detail_noise = FastNoiseLite::with_seed(2048);
-    detail_noise.set_noise_type(Some(NoiseType::OpenSimplex2));
-    detail_noise.set_frequency(Some(2.0)); // Mayor frecuencia para detalles finos
-    detail_noise.set_fractal_type(Some(FractalType::FBm));
-    detail_noise.set_fractal_octaves(Some(3));
-
-    let mut atmospheric_noise = FastNoiseLite::with_seed(3100);
-    atmospheric_noise.set_noise_type(Some(NoiseType::Perlin));
-    atmospheric_noise.set_frequency(Some(0.5));
-    atmospheric_noise.set_fractal_type(Some(FractalType::Ridged));
-    atmospheric_noise.set_fractal_octaves(Some(2));
-
-    vec![surface_noise, detail_noise, atmospheric_noise]
-}
-
-fn create_phobos_noises() -> Vec<FastNoiseLite> {
-    let mut crater_noise = FastNoiseLite::with_seed(2341);
-    crater_noise.set_noise_type(Some(NoiseType::Cellular));
-    crater_noise.set_frequency(Some(0.5));
-    crater_noise.set_fractal_type(Some(FractalType::FBm));
-    crater_noise.set_fractal_octaves(Some(4));
-    crater_noise.set_cellular_distance_function(Some(CellularDistanceFunction::Manhattan));
-
-    // Additional noise for textural variation
-    let mut texture_noise = FastNoiseLite::with_seed(4567);
-    texture_noise.set_noise_type(Some(NoiseType::Perlin));
-    texture_noise.set_frequency(Some(2.0));
-    texture_noise.set_fractal_type(Some(FractalType::Ridged));
-    texture_noise.set_fractal_octaves(Some(3));
-
-    // Another noise for subtle surface undulations
-    let mut undulation_noise = FastNoiseLite::with_seed(7890);
-    undulation_noise.set_noise_type(Some(NoiseType::Perlin));
-    undulation_noise.set_frequency(Some(0.1));
-    undulation_noise.set_fractal_type(Some(FractalType::FBm));
-    undulation_noise.set_fractal_octaves(Some(2));
-
-    vec![crater_noise, texture_noise, undulation_noise]
-}
-
-fn create_saturn_noises() -> Vec<FastNoiseLite> {
-    let mut band_noise = FastNoiseLite::with_seed(12345);
-    band_noise.set_noise_type(Some(NoiseType::OpenSimplex2));
-    band_noise.set_frequency(Some(3.0));
-    band_noise.set_fractal_type(Some(FractalType::FBm));
-    band_noise.set_fractal_octaves(Some(4));
-
-    let mut cloud_noise = FastNoiseLite::with_seed(67890);
-    cloud_noise.set_noise_type(Some(NoiseType::Perlin));
-    cloud_noise.set_frequency(Some(1.5));
-    cloud_noise.set_fractal_type(Some(FractalType::Ridged));
-    cloud_noise.set_fractal_octaves(Some(3));
-
-    vec![band_noise, cloud_noise]
-}
-
-fn create_uranus_noises() -> Vec<FastNoiseLite> {
-    let mut primary_noise = FastNoiseLite::with_seed(1234);
-    primary_noise.set_noise_type(Some(NoiseType::OpenSimplex2));
-    primary_noise.set_frequency(Some(1.5));
-    primary_noise.set_fractal_type(Some(FractalType::FBm));
-    primary_noise.set_fractal_octaves(Some(3));
-
-    let mut secondary_noise = FastNoiseLite::with_seed(5678);
-    secondary_noise.set_noise_type(Some(NoiseType::Perlin));
-    secondary_noise.set_frequency(Some(2.0));
-    secondary_noise.set_fractal_type(Some(FractalType::Ridged));
-    secondary_noise.set_fractal_octaves(Some(2));
-
-    vec![primary_noise, secondary_noise]
-}
-
-fn create_uranus_ring_noises() -> Vec<FastNoiseLite> {
-    let mut ring_noise1 = FastNoiseLite::with_seed(8910);
-    ring_noise1.set_noise_type(Some(NoiseType::Cellular));
-    ring_noise1.set_frequency(Some(0.5));
-    ring_noise1.set_fractal_type(Some(FractalType::FBm));
-    ring_noise1.set_fractal_octaves(Some(2));
-
-    let mut ring_noise2 = FastNoiseLite::with_seed(1112);
-    ring_noise2.set_noise_type(Some(NoiseType::Perlin));
-    ring_noise2.set_frequency(Some(1.0));
-    ring_noise2.set_fractal_type(Some(FractalType::FBm));
-    ring_noise2.set_fractal_octaves(Some(1));
-
-    vec![ring_noise1, ring_noise2]
-}
-
-fn create_neptune_noises() -> Vec<FastNoiseLite> {
-    let mut surface_noise = FastNoiseLite::with_seed(501);
-    surface_noise.set_noise_type(Some(NoiseType::Perlin));
-    surface_noise.set_frequency(Some(0.8));
-    surface_noise.set_fractal_type(Some(FractalType::FBm));
-    surface_noise.set_fractal_octaves(Some(5));
-
-    let mut atmosphere_noise = FastNoiseLite::with_seed(502);
-    atmosphere_noise.set_noise_type(Some(NoiseType::Perlin));
-    atmosphere_noise.set_frequency(Some(1.2));
-    atmosphere_noise.set_fractal_type(Some(FractalType::Ridged));
-    atmosphere_noise.set_fractal_octaves(Some(4));
-
-    vec![surface_noise, atmosphere_noise]
-}
-
-fn create_pluto_noises() -> Vec<FastNoiseLite> {
-    let mut surface_noise = FastNoiseLite::with_seed(601);
-    surface_noise.set_noise_type(Some(NoiseType::Cellular));
-    surface_noise.set_frequency(Some(0.5));
-    surface_noise.set_cellular_distance_function(Some(CellularDistanceFunction::Euclidean));
-
-    let mut ice_noise = FastNoiseLite::with_seed(602);
-    ice_noise.set_noise_type(Some(NoiseType::Perlin));
-    ice_noise.set_frequency(Some(1.0));
-    ice_noise.set_fractal_type(Some(FractalType::FBm));
-    ice_noise.set_fractal_octaves(Some(3));
-
-    vec![surface_noise, ice_noise]
-}
-
-fn create_eris_noises() -> Vec<FastNoiseLite> {
-    let mut surface_noise = FastNoiseLite::with_seed(701);
-    surface_noise.set_noise_type(Some(NoiseType::Perlin));
-    surface_noise.set_frequency(Some(0.7));
-    surface_noise.set_fractal_type(Some(FractalType::FBm));
-    surface_noise.set_fractal_octaves(Some(4));
-
-    let mut ice_noise = FastNoiseLite::with_seed(702);
-    ice_noise.set_noise_type(Some(NoiseType::Perlin));
-    ice_noise.set_frequency(Some(1.1));
-    ice_noise.set_fractal_type(Some(FractalType::Ridged));
-    ice_noise.set_fractal_octaves(Some(5));
-
-    vec![surface_noise, ice_noise]
+use solar_system::camera::{Camera, CameraSpeeds};
+use solar_system::cli::{CliOptions, ScenePreset};
+use solar_system::clock::SystemClock;
+use solar_system::color::Color;
+use solar_system::error::RenderError;
+use solar_system::framebuffer::{Framebuffer, Rasterizer};
+use solar_system::generator::{generate_system, GeneratedSystem};
+use solar_system::keybindings::{KeyBindings, MovementScheme};
+use solar_system::mousestate::MouseState;
+use solar_system::particle_ring::ParticleRing;
+use solar_system::planet::{orbit_translation, Planet};
+use solar_system::planet_trail::{distribute_trail_budget, PlanetTrail};
+use solar_system::postprocess::{
+    AutoExposure, ColorBlindness, DebugView, GodRayParams, LensFlareParams, MotionBlur,
+};
+use solar_system::quality::{AutoQuality, DEFAULT_QUALITY_LEVEL};
+use solar_system::render::{
+    create_model_matrix, create_orthographic_matrix, create_perspective_matrix, project_to_screen,
+    projected_radius, render_line, render_scene_into, render_trail, render_wireframe,
+    sun_visibility, visible_sun_position, CutawayLayer, Renderer, Scene, TrailStyle, Uniforms,
+    DEFAULT_AMBIENT, FAR_PLANE, NEAR_PLANE,
+};
+use solar_system::scene_dump::{BodyState, SceneState};
+use solar_system::seed::SceneSeed;
+use solar_system::shaders::LightingSettings;
+use solar_system::simulation::{
+    sim_time_from_seconds, Simulation, FIXED_DT, SIM_TIME_PER_STEP, TRAIL_SAMPLE_INTERVAL,
+};
+use solar_system::skybox::{Skybox, SkyboxConfig, SKYBOX_DEPTH};
+use solar_system::{hud, log_debug, log_error, log_info, postprocess, screenshot};
+
+// Escala global de la longitud de las estelas: cada pulsación multiplica o divide por el paso
+const TRAIL_LENGTH_STEP: f32 = 1.25;
+const MIN_TRAIL_LENGTH_SCALE: f32 = 0.1;
+const MAX_TRAIL_LENGTH_SCALE: f32 = 8.0;
+
+// "Explotar órbitas": radios equiespaciados y duración de la transición en segundos
+const EXPLODED_INNER_RADIUS: f32 = 15.0;
+const EXPLODED_ORBIT_SPACING: f32 = 10.0;
+const EXPLODE_DURATION: f32 = 2.0;
+
+// FPS que intenta mantener la calidad automática
+const TARGET_FPS: f32 = 30.0;
+// Peso de cada cuadro nuevo en el promedio de FPS que muestra el HUD
+const FPS_SMOOTHING: f32 = 0.1;
+
+// Cambio de la luz ambiental por pulsación de ',' o '.'
+const AMBIENT_STEP: f32 = 0.05;
+
+// Ejes de coordenadas ('Retroceso'): sin `--axes-length` llegan un poco más allá de
+// la órbita más externa
+const AXES_MARGIN: f32 = 1.1;
+
+// Factor de la exposición manual por pulsación de 'RePág' o 'AvPág', y sus límites
+const EXPOSURE_STEP: f32 = 1.25;
+const EXPOSURE_RANGE: (f32, f32) = (0.1, 10.0);
+
+// Duración en segundos de la transición a las vistas cenital y de canto
+const VIEW_PRESET_TRANSITION: f32 = 0.8;
+
+// Vista individual ('Enter'): distancia de la cámara en radios del planeta y color
+// del fondo liso
+const SOLO_DISTANCE: f32 = 3.0;
+const SOLO_BACKGROUND: u32 = 0x202024;
+
+// Solo el planeta enfocado, grande y girando sobre un fondo liso, para revisar su
+// shader. Guarda el encuadre, la distancia de seguimiento y si el giro estaba
+// activo para volver al salir.
+struct SoloView {
+    index: usize,
+    offset: Vec3,
+    up: Vec3,
+    track_distance: Option<f32>,
+    turntable: bool,
 }
 
-fn create_sedna_noises() -> Vec<FastNoiseLite> {
-    let mut surface_noise = FastNoiseLite::with_seed(801);
-    surface_noise.set_noise_type(Some(NoiseType::OpenSimplex2));
-    surface_noise.set_frequency(Some(0.6));
-    surface_noise.set_fractal_type(Some(FractalType::FBm));
-    surface_noise.set_fractal_octaves(Some(3));
-
-    let mut ice_noise = FastNoiseLite::with_seed(802);
-    ice_noise.set_noise_type(Some(NoiseType::Cellular));
-    ice_noise.set_frequency(Some(0.4));
-    ice_noise.set_cellular_distance_function(Some(CellularDistanceFunction::Manhattan));
-
-    vec![surface_noise, ice_noise]
+// Vistas esquemáticas con proyección ortográfica
+#[derive(Clone, Copy, PartialEq)]
+enum ViewPreset {
+    // Mirando hacia abajo por el eje Y
+    TopDown,
+    // Mirando a lo largo del plano de las órbitas
+    Side,
 }
 
-fn create_model_matrix(translation: Vec3, scale: f32, rotation: Vec3) -> Mat4 {
-    let (sin_x, cos_x) = rotation.x.sin_cos();
-    let (sin_y, cos_y) = rotation.y.sin_cos();
-    let (sin_z, cos_z) = rotation.z.sin_cos();
-
-    let rotation_matrix_x = Mat4::new(
-        1.0, 0.0, 0.0, 0.0, 0.0, cos_x, -sin_x, 0.0, 0.0, sin_x, cos_x, 0.0, 0.0, 0.0, 0.0, 1.0,
-    );
+// Minimapa: altura de la cámara cenital (abarca la órbita de la estrella compañera)
+const MINIMAP_HEIGHT: f32 = 135.0;
 
-    let rotation_matrix_y = Mat4::new(
-        cos_y, 0.0, sin_y, 0.0, 0.0, 1.0, 0.0, 0.0, -sin_y, 0.0, cos_y, 0.0, 0.0, 0.0, 0.0, 1.0,
-    );
+// Cuerpos de cada sistema generado en el modo exploración ('F6')
+const GENERATED_BODY_COUNT: usize = 8;
 
-    let rotation_matrix_z = Mat4::new(
-        cos_z, -sin_z, 0.0, 0.0, sin_z, cos_z, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0,
-    );
+// Halos de los cuerpos lejanos ('F10'): aparecen cuando el radio en pantalla baja
+// de GLOW_FADE_START píxeles y llegan a toda su intensidad en GLOW_FADE_END.
+// El radio del halo está en píxeles de la ventana.
+const GLOW_FADE_START: f32 = 4.0;
+const GLOW_FADE_END: f32 = 1.0;
+const GLOW_RADIUS: f32 = 5.0;
+const GLOW_INTENSITY: f32 = 0.7;
 
-    let rotation_matrix = rotation_matrix_z * rotation_matrix_y * rotation_matrix_x;
-
-    let transform_matrix = Mat4::new(
-        scale,
-        0.0,
-        0.0,
-        translation.x,
-        0.0,
-        scale,
-        0.0,
-        translation.y,
-        0.0,
-        0.0,
-        scale,
-        translation.z,
-        0.0,
-        0.0,
-        0.0,
-        1.0,
-    );
+// Anillo alrededor del planeta enfocado: separación del borde del planeta y radio
+// mínimo (en píxeles de la ventana), cantidad de trazos y giro en radianes por segundo
+const FOCUS_RING_MARGIN: f32 = 6.0;
+const FOCUS_RING_MIN_RADIUS: f32 = 10.0;
+const FOCUS_RING_DASHES: usize = 12;
+const FOCUS_RING_SPEED: f32 = 0.8;
 
-    transform_matrix * rotation_matrix
-}
+// Archivo opcional para reasignar controles; sin él se usan las teclas por defecto
+const KEYBINDINGS_PATH: &str = "assets/keybindings.cfg";
 
-fn create_view_matrix(eye: Vec3, center: Vec3, up: Vec3) -> Mat4 {
-    look_at(&eye, &center, &up)
-}
-
-fn create_perspective_matrix(window_width: f32, window_height: f32) -> Mat4 {
-    let fov = 45.0 * PI / 180.0;
-    let aspect_ratio = window_width / window_height;
-    let near = 0.1;
-    let far = 1000.0;
-
-    perspective(fov, aspect_ratio, near, far)
-}
-
-fn create_viewport_matrix(width: f32, height: f32) -> Mat4 {
-    Mat4::new(
-        width / 2.0,
-        0.0,
-        0.0,
-        width / 2.0,
-        0.0,
-        -height / 2.0,
-        0.0,
-        height / 2.0,
-        0.0,
-        0.0,
-        1.0,
-        0.0,
-        0.0,
-        0.0,
-        0.0,
-        1.0,
-    )
-}
-
-fn render(
-    framebuffer: &mut Framebuffer,
-    uniforms: &Uniforms,
-    vertex_array: &[Vertex],
-    shader_fn: fn(&Fragment, &Uniforms) -> Color,
-) {
-    // Vertex Shader Stage
-    let mut transformed_vertices = Vec::with_capacity(vertex_array.len());
-    for vertex in vertex_array {
-        let transformed = vertex_shader(vertex, uniforms);
-        transformed_vertices.push(transformed);
-    }
-
-    // Primitive Assembly Stage
-    let mut triangles = Vec::new();
-    for i in (0..transformed_vertices.len()).step_by(3) {
-        if i + 2 < transformed_vertices.len() {
-            triangles.push([
-                transformed_vertices[i].clone(),
-                transformed_vertices[i + 1].clone(),
-                transformed_vertices[i + 2].clone(),
-            ]);
-        }
-    }
-
-    // Rasterization Stage
-    let mut fragments = Vec::new();
-    for tri in &triangles {
-        fragments.extend(triangle(&tri[0], &tri[1], &tri[2]));
-    }
-
-    // Fragment Processing Stage
-    for fragment in fragments {
-        let x = fragment.position.x as usize;
-        let y = fragment.position.y as usize;
-        if x < framebuffer.width && y < framebuffer.height {
-            // Aplicar el shader específico
-            let shaded_color = shader_fn(&fragment, &uniforms);
-            let color = shaded_color.to_hex();
-            framebuffer.set_current_color(color);
-            framebuffer.point(x, y, fragment.depth);
+fn main() {
+    if let Err(error) = run() {
+        eprintln!("Error: {}", error);
+        if let Some(hint) = error.hint() {
+            eprintln!("{}", hint);
         }
+        std::process::exit(1);
     }
 }
 
-fn main() {
-    let window_width = 800;
-    let window_height = 800;
-    let framebuffer_width = 800;
-    let framebuffer_height = 800;
-
-    let mut framebuffer = Framebuffer::new(framebuffer_width, framebuffer_height);
+fn run() -> Result<(), RenderError> {
+    let cli_options = CliOptions::parse();
+    let bindings = KeyBindings::load(KEYBINDINGS_PATH);
+
+    let window_width = cli_options.window.width;
+    let window_height = cli_options.window.height;
+    // El framebuffer cambia de tamaño con la calidad automática (ver quality.rs)
+    let mut framebuffer_width = window_width;
+    let mut framebuffer_height = window_height;
+
+    // Framebuffer con su proyección y viewport; la selección (el planeta enfocado)
+    // también pasa por él
+    let mut renderer = Renderer::new(framebuffer_width, framebuffer_height);
+    renderer.framebuffer.set_rasterizer(cli_options.rasterizer);
+    renderer.on_select(Box::new(|index| log_debug!("focused planet: {:?}", index)));
     let mut window = Window::new(
-        "Sistema Solar con Estelas",
+        &cli_options.window.title,
         window_width,
         window_height,
-        WindowOptions::default(),
+        WindowOptions {
+            // Si la ventana y el framebuffer difieren en aspecto, se agregan bandas en lugar de deformar
+            scale_mode: ScaleMode::AspectRatioStretch,
+            ..WindowOptions::default()
+        },
     )
-    .unwrap();
+    .map_err(RenderError::Window)?;
 
-    window.set_position(500, 500);
+    if let Some((x, y)) = cli_options.window.position {
+        window.set_position(x, y);
+    }
     window.update();
+    log_info!(
+        "window opened: size={}x{} rasterizer={:?} scene={:?} seed={:?}",
+        window_width,
+        window_height,
+        cli_options.rasterizer,
+        cli_options.scene,
+        cli_options.scene_seed
+    );
 
-    framebuffer.set_background_color(0x000000);
+    // Color de fondo de la escena, o el de `--clear-color`
+    let clear_color = cli_options
+        .clear_color
+        .unwrap_or(cli_options.scene.clear_color());
+    renderer.framebuffer.set_background_color(clear_color);
 
     // Parámetros de la cámara
     let mut camera = Camera::new(
@@ -500,193 +180,241 @@ fn main() {
         Vec3::new(0.0, 0.0, 0.0),
         Vec3::new(0.0, 1.0, 0.0),
     );
+    camera.track_distance = cli_options.follow_distance;
+    // Velocidades de la cámara: iniciales por línea de comandos, '3' y '4' las cambian
+    let mut camera_speeds = CameraSpeeds {
+        factor: cli_options.camera_speed,
+        rotation: CameraSpeeds::default().rotation * cli_options.rotation_speed,
+        ..CameraSpeeds::default()
+    };
 
-    // Cargar el modelo de esfera y anillo
-    let obj = Obj::load("assets/models/sphere.obj").expect("Failed to load obj");
-    let ring_obj: Obj = Obj::load("assets/models/ring.obj").expect("Failed to load ring obj");
-    let mut previous_time = Instant::now();
+    // Cuerpos, ruidos y mallas; la esfera con la resolución pedida (--sphere-detail)
+    let mut solar = SolarSystem::new(
+        &cli_options.scene_seed,
+        cli_options.scene,
+        cli_options.sphere_stacks,
+    )?;
 
     let mut bird_eye_active = false; // Añade esta línea
 
-    // Parámetros orbitales ajustados
-    let mercury_orbit_radius = 8.0;
-    let mercury_orbit_speed = 0.02;
-
-    let venus_orbit_radius = 10.0;
-    let venus_orbit_speed = 0.015;
-
-    let earth_orbit_radius = 12.0;
-    let earth_orbit_speed = 0.01;
-
-    let mars_orbit_radius = 14.0;
-    let mars_orbit_speed = 0.008;
-
-    let jupiter_orbit_radius = 18.0;
-    let jupiter_orbit_speed = 0.005;
-
-    let saturn_orbit_radius = 22.0;
-    let saturn_orbit_speed = 0.004;
-
-    let uranus_orbit_radius = 26.0;
-    let uranus_orbit_speed = 0.003;
-
-    let neptune_orbit_radius = 30.0;
-    let neptune_orbit_speed = 0.002;
-
-    let pluto_orbit_radius = 34.0;
-    let pluto_orbit_speed = 0.0015;
-
-    let eris_orbit_radius = 38.0;
-    let eris_orbit_speed = 0.0012;
-
-    let sedna_orbit_radius = 42.0;
-    let sedna_orbit_speed = 0.001;
-
-    // Noises
-    let sun_noises = create_lava_noise();
-    let mercury_noises = create_mercury_noises();
-    let venus_noises = create_venus_noises();
-    let earth_noises = create_earth_noises();
-    let moon_noises = create_moon_noises();
-    let mars_noises = create_mars_noises();
-    let phobos_noises = create_phobos_noises();
-    let jupiter_noises = create_jupiter_noise();
-    let saturn_noises = create_saturn_noises();
-    let uranus_noises = create_uranus_noises();
-    let neptune_noises = create_neptune_noises();
-    let pluto_noises = create_pluto_noises();
-    let eris_noises = create_eris_noises();
-    let sedna_noises = create_sedna_noises();
-
-    // Parámetros de escala para los planetas
-    let scale_sun = 5.0;
-    let scale_mercury = 0.7f32;
-    let scale_venus = 0.9f32;
-    let scale_earth = 1.2f32;
-    let scale_moon = 0.50f32; // Tamaño relativo de la luna respecto a la Tierra
-    let scale_mars = 0.8f32;
-    let scale_phobos = 0.33f32; // Tamaño relativo de Phobos comparado con la Luna
-    let scale_jupiter = 3.0f32;
-    let scale_saturn = 2.5f32;
-    let scale_uranus = 1.8f32;
-    let scale_neptune = 1.6f32;
-    let scale_pluto = 1.0f32;
-    let scale_eris = 1.2f32;
-    let scale_sedna = 1.3f32;
-
-    let max_trail_length_mercury = 100; // Ajusta este valor para Mercurio
-    let max_trail_length_venus = 150; // Ajusta este valor para Venus
-    let max_trail_length_earth = 200; // Ajusta este valor para la Tierra
-    let max_trail_length_mars = 250; // Ajusta este valor para Marte
-    let max_trail_length_jupiter = 300; // Ajusta este valor para Júpiter
-    let max_trail_length_saturn = 350; // Ajusta este valor para Saturno
-    let max_trail_length_uranus = 400; // Ajusta este valor para Urano
-    let max_trail_length_neptune = 450; // Ajusta este valor para Neptuno
-    let max_trail_length_pluto = 500; // Ajusta este valor para Plutón
-    let max_trail_length_eris = 550; // Ajusta este valor para Eris
-    let max_trail_length_sedna = 600; // Ajusta este valor para Sedna
-
-    let trail_thickness = 1; // Ajusta este valor al grosor deseado
-    let mut mercury_trail = PlanetTrail::new(max_trail_length_mercury);
-    let mut venus_trail = PlanetTrail::new(max_trail_length_venus);
-    let mut earth_trail = PlanetTrail::new(max_trail_length_earth);
-    let mut mars_trail = PlanetTrail::new(max_trail_length_mars);
-    let mut jupiter_trail = PlanetTrail::new(max_trail_length_jupiter);
-    let mut saturn_trail = PlanetTrail::new(max_trail_length_saturn);
-    let mut uranus_trail = PlanetTrail::new(max_trail_length_uranus);
-    let mut neptune_trail = PlanetTrail::new(max_trail_length_neptune);
-    let mut pluto_trail = PlanetTrail::new(max_trail_length_pluto);
-    let mut eris_trail = PlanetTrail::new(max_trail_length_eris);
-    let mut sedna_trail = PlanetTrail::new(max_trail_length_sedna);
-
-    // Configuraciones de los planetas
-
-    let translation_sun = Vec3::new(0.0, 0.0, 0.0); // Centered in the solar system
-    let vertex_array_sun = obj.get_vertex_array();
-    let rotation_sun = Vec3::new(0.0, 0.0, 0.0); // No rotation needed for visual effect
-
-    // Posición, rotación y escala para Mercurio
-    let rotation_mercury = Vec3::new(0.0, 0.0, 0.0); // Sin rotación inicial
-    let vertex_array_mercury = obj.get_vertex_array();
-
-    // Posición, rotación y escala para Venus
-    let rotation_venus = Vec3::new(0.0, 0.0, 0.0); // Sin rotación inicial
-    let vertex_array_venus = obj.get_vertex_array();
-
-    // Tierra
-    let rotation_earth = Vec3::new(0.0, 0.0, 0.0);
-    let vertex_array_earth = obj.get_vertex_array();
-
-    // Luna
-    let distance_moon = 1.0; // Distancia desde la Tierra
-    let vertex_array_moon = obj.get_vertex_array();
-    let vertex_array_ring = ring_obj.get_vertex_array();
-    let scale_ring = scale_moon * 0.75; // Ajusta el tamaño del anillo relativo a la Luna
-    let scale_ring2 = scale_moon * 0.75; // Ajusta el tamaño del anillo relativo a la Luna
-    let mut ring1_angle = 0.0f32;
-    let mut ring2_angle = 0.0f32;
-    let ring1_rotation_speed = 1.0; // Radianes por segundo
-    let ring2_rotation_speed = -1.45; // Radianes por segundo
-
-    // Posición, rotación y escala para Marte
-    let rotation_mars = Vec3::new(0.0, 0.0, 0.0);
-    let vertex_array_mars = obj.get_vertex_array();
-
-    // Posición, rotación y escala para Phobos
-    let rotation_phobos = Vec3::new(5.0, 0.0, 0.0);
-    let vertex_array_phobos = obj.get_vertex_array();
-
-    // Júpiter
-    let rotation_jupiter = Vec3::new(0.0, 0.0, 0.0);
-    let vertex_array_jupiter = obj.get_vertex_array();
-
-    // Saturn
-    let rotation_saturn = Vec3::new(0.0, 0.0, 0.0); // No initial rotation
-    let vertex_array_saturn = obj.get_vertex_array(); // Use the same sphere model
-
-    // Saturn's Rings
-    let vertex_array_rings = ring_obj.get_vertex_array(); // Use a different model if rings are unique
-    let num_rings = 6; // Número de anillos que quieres generar
-    let base_scale = 2.0f32; // Escala inicial para el primer anillo
-    let scale_increment = 0.1f32; // Incremento de escala entre anillos consecutivos
-    let base_rotation = Vec3::new(0.0, 1.0, 0.0); // Rotación inicial
-    let rotation_increment = 0.015; // Incremento en la rotación en el eje Y entre anillos
-
-    // Configuraciones para Urano
-    let rotation_urano = Vec3::new(0.0, 0.0, 0.0);
-    let vertex_array_urano = obj.get_vertex_array();
-
-    // Configuraciones para el Anillo de Urano
-    let rotation_urano_ring = Vec3::new(0.0, 0.1, 1.0); // Los anillos de Urano son notablemente inclinados
-    let scale_urano_ring = 2.4f32; // Escala del anillo respecto a Urano
-    let urano_ring_noises = create_uranus_ring_noises(); // Asumiendo que está definido
-    let vertex_array_urano_ring = ring_obj.get_vertex_array(); // Asumiendo que cargaste un modelo para los anillos
-
-    // Neptuno
-    let rotation_neptune = Vec3::new(0.0, 0.0, 0.0);
-    let vertex_array_neptune = obj.get_vertex_array();
-
-    // Plutón
-    let rotation_pluto = Vec3::new(0.0, 0.0, 0.0);
-    let vertex_array_pluto = obj.get_vertex_array();
-
-    // Eris
-    let rotation_eris = Vec3::new(0.0, 0.0, 0.0);
-    let vertex_array_eris = obj.get_vertex_array();
-
-    // Sedna
-    let rotation_sedna = Vec3::new(0.0, 0.0, 0.0);
-    let vertex_array_sedna = obj.get_vertex_array();
+    // Comparación en pantalla dividida del planeta enfocado, alternar con 'V';
+    // '1' y '2' cambian el shader de la mitad izquierda y derecha
+    let mut shader_compare_enabled = false;
+    let mut compare_shaders = [3, 5];
+    // Canal de ruido crudo que se muestra en el planeta enfocado; 'F4' recorre sus
+    // canales y vuelve a la imagen normal tras el último
+    let mut noise_channel: Option<usize> = None;
+    // Modo exploración: un sistema generado reemplaza a los planetas. 'F6' entra y
+    // sale, 'F7' genera otro con una semilla nueva; la semilla se muestra en el HUD
+    // y `--sandbox <semilla>` arranca directamente en ese sistema.
+    let mut sandbox: Option<GeneratedSystem> = cli_options
+        .sandbox_seed
+        .map(|seed| generate_system(seed, GENERATED_BODY_COUNT));
+    let mut sandbox_seed = cli_options.sandbox_seed.unwrap_or_else(rand::random);
+    // Minimapa cenital en la esquina inferior derecha, alternar con '5'
+    let mut minimap_enabled = false;
+    // Cuerpos en un color plano por cuerpo, como en una ilustración ('F5')
+    let mut flat_shading = false;
+    // Halos que mantienen visibles los cuerpos lejanos, alternar con 'F10'
+    let mut glow_enabled = true;
+
+    // Grosor de 3 píxeles a 40 unidades de la cámara; antialiasado alternable con 'L'
+    // y cinta 3D o líneas con 'Z'
+    let mut trail_style = TrailStyle {
+        color_start: Color::new(100, 100, 100),
+        color_end: Color::new(0, 0, 0),
+        thickness: 3.0,
+        reference_distance: 40.0,
+        antialiased: true,
+        ribbon: true,
+    };
 
-    // Skybox
-    let skybox = Skybox::new(5000);
+    // Vistas de depuración (profundidad, normales, alambre), se recorren con F3
+    let mut debug_view = DebugView::Off;
+
+    // Sombra de Saturno y Urano sobre sus anillos, alternar con 'I'
+    let mut ring_shadows_enabled = true;
+
+    // Luz ambiental global para el lado nocturno, ajustable con ',' y '.'
+    let mut ambient_light = DEFAULT_AMBIENT;
+    // Preajuste de iluminación, estilizado o realista; se alterna con '0'
+    let mut lighting = LightingSettings::default();
+    // Exposición manual ('RePág'/'AvPág'); con la automática ('Fin') activa hace de
+    // compensación sobre la que elige la medición
+    let mut exposure = 1.0f32;
+    let mut auto_exposure_enabled = false;
+    let mut auto_exposure = AutoExposure::new(
+        cli_options.exposure_target,
+        cli_options.exposure_speed,
+        cli_options.exposure_range,
+    );
 
-    let projection_matrix = create_perspective_matrix(window_width as f32, window_height as f32);
-    let viewport_matrix =
-        create_viewport_matrix(framebuffer_width as f32, framebuffer_height as f32);
+    // Vista cenital ('Y') o de canto ('U'); repetir la tecla vuelve a la vista anterior.
+    // Se guarda el desplazamiento del ojo respecto al centro y el vector arriba.
+    let mut view_preset: Option<ViewPreset> = None;
+    let mut view_before_preset = (Vec3::new(0.0, 10.0, 100.0), Vec3::new(0.0, 1.0, 0.0));
+
+    // Capturas con fondo transparente ('Supr'): sin estrellas y con alfa 0 donde no
+    // hay geometría
+    let mut transparent_capture = cli_options.transparent_screenshots;
+
+    // Ejes X, Y y Z por el origen, con prueba de profundidad
+    let mut axes_enabled = false;
+
+    // Resaltado de cuerpos cercanos en pantalla (conjunciones), alternar con 'H'
+    let mut proximity_highlight = false;
+    let proximity_threshold = cli_options.proximity_threshold;
+    let proximity_color = Color::from_hex(cli_options.proximity_color);
+
+    // Rayos de luz desde el Sol, alternar con 'G'
+    let mut god_rays_enabled = false;
+    let god_ray_params = GodRayParams::default();
+    // Destello de lente del Sol, alternar con 'F11'
+    let mut lens_flare_enabled = false;
+    let lens_flare_params = LensFlareParams {
+        ghosts: cli_options.flare_ghosts,
+        intensity: cli_options.flare_intensity,
+        ..LensFlareParams::default()
+    };
+
+    // Tramado ordenado contra las bandas de los degradados, alternar con 'T'
+    let mut dither_enabled = true;
+    let dither_strength = 2.0; // En niveles de 8 bits
+
+    // Simulación de daltonismo sobre la imagen final, se recorre con F9
+    let mut color_blindness = ColorBlindness::Off;
+
+    // Contornos estilizados (requieren el G-buffer), alternar con 'O'
+    let mut outlines_enabled = false;
+    let outline_color = 0x101010;
+
+    // Desenfoque de movimiento por acumulación, alternar con 'M' (intensidad con --motion-blur-decay)
+    let mut motion_blur_enabled = false;
+    let mut motion_blur = MotionBlur::new(cli_options.motion_blur_decay);
+
+    // Anaglifo rojo/cian para lentes 3D, alternar con 'N' (separación con --eye-separation)
+    let mut anaglyph_enabled = false;
+
+    // Calidad automática según el FPS, alternar con 'K'
+    let mut auto_quality_enabled = false;
+    let mut auto_quality = AutoQuality::new(TARGET_FPS);
+
+    let axes_length = cli_options.axes_length.unwrap_or_else(|| {
+        solar
+            .planets
+            .iter()
+            .map(|planet| planet.orbit_radius)
+            .fold(0.0, f32::max)
+            * AXES_MARGIN
+    });
+    let mut trails: Vec<PlanetTrail> = solar
+        .planets
+        .iter()
+        .map(|planet| PlanetTrail::new(planet.max_trail_length))
+        .collect();
+    // Solo se dibujan y simulan los primeros cuerpos, de adentro hacia afuera
+    // (`--max-bodies`, '6' y '7'); el Sol siempre está. Las lunas y los anillos
+    // siguen a su planeta.
+    let mut active_bodies = cli_options
+        .max_bodies
+        .map_or(solar.planets.len(), |count| count.min(solar.planets.len()));
+    // Factor global sobre la longitud de cada estela; conserva las proporciones entre planetas
+    let mut trail_length_scale = 1.0f32;
+    apply_trail_lengths(
+        &solar.planets,
+        &mut trails,
+        active_bodies,
+        cli_options.trail_budget,
+        trail_length_scale,
+    );
+
+    // El planeta enfocado es la selección del renderizador (índice del planeta): la
+    // cámara lo sigue y se muestra su panel de información. Tab recorre los planetas
+    // y vuelve a "ninguno" tras el último.
+    let mut solo: Option<SoloView> = None;
+    // Giro del planeta enfocado sobre su eje ('Inicio'), aparte de la simulación;
+    // al apagarlo se queda en el ángulo alcanzado
+    let mut turntable_enabled = false;
+    let mut turntable_angle = 0.0f32;
+
+    // Corte transversal por planeta (alternar con 'C' sobre el planeta enfocado).
+    // Capas de afuera hacia adentro: radio relativo al planeta y color.
+    let mut cutaway_planets = vec![false; solar.planets.len()];
+    let cutaway_layers = [
+        CutawayLayer::new(0.97, Color::new(120, 90, 60)), // Corteza
+        CutawayLayer::new(0.85, Color::new(200, 80, 30)), // Manto
+        CutawayLayer::new(0.55, Color::new(240, 170, 40)), // Núcleo externo
+        CutawayLayer::new(0.30, Color::new(255, 240, 190)), // Núcleo interno
+    ];
+
+    // 0 = órbitas configuradas, 1 = órbitas separadas; se alterna con 'X'
+    let mut explode_progress = 0.0f32;
+    let mut explode_target = 0.0f32;
+    // Malla de alambre del planeta enfocado, alternar con 'F'
+    let mut focused_wireframe = false;
+    let wireframe_color = 0x00FF80;
+
+    // Alternativa con partículas (tecla 'P'), inclinada como el eje de Saturno
+    let mut particle_ring_enabled = false;
+    let particle_ring_tilt = Vec3::new(0.0, 0.0, 26.7f32.to_radians());
+    let particle_ring_color = Color::new(215, 195, 160);
+    let mut particle_ring = match cli_options.scene_seed.rng_seed("saturn_ring") {
+        Some(seed) => ParticleRing::with_seed(
+            cli_options.ring_particles,
+            cli_options.ring_inner * solar.planets[5].scale,
+            cli_options.ring_outer * solar.planets[5].scale,
+            particle_ring_color,
+            seed,
+        ),
+        None => ParticleRing::new(
+            cli_options.ring_particles,
+            cli_options.ring_inner * solar.planets[5].scale,
+            cli_options.ring_outer * solar.planets[5].scale,
+            particle_ring_color,
+        ),
+    };
+    particle_ring.soft_distance = cli_options.particle_soft_distance;
 
-    let mut time = 0.0f32;
+    // Skybox
+    // 5000 estrellas que titilan suavemente, repartidas por todo el cielo
+    let mut skybox = Skybox::with_config(
+        &SkyboxConfig::default(),
+        cli_options.scene_seed.rng_seed("skybox"),
+    );
 
+    // `--time` arranca la simulación en ese instante. Las posiciones son funciones
+    // puras del tiempo, así que todo queda donde estaría tras correr hasta ahí;
+    // con `--hold-time` la simulación no avanza y cada captura sale igual.
+    let start_time = cli_options.start_time.map_or(0.0, sim_time_from_seconds);
+    // Pasos fijos a partir del reloj del sistema; 'R' invierte su sentido
+    let mut clock = SystemClock::new();
+    let mut simulation = Simulation::new(start_time);
+    simulation.held = cli_options.hold_time;
+    // Tiempo de la animación de las superficies: sigue al simulado salvo cuando se
+    // congela con 'F8', y al reanudar continúa desde donde quedó
+    let mut surface_time = start_time;
+    let mut surface_frozen = false;
+    let mut previous_frame_time = start_time;
+    // Con un instante inicial las estelas se rellenan con los puntos que habrían
+    // dejado hasta él, para no depender de cuánto tiempo lleva abierta la ventana
+    if cli_options.start_time.is_some() {
+        for (planet, trail) in solar
+            .planets
+            .iter()
+            .zip(trails.iter_mut())
+            .take(active_bodies)
+        {
+            for step in (0..trail.max_length).rev() {
+                trail.advance(orbit_translation(
+                    BARYCENTER,
+                    planet.orbit_radius,
+                    planet.signed_orbit_speed(),
+                    planet.phase_offset,
+                    start_time - step as f32 * TRAIL_SAMPLE_INTERVAL,
+                ));
+            }
+        }
+    }
     // Inicializar variables para el control del mouse
     let mut last_mouse_pos = (0.0, 0.0);
     let mut is_dragging = false;
@@ -701,628 +429,1063 @@ fn main() {
         last_mouse_pos_middle: (0.0, 0.0),
     };
 
+    // Promedio móvil del tiempo total de cuadro (con la espera del límite) para el HUD,
+    // y tiempo de trabajo del último cuadro (sin la espera) para la calidad automática
+    let mut average_frame_time: Option<f32> = None;
+    let mut work_time = 0.0f32;
+    // Segundos desde las últimas estadísticas de diagnóstico (RUST_LOG=debug)
+    let mut stats_elapsed = 0.0f32;
+    // Tiempo real para animar la interfaz, que sigue aunque la simulación esté detenida
+    let mut ui_time = 0.0f32;
+
+    log_info!(
+        "scene ready: bodies={} active={} trails={} ring_particles={} sandbox={:?}",
+        solar.planets.len(),
+        active_bodies,
+        trails.len(),
+        cli_options.ring_particles,
+        sandbox.as_ref().map(|system| system.seed)
+    );
+
     while window.is_open() {
-        if window.is_key_down(Key::Escape) {
+        let frame_start = Instant::now();
+        if window.is_key_down(bindings.quit) {
             break;
         }
+        if window.is_key_pressed(bindings.toggle_antialiasing, minifb::KeyRepeat::No) {
+            trail_style.antialiased = !trail_style.antialiased;
+        }
+        if window.is_key_pressed(bindings.toggle_trail_ribbon, minifb::KeyRepeat::No) {
+            trail_style.ribbon = !trail_style.ribbon;
+        }
+        if window.is_key_pressed(bindings.toggle_proximity, minifb::KeyRepeat::No) {
+            proximity_highlight = !proximity_highlight;
+        }
+        if window.is_key_pressed(bindings.toggle_outlines, minifb::KeyRepeat::No) {
+            outlines_enabled = !outlines_enabled;
+            renderer
+                .framebuffer
+                .set_gbuffer_enabled(outlines_enabled || debug_view == DebugView::Normals);
+        }
+        if window.is_key_pressed(bindings.cycle_debug_view, minifb::KeyRepeat::No) {
+            debug_view = debug_view.next();
+            renderer
+                .framebuffer
+                .set_gbuffer_enabled(outlines_enabled || debug_view == DebugView::Normals);
+        }
+        if window.is_key_pressed(bindings.toggle_cutaway, minifb::KeyRepeat::No) {
+            if let Some(index) = renderer.selected() {
+                cutaway_planets[index] = !cutaway_planets[index];
+            }
+        }
+        if window.is_key_pressed(bindings.reverse_time, minifb::KeyRepeat::No) {
+            simulation.direction = -simulation.direction;
+            for trail in trails.iter_mut() {
+                trail.reverse();
+            }
+        }
+        let mut quality_changed = false;
+        if window.is_key_pressed(bindings.toggle_auto_quality, minifb::KeyRepeat::No) {
+            auto_quality_enabled = !auto_quality_enabled;
+            if !auto_quality_enabled && auto_quality.level != DEFAULT_QUALITY_LEVEL {
+                auto_quality.set_level(DEFAULT_QUALITY_LEVEL);
+                quality_changed = true;
+            }
+        }
+        if window.is_key_pressed(bindings.toggle_shader_compare, minifb::KeyRepeat::No) {
+            shader_compare_enabled = !shader_compare_enabled;
+        }
+        if window.is_key_pressed(bindings.compare_left_next, minifb::KeyRepeat::No) {
+            compare_shaders[0] = (compare_shaders[0] + 1) % SHADER_REGISTRY_LEN;
+        }
+        if window.is_key_pressed(bindings.compare_right_next, minifb::KeyRepeat::No) {
+            compare_shaders[1] = (compare_shaders[1] + 1) % SHADER_REGISTRY_LEN;
+        }
+        if window.is_key_pressed(bindings.toggle_anaglyph, minifb::KeyRepeat::No) {
+            anaglyph_enabled = !anaglyph_enabled;
+        }
+        if window.is_key_pressed(bindings.toggle_motion_blur, minifb::KeyRepeat::No) {
+            motion_blur_enabled = !motion_blur_enabled;
+            motion_blur.reset();
+        }
+        for (key, preset) in [
+            (bindings.view_top_down, ViewPreset::TopDown),
+            (bindings.view_side, ViewPreset::Side),
+        ] {
+            if !window.is_key_pressed(key, minifb::KeyRepeat::No) {
+                continue;
+            }
+            if view_preset == Some(preset) {
+                let (offset, up) = view_before_preset;
+                camera.animate_to(
+                    camera.center + offset,
+                    camera.center,
+                    up,
+                    VIEW_PRESET_TRANSITION,
+                );
+                view_preset = None;
+            } else {
+                if view_preset.is_none() {
+                    view_before_preset = (camera.eye - camera.center, camera.up);
+                }
+                let distance = (camera.eye - camera.center).magnitude();
+                // Desde arriba, el vector arriba no puede ser Y (sería paralelo a la
+                // dirección de vista y look_at degeneraría); se usa -Z
+                let (direction, up) = match preset {
+                    ViewPreset::TopDown => (Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 0.0, -1.0)),
+                    ViewPreset::Side => (Vec3::new(0.0, 0.0, 1.0), Vec3::new(0.0, 1.0, 0.0)),
+                };
+                camera.animate_to(
+                    camera.center + direction * distance,
+                    camera.center,
+                    up,
+                    VIEW_PRESET_TRANSITION,
+                );
+                view_preset = Some(preset);
+            }
+        }
+        if window.is_key_pressed(bindings.toggle_ring_shadows, minifb::KeyRepeat::No) {
+            ring_shadows_enabled = !ring_shadows_enabled;
+        }
+        let capture_requested = window.is_key_pressed(bindings.screenshot, minifb::KeyRepeat::No);
+        if window.is_key_pressed(bindings.ambient_down, minifb::KeyRepeat::Yes) {
+            ambient_light = (ambient_light - AMBIENT_STEP).max(0.0);
+        }
+        if window.is_key_pressed(bindings.ambient_up, minifb::KeyRepeat::Yes) {
+            ambient_light = (ambient_light + AMBIENT_STEP).min(1.0);
+        }
+        if window.is_key_pressed(bindings.toggle_star_parallax, minifb::KeyRepeat::No) {
+            skybox.parallax_enabled = !skybox.parallax_enabled;
+        }
+        if window.is_key_pressed(bindings.toggle_sky_gradient, minifb::KeyRepeat::No) {
+            skybox.gradient_enabled = !skybox.gradient_enabled;
+        }
+        if window.is_key_pressed(bindings.toggle_particle_ring, minifb::KeyRepeat::No) {
+            particle_ring_enabled = !particle_ring_enabled;
+        }
+        if window.is_key_pressed(bindings.toggle_explode, minifb::KeyRepeat::No) {
+            explode_target = 1.0 - explode_target;
+        }
+        if window.is_key_pressed(bindings.toggle_dither, minifb::KeyRepeat::No) {
+            dither_enabled = !dither_enabled;
+        }
+        if window.is_key_pressed(bindings.cycle_color_blindness, minifb::KeyRepeat::No) {
+            color_blindness = color_blindness.next();
+        }
+        if window.is_key_pressed(bindings.toggle_god_rays, minifb::KeyRepeat::No) {
+            god_rays_enabled = !god_rays_enabled;
+        }
+        if window.is_key_pressed(bindings.toggle_lens_flare, minifb::KeyRepeat::No) {
+            lens_flare_enabled = !lens_flare_enabled;
+        }
+        if window.is_key_pressed(bindings.toggle_wireframe, minifb::KeyRepeat::No) {
+            focused_wireframe = !focused_wireframe;
+        }
+        let previous_trail_length_scale = trail_length_scale;
+        if window.is_key_pressed(bindings.trail_shorter, minifb::KeyRepeat::Yes) {
+            trail_length_scale =
+                (trail_length_scale / TRAIL_LENGTH_STEP).max(MIN_TRAIL_LENGTH_SCALE);
+        }
+        if window.is_key_pressed(bindings.trail_longer, minifb::KeyRepeat::Yes) {
+            trail_length_scale =
+                (trail_length_scale * TRAIL_LENGTH_STEP).min(MAX_TRAIL_LENGTH_SCALE);
+        }
+        if trail_length_scale != previous_trail_length_scale {
+            apply_trail_lengths(
+                &solar.planets,
+                &mut trails,
+                active_bodies,
+                cli_options.trail_budget,
+                trail_length_scale,
+            );
+        }
+        if window.is_key_pressed(bindings.toggle_sandbox, minifb::KeyRepeat::No) {
+            sandbox = match sandbox {
+                Some(_) => None,
+                None => Some(generate_system(sandbox_seed, GENERATED_BODY_COUNT)),
+            };
+            renderer.select(None);
+            noise_channel = None;
+        }
+        if window.is_key_pressed(bindings.regenerate_sandbox, minifb::KeyRepeat::No) {
+            sandbox_seed = rand::random();
+            sandbox = Some(generate_system(sandbox_seed, GENERATED_BODY_COUNT));
+            renderer.select(None);
+            noise_channel = None;
+        }
+        // En el modo exploración no hay planetas reales que enfocar
+        if sandbox.is_none() && window.is_key_pressed(bindings.cycle_focus, minifb::KeyRepeat::No) {
+            renderer.select(match renderer.selected() {
+                None if active_bodies > 0 => Some(0),
+                Some(index) if index + 1 < active_bodies => Some(index + 1),
+                _ => None,
+            });
+            noise_channel = None;
+            turntable_angle = 0.0;
+        }
+        if window.is_key_pressed(bindings.toggle_turntable, minifb::KeyRepeat::No) {
+            turntable_enabled = !turntable_enabled;
+        }
+        if window.is_key_pressed(bindings.toggle_solo, minifb::KeyRepeat::No) {
+            match solo.take() {
+                Some(view) => leave_solo(&mut camera, &mut turntable_enabled, view),
+                None => {
+                    if let (Some(index), None) = (renderer.selected(), &sandbox) {
+                        solo = Some(enter_solo(
+                            &mut camera,
+                            &mut turntable_enabled,
+                            index,
+                            solar.planets[index].scale,
+                        ));
+                    }
+                }
+            }
+        }
+        // Se sale sola si cambia el foco o se entra al modo exploración
+        if solo
+            .as_ref()
+            .is_some_and(|view| renderer.selected() != Some(view.index) || sandbox.is_some())
+        {
+            if let Some(view) = solo.take() {
+                leave_solo(&mut camera, &mut turntable_enabled, view);
+            }
+        }
+        if window.is_key_pressed(bindings.camera_slower, minifb::KeyRepeat::Yes) {
+            camera_speeds.slower();
+        }
+        if window.is_key_pressed(bindings.camera_faster, minifb::KeyRepeat::Yes) {
+            camera_speeds.faster();
+        }
+        if window.is_key_pressed(bindings.toggle_minimap, minifb::KeyRepeat::No) {
+            minimap_enabled = !minimap_enabled;
+        }
+        if window.is_key_pressed(bindings.toggle_glow, minifb::KeyRepeat::No) {
+            glow_enabled = !glow_enabled;
+        }
+        if window.is_key_pressed(bindings.cycle_lighting, minifb::KeyRepeat::No) {
+            lighting = lighting.next();
+        }
+        if window.is_key_pressed(bindings.exposure_up, minifb::KeyRepeat::Yes) {
+            exposure = (exposure * EXPOSURE_STEP).min(EXPOSURE_RANGE.1);
+        }
+        if window.is_key_pressed(bindings.exposure_down, minifb::KeyRepeat::Yes) {
+            exposure = (exposure / EXPOSURE_STEP).max(EXPOSURE_RANGE.0);
+        }
+        if window.is_key_pressed(bindings.toggle_auto_exposure, minifb::KeyRepeat::No) {
+            auto_exposure_enabled = !auto_exposure_enabled;
+        }
+        if window.is_key_pressed(bindings.toggle_transparent_capture, minifb::KeyRepeat::No) {
+            transparent_capture = !transparent_capture;
+        }
+        if window.is_key_pressed(bindings.toggle_axes, minifb::KeyRepeat::No) {
+            axes_enabled = !axes_enabled;
+        }
+        let total_bodies = sandbox
+            .as_ref()
+            .map_or(solar.planets.len(), |system| system.planets.len());
+        if window.is_key_pressed(bindings.fewer_bodies, minifb::KeyRepeat::Yes) && active_bodies > 0
+        {
+            active_bodies = active_bodies.min(total_bodies) - 1;
+            // La estela del cuerpo desactivado se libera; al volver crece de nuevo
+            trails[active_bodies].clear();
+            apply_trail_lengths(
+                &solar.planets,
+                &mut trails,
+                active_bodies,
+                cli_options.trail_budget,
+                trail_length_scale,
+            );
+            if renderer
+                .selected()
+                .is_some_and(|index| index >= active_bodies)
+            {
+                renderer.select(None);
+            }
+        }
+        if window.is_key_pressed(bindings.more_bodies, minifb::KeyRepeat::Yes) {
+            active_bodies = (active_bodies + 1).min(total_bodies);
+            apply_trail_lengths(
+                &solar.planets,
+                &mut trails,
+                active_bodies,
+                cli_options.trail_budget,
+                trail_length_scale,
+            );
+        }
+        if window.is_key_pressed(bindings.toggle_surface_animation, minifb::KeyRepeat::No) {
+            surface_frozen = !surface_frozen;
+        }
+        if window.is_key_pressed(bindings.toggle_flat_shading, minifb::KeyRepeat::No) {
+            flat_shading = !flat_shading;
+        }
+        // A partir de aquí la selección ya no cambia en este cuadro
+        let focused_planet = renderer.selected();
+        if let Some(index) = focused_planet {
+            if window.is_key_pressed(bindings.tweak_down, minifb::KeyRepeat::Yes) {
+                solar.params[index].1 = PLANET_TWEAKS[index].adjust(solar.params[index].1, -1.0);
+            }
+            if window.is_key_pressed(bindings.tweak_up, minifb::KeyRepeat::Yes) {
+                solar.params[index].1 = PLANET_TWEAKS[index].adjust(solar.params[index].1, 1.0);
+            }
+        }
+        if window.is_key_pressed(bindings.cycle_noise_channel, minifb::KeyRepeat::No) {
+            let channels = focused_planet.map_or(0, |index| solar.planet_noises(index).len());
+            noise_channel = match noise_channel {
+                None if channels > 0 => Some(0),
+                Some(channel) if channel + 1 < channels => Some(channel + 1),
+                _ => None,
+            };
+        }
+        // Nuevas semillas para los ruidos del planeta enfocado
+        if let Some(index) = focused_planet {
+            if window.is_key_pressed(bindings.reseed_planet, minifb::KeyRepeat::No) {
+                solar.reseed(index);
+            }
+        }
 
-        time += 100.0;
-
-        // Manejar entradas de teclado y mouse
-        handle_input(&window, &mut camera, &mut bird_eye_active, &mut mouse_state);
-        framebuffer.clear();
-
-        let mercury_angle = time * mercury_orbit_speed * 0.01;
-        let translation_mercury = Vec3::new(
-            translation_sun.x + mercury_orbit_radius * mercury_angle.cos(),
-            translation_sun.y,
-            translation_sun.z + mercury_orbit_radius * mercury_angle.sin(),
-        );
-
-        let venus_angle = time * venus_orbit_speed * 0.01;
-        let translation_venus = Vec3::new(
-            translation_sun.x + venus_orbit_radius * venus_angle.cos(),
-            translation_sun.y,
-            translation_sun.z + venus_orbit_radius * venus_angle.sin(),
-        );
-
-        let earth_angle = time * earth_orbit_speed * 0.01;
-        let translation_earth = Vec3::new(
-            translation_sun.x + earth_orbit_radius * earth_angle.cos(),
-            translation_sun.y,
-            translation_sun.z + earth_orbit_radius * earth_angle.sin(),
-        );
-
-        let mars_angle = time * mars_orbit_speed * 0.01;
-        let translation_mars = Vec3::new(
-            translation_sun.x + mars_orbit_radius * mars_angle.cos(),
-            translation_sun.y,
-            translation_sun.z + mars_orbit_radius * mars_angle.sin(),
-        );
-
-        let jupiter_angle = time * jupiter_orbit_speed * 0.01;
-        let translation_jupiter = Vec3::new(
-            translation_sun.x + jupiter_orbit_radius * jupiter_angle.cos(),
-            translation_sun.y,
-            translation_sun.z + jupiter_orbit_radius * jupiter_angle.sin(),
-        );
-
-        let saturn_angle = time * saturn_orbit_speed * 0.01;
-        let translation_saturn = Vec3::new(
-            translation_sun.x + saturn_orbit_radius * saturn_angle.cos(),
-            translation_sun.y,
-            translation_sun.z + saturn_orbit_radius * saturn_angle.sin(),
-        );
-        let translation_rings = translation_saturn;
-
-        let uranus_angle = time * uranus_orbit_speed * 0.01;
-        let translation_uranus = Vec3::new(
-            translation_sun.x + uranus_orbit_radius * uranus_angle.cos(),
-            translation_sun.y,
-            translation_sun.z + uranus_orbit_radius * uranus_angle.sin(),
-        );
-        let translation_urano_ring = translation_uranus;
-
-        let neptune_angle = time * neptune_orbit_speed * 0.01;
-        let translation_neptune = Vec3::new(
-            translation_sun.x + neptune_orbit_radius * neptune_angle.cos(),
-            translation_sun.y,
-            translation_sun.z + neptune_orbit_radius * neptune_angle.sin(),
-        );
+        // Guardar la escena tal como está ahora (cámara, ajustes y cuerpos)
+        if window.is_key_pressed(bindings.dump_scene, minifb::KeyRepeat::No) {
+            let bodies = match &sandbox {
+                Some(system) => system
+                    .planets
+                    .iter()
+                    .zip(&system.bodies)
+                    .map(|(planet, body)| BodyState {
+                        planet,
+                        noises: &body.noises,
+                        tweak: None,
+                    })
+                    .collect(),
+                None => solar
+                    .planets
+                    .iter()
+                    .enumerate()
+                    .map(|(index, planet)| BodyState {
+                        planet,
+                        noises: solar.planet_noises(index),
+                        tweak: Some(solar.params[index]),
+                    })
+                    .collect(),
+            };
+            let mut settings = vec![
+                ("active_bodies", active_bodies.to_string()),
+                ("trail_length_scale", trail_length_scale.to_string()),
+                ("glow", glow_enabled.to_string()),
+                ("god_rays", god_rays_enabled.to_string()),
+                ("lens_flare", lens_flare_enabled.to_string()),
+                ("sky_gradient", skybox.gradient_enabled.to_string()),
+                ("star_parallax", skybox.parallax_enabled.to_string()),
+                ("color_blindness", format!("\"{}\"", color_blindness.name())),
+            ];
+            if let Some(system) = &sandbox {
+                settings.push(("sandbox_seed", system.seed.to_string()));
+            }
+            let state = SceneState {
+                camera: &camera,
+                time: simulation.time(),
+                direction: simulation.direction,
+                ambient: ambient_light,
+                preset: match cli_options.scene {
+                    ScenePreset::Solar => "solar",
+                    ScenePreset::Binary => "binary",
+                },
+                seed: match cli_options.scene_seed {
+                    SceneSeed::Master(seed) => Some(seed),
+                    SceneSeed::HandTuned => None,
+                },
+                settings,
+                bodies,
+            };
+            match state.save() {
+                Ok(path) => log_info!("Saved scene to {}", path.display()),
+                Err(error) => log_error!("Could not save the scene: {}", error),
+            }
+        }
 
-        let pluto_angle = time * pluto_orbit_speed * 0.01;
-        let translation_pluto = Vec3::new(
-            translation_sun.x + pluto_orbit_radius * pluto_angle.cos(),
-            translation_sun.y,
-            translation_sun.z + pluto_orbit_radius * pluto_angle.sin(),
-        );
+        // Avanzar la simulación en pasos fijos según el tiempo real transcurrido
+        let frame_time = simulation.tick(&mut clock);
+        average_frame_time = Some(match average_frame_time {
+            Some(average) => average + (frame_time - average) * FPS_SMOOTHING,
+            None => frame_time,
+        });
+        stats_elapsed += frame_time;
+        ui_time += frame_time;
+        if turntable_enabled {
+            turntable_angle += frame_time * cli_options.turntable_speed;
+        }
+        if stats_elapsed >= 1.0 {
+            stats_elapsed = 0.0;
+            log_debug!(
+                "frame stats: frame_ms={:.2} work_ms={:.2} sim_time={:.0} active_bodies={} quality={:?}",
+                average_frame_time.unwrap_or(0.0) * 1000.0,
+                work_time * 1000.0,
+                simulation.time(),
+                active_bodies,
+                auto_quality.current()
+            );
+        }
 
-        let eris_angle = time * eris_orbit_speed * 0.01;
-        let translation_eris = Vec3::new(
-            translation_sun.x + eris_orbit_radius * eris_angle.cos(),
-            translation_sun.y,
-            translation_sun.z + eris_orbit_radius * eris_angle.sin(),
-        );
+        // Con el límite de FPS el tiempo de cuadro incluye la espera; la calidad
+        // automática debe ver solo lo que costó dibujar
+        if auto_quality_enabled {
+            quality_changed |= auto_quality.update(work_time);
+        }
+        if quality_changed {
+            let level = auto_quality.current();
+            (framebuffer_width, framebuffer_height) =
+                level.framebuffer_size(window_width, window_height);
+            renderer.resize(framebuffer_width, framebuffer_height);
+            configure_framebuffer(
+                &mut renderer.framebuffer,
+                cli_options.rasterizer,
+                clear_color,
+                outlines_enabled || debug_view == DebugView::Normals,
+            );
+            solar.set_sphere_detail(level.sphere_stacks(cli_options.sphere_stacks));
+        }
 
-        let sedna_angle = time * sedna_orbit_speed * 0.01;
-        let translation_sedna = Vec3::new(
-            translation_sun.x + sedna_orbit_radius * sedna_angle.cos(),
-            translation_sun.y,
-            translation_sun.z + sedna_orbit_radius * sedna_angle.sin(),
-        );
+        // La animación de explotar órbitas avanza con el tiempo real, no con la simulación
+        let explode_step = frame_time / EXPLODE_DURATION;
+        explode_progress = if explode_target > explode_progress {
+            (explode_progress + explode_step).min(explode_target)
+        } else {
+            (explode_progress - explode_step).max(explode_target)
+        };
+        let orbit_radii: Vec<f32> = solar
+            .planets
+            .iter()
+            .enumerate()
+            .map(|(index, planet)| exploded_orbit_radius(planet, index, explode_progress))
+            .collect();
+
+        // Las estelas se muestrean en el instante exacto de cada muestra, no por cuadro
+        simulation.step(|sample_time| {
+            for ((planet, trail), radius) in solar
+                .planets
+                .iter()
+                .zip(trails.iter_mut())
+                .zip(&orbit_radii)
+                .take(active_bodies)
+            {
+                trail.advance(orbit_translation(
+                    BARYCENTER,
+                    *radius,
+                    planet.signed_orbit_speed(),
+                    planet.phase_offset,
+                    sample_time,
+                ));
+            }
+        });
+
+        // Se renderiza interpolando entre los dos últimos pasos
+        let time = simulation.time();
+        if !surface_frozen {
+            surface_time += time - previous_frame_time;
+        }
+        previous_frame_time = time;
+        let elapsed_seconds = time / SIM_TIME_PER_STEP * FIXED_DT;
 
-        mercury_trail.add_position(translation_mercury);
-        venus_trail.add_position(translation_venus);
-        earth_trail.add_position(translation_earth);
-        mars_trail.add_position(translation_mars);
-        jupiter_trail.add_position(translation_jupiter);
-        saturn_trail.add_position(translation_saturn);
-        uranus_trail.add_position(translation_uranus);
-        neptune_trail.add_position(translation_neptune);
-        pluto_trail.add_position(translation_pluto);
-        eris_trail.add_position(translation_eris);
-        sedna_trail.add_position(translation_sedna);
-
-        // Calcular la posición de la luna orbitando alrededor de la Tierra
-        let moon_orbit_speed = 0.005; // Velocidad de órbita de la luna
-        let angle = 0.025 * time * moon_orbit_speed;
-
-        let moon_translation = Vec3::new(
-            translation_earth.x + distance_moon * angle.cos(),
-            translation_earth.y,
-            translation_earth.z + distance_moon * angle.sin(),
+        // Manejar entradas de teclado y mouse
+        handle_input(
+            &window,
+            &bindings,
+            &mut camera,
+            &camera_speeds,
+            &mut bird_eye_active,
+            &mut mouse_state,
         );
 
-        let rotation_moon = Vec3::new(0.0, angle, 0.0);
-
-        // Calcular delta_time
-        let current_time = Instant::now();
-        let delta_time = (current_time - previous_time).as_secs_f32();
-        previous_time = current_time;
-        ring1_angle += ring1_rotation_speed * delta_time;
-        ring2_angle += ring2_rotation_speed * delta_time;
+        let planet_positions = solar.planet_positions(&orbit_radii, time);
+        let sandbox_positions: Vec<Vec3> = sandbox
+            .iter()
+            .flat_map(|system| {
+                system.planets.iter().take(active_bodies).map(|planet| {
+                    orbit_translation(
+                        BARYCENTER,
+                        planet.orbit_radius,
+                        planet.signed_orbit_speed(),
+                        planet.phase_offset,
+                        time,
+                    )
+                })
+            })
+            .collect();
+        let active_positions = &planet_positions[..active_bodies];
+        let (translation_sun, translation_companion) = solar.stars(time);
+
+        camera.update_animation(frame_time);
+
+        // La cámara sigue al planeta enfocado conservando su desplazamiento relativo
+        // (o a la distancia de `--follow-distance`)
+        if let Some(index) = focused_planet {
+            camera.track(planet_positions[index]);
+        }
 
-        let phobos_orbit_speed = 0.0002; // Ajusta la velocidad de la órbita
-        let phobos_distance_from_mars = 1.5; // Distancia de Phobos a Marte
-        let phobos_orbit_angle = time * phobos_orbit_speed;
+        // Captura en alta resolución: este cuadro se dibuja con la misma cámara y el
+        // mismo tiempo en un framebuffer temporal más grande, con su propia proyección
+        // y viewport; el renderizador vuelve al tamaño de la ventana tras exportar el PNG
+        if capture_requested {
+            let (width, height) = screenshot::capture_size(window_width, window_height);
+            renderer.resize(width, height);
+            configure_framebuffer(
+                &mut renderer.framebuffer,
+                cli_options.rasterizer,
+                clear_color,
+                outlines_enabled || debug_view == DebugView::Normals,
+            );
+        }
 
-        // Cálculo de la nueva posición de Phobos en órbita
-        let phobos_translation = Vec3::new(
-            translation_mars.x + phobos_distance_from_mars * phobos_orbit_angle.cos(),
-            translation_mars.y + phobos_distance_from_mars * phobos_orbit_angle.sin(),
-            translation_mars.z,
+        // Las vistas cenital y de canto pasan a ortográfica al terminar la transición;
+        // el encuadre depende de la distancia, así que el zoom sigue funcionando
+        let orthographic = view_preset.is_some() && !camera.is_animating();
+        let (width, height) = (
+            renderer.framebuffer.width as f32,
+            renderer.framebuffer.height as f32,
         );
-
-        // Renderizar el Skybox
-        let default_noise = create_default_noise();
-        let uniforms_skybox = Uniforms {
-            model_matrix: Mat4::identity(),
-            view_matrix: create_view_matrix(camera.eye, camera.center, camera.up),
-            projection_matrix,
-            viewport_matrix,
-            time,
-            noises: vec![&default_noise],
-        };
-        skybox.render(&mut framebuffer, &uniforms_skybox, camera.eye);
-
-        let sun_noises_refs: Vec<&FastNoiseLite> = sun_noises.iter().collect();
-        let uniforms_sun = Uniforms {
-            model_matrix: create_model_matrix(translation_sun, scale_sun, rotation_sun),
-            view_matrix: create_view_matrix(camera.eye, camera.center, camera.up),
-            projection_matrix,
-            viewport_matrix,
-            time,
-            noises: sun_noises_refs,
-        };
-
-        // Uniforms de la Tierra
-        let earth_noise_refs: Vec<&FastNoiseLite> = earth_noises.iter().collect();
-        let uniforms_earth = Uniforms {
-            model_matrix: create_model_matrix(translation_earth, scale_earth, rotation_earth),
-            view_matrix: create_view_matrix(camera.eye, camera.center, camera.up),
-            projection_matrix,
-            viewport_matrix,
-            time,
-            noises: earth_noise_refs,
-        };
-
-        let jupiter_noise_refs: Vec<&FastNoiseLite> = jupiter_noises.iter().collect();
-        let uniforms_jupiter = Uniforms {
-            model_matrix: create_model_matrix(translation_jupiter, scale_jupiter, rotation_jupiter),
-            view_matrix: create_view_matrix(camera.eye, camera.center, camera.up),
-            projection_matrix,
-            viewport_matrix,
-            time,
-            noises: jupiter_noise_refs,
-        };
-
-        let moon_noise_refs: Vec<&FastNoiseLite> = moon_noises.iter().collect();
-        let uniforms_moon = Uniforms {
-            model_matrix: create_model_matrix(moon_translation, scale_moon, rotation_moon),
-            view_matrix: create_view_matrix(camera.eye, camera.center, camera.up),
-            projection_matrix,
-            viewport_matrix,
-            time,
-            noises: moon_noise_refs,
-        };
-
-        let rotation_ring1 = Vec3::new(0.0, 0.0, ring1_angle);
-        let uniforms_ring = Uniforms {
-            model_matrix: create_model_matrix(moon_translation, scale_ring, rotation_ring1),
-            view_matrix: create_view_matrix(camera.eye, camera.center, camera.up),
-            projection_matrix,
-            viewport_matrix,
-            time,
-            noises: vec![], // Puedes agregar noises si los necesitas para el shader
-        };
-
-        let rotation_ring2 = Vec3::new(ring2_angle, 0.0, 0.0);
-        let uniforms_ring2 = Uniforms {
-            model_matrix: create_model_matrix(moon_translation, scale_ring2, rotation_ring2),
-            view_matrix: create_view_matrix(camera.eye, camera.center, camera.up),
-            projection_matrix,
-            viewport_matrix,
-            time,
-            noises: vec![],
-        };
-
-        let uniforms_venus = Uniforms {
-            model_matrix: create_model_matrix(translation_venus, scale_venus, rotation_venus),
-            view_matrix: create_view_matrix(camera.eye, camera.center, camera.up),
-            projection_matrix,
-            viewport_matrix,
-            time,
-            noises: venus_noises.iter().collect(),
-        };
-
-        let uniforms_mercury = Uniforms {
-            model_matrix: create_model_matrix(translation_mercury, scale_mercury, rotation_mercury),
-            view_matrix: create_view_matrix(camera.eye, camera.center, camera.up),
-            projection_matrix,
-            viewport_matrix,
-            time,
-            noises: mercury_noises.iter().collect(),
-        };
-
-        // Crear uniforms para Marte y Phobos
-        let uniforms_mars = Uniforms {
-            model_matrix: create_model_matrix(translation_mars, scale_mars, rotation_mars),
-            view_matrix: create_view_matrix(camera.eye, camera.center, camera.up),
-            projection_matrix,
-            viewport_matrix,
-            time,
-            noises: mars_noises.iter().collect(),
-        };
-
-        let uniforms_phobos = Uniforms {
-            model_matrix: create_model_matrix(phobos_translation, scale_phobos, rotation_phobos),
-            view_matrix: create_view_matrix(camera.eye, camera.center, camera.up),
-            projection_matrix,
-            viewport_matrix,
-            time,
-            noises: phobos_noises.iter().collect(),
-        };
-
-        // Uniforms for Saturn
-        let uniforms_saturn = Uniforms {
-            model_matrix: create_model_matrix(translation_saturn, scale_saturn, rotation_saturn),
-            view_matrix: create_view_matrix(camera.eye, camera.center, camera.up),
-            projection_matrix,
-            viewport_matrix,
-            time,
-            noises: saturn_noises.iter().collect(),
+        renderer.projection_matrix = if orthographic {
+            create_orthographic_matrix(width, height, (camera.eye - camera.center).magnitude())
+        } else {
+            create_perspective_matrix(width, height)
         };
 
-        // Uniforms para Urano
-        let uniforms_urano = Uniforms {
-            model_matrix: create_model_matrix(translation_uranus, scale_uranus, rotation_urano),
-            view_matrix: create_view_matrix(camera.eye, camera.center, camera.up),
-            projection_matrix,
-            viewport_matrix,
+        // Giro del planeta enfocado, en su espacio de modelo
+        let turntable = rotation(turntable_angle, &Vec3::y());
+        // Qué se muestra de los cuerpos en este cuadro
+        let view_uniforms = renderer.uniforms(&camera, Mat4::identity(), time, vec![]);
+        let solar_frame = SolarFrame {
             time,
-            noises: uranus_noises.iter().collect(),
-        };
-
-        // Uniforms para el Anillo de Urano
-        let uniforms_urano_ring = Uniforms {
-            model_matrix: create_model_matrix(
-                translation_urano_ring,
-                scale_urano_ring,
-                rotation_urano_ring,
+            elapsed_seconds,
+            positions: planet_positions,
+            sun: translation_sun,
+            companion: translation_companion,
+            active_bodies,
+            solo: solo.as_ref().map(|view| view.index),
+            flat_shading,
+            ring_shadows: ring_shadows_enabled,
+            saturn_rings: !particle_ring_enabled,
+            focus: focused_planet.map(|index| Focus {
+                index,
+                turntable,
+                compare: shader_compare_enabled.then_some(ShaderCompare {
+                    shaders: compare_shaders,
+                    width: renderer.framebuffer.width,
+                    height: renderer.framebuffer.height,
+                }),
+                noise_channel,
+            }),
+            cutaway: &cutaway_planets,
+            cutaway_layers: &cutaway_layers,
+            noise_layers: solar.noise_layers(
+                &planet_positions,
+                &view_uniforms,
+                (framebuffer_width / window_width).max(1) as f32,
             ),
-            view_matrix: create_view_matrix(camera.eye, camera.center, camera.up),
-            projection_matrix,
-            viewport_matrix,
-            time,
-            noises: urano_ring_noises.iter().collect(),
-        };
-
-        // Neptuno
-        let uniforms_neptune = Uniforms {
-            model_matrix: create_model_matrix(translation_neptune, scale_neptune, rotation_neptune),
-            view_matrix: create_view_matrix(camera.eye, camera.center, camera.up),
-            projection_matrix,
-            viewport_matrix,
-            time,
-            noises: neptune_noises.iter().collect(),
-        };
-
-        // Plutón
-        let uniforms_pluto = Uniforms {
-            model_matrix: create_model_matrix(translation_pluto, scale_pluto, rotation_pluto),
-            view_matrix: create_view_matrix(camera.eye, camera.center, camera.up),
-            projection_matrix,
-            viewport_matrix,
-            time,
-            noises: pluto_noises.iter().collect(),
-        };
-
-        // Eris
-        let uniforms_eris = Uniforms {
-            model_matrix: create_model_matrix(translation_eris, scale_eris, rotation_eris),
-            view_matrix: create_view_matrix(camera.eye, camera.center, camera.up),
-            projection_matrix,
-            viewport_matrix,
-            time,
-            noises: eris_noises.iter().collect(),
+            sandbox: sandbox
+                .as_ref()
+                .map(|system| (system, sandbox_positions.as_slice())),
         };
 
-        // Sedna
-        let uniforms_sedna = Uniforms {
-            model_matrix: create_model_matrix(translation_sedna, scale_sedna, rotation_sedna),
-            view_matrix: create_view_matrix(camera.eye, camera.center, camera.up),
-            projection_matrix,
-            viewport_matrix,
-            time,
-            noises: sedna_noises.iter().collect(),
+        // Una pasada normal, o dos (ojo izquierdo y derecho) en modo anaglifo
+        let eye_offsets: &[f32] = if anaglyph_enabled {
+            &[-0.5, 0.5]
+        } else {
+            &[0.0]
         };
+        let mut left_eye_buffer: Option<Vec<u32>> = None;
+        for &eye_offset in eye_offsets {
+            // Ejes paralelos: ambos ojos se desplazan junto con el punto de mira
+            let shift = camera.right() * eye_offset * cli_options.eye_separation;
+            let camera = Camera::new(camera.eye + shift, camera.center + shift, camera.up);
+            renderer
+                .framebuffer
+                .set_background_color(if solo.is_some() {
+                    SOLO_BACKGROUND
+                } else {
+                    clear_color
+                });
+
+            // En ortográfica las estrellas no están "en el infinito"; la vista
+            // esquemática queda más limpia sin ellas. La captura transparente tampoco
+            // las lleva: su fondo tiene que quedar vacío.
+            let transparent_frame = transparent_capture && capture_requested;
+            let show_stars = !orthographic && solo.is_none() && !transparent_frame;
+            let scene = Scene {
+                skybox: show_stars.then_some(&skybox),
+                ambient: ambient_light,
+                lighting,
+                surface_time: Some(surface_time),
+                ..solar.scene(&solar_frame)
+            };
+            renderer.render_scene(&scene, &camera, time);
+
+            // Lo que se dibuja sobre la escena usa solo las matrices de este ojo
+            let view_uniforms = renderer.uniforms(&camera, Mat4::identity(), time, vec![]);
+            let framebuffer = &mut renderer.framebuffer;
+
+            // Las partículas del anillo de Saturno mezclan con lo ya dibujado, así
+            // que van después de los cuerpos
+            if particle_ring_enabled && active_bodies > 5 && solo.is_none() && sandbox.is_none() {
+                let uniforms_particle_ring = Uniforms {
+                    model_matrix: create_model_matrix(planet_positions[5], 1.0, particle_ring_tilt),
+                    ..view_uniforms.clone()
+                };
+                particle_ring.render(framebuffer, &uniforms_particle_ring, elapsed_seconds);
+            }
+
+            // Malla de alambre solo sobre el planeta enfocado
+            if let (true, Some(index)) = (focused_wireframe, focused_planet) {
+                let uniforms_wireframe = Uniforms {
+                    model_matrix: solar.planet_model_matrix(index, planet_positions[index], time)
+                        * turntable,
+                    ..view_uniforms.clone()
+                };
+                render_wireframe(
+                    framebuffer,
+                    &uniforms_wireframe,
+                    solar.planet_mesh(index),
+                    wireframe_color,
+                    camera.eye,
+                );
+            }
+
+            // Vista de alambre: se borra el color pero se conserva la profundidad,
+            // así las mallas quedan con las líneas ocultas eliminadas. Solo los
+            // cuerpos que se dibujaron (los activos, o el de la vista individual).
+            if debug_view == DebugView::Wireframe {
+                framebuffer.buffer.fill(0x000000);
+                let bodies = (0..active_bodies)
+                    .filter(|&index| {
+                        sandbox.is_none() && solo.as_ref().is_none_or(|view| view.index == index)
+                    })
+                    .map(|index| {
+                        let mut model_matrix =
+                            solar.planet_model_matrix(index, planet_positions[index], time);
+                        if focused_planet == Some(index) {
+                            model_matrix *= turntable;
+                        }
+                        (model_matrix, solar.planet_mesh(index))
+                    });
+                let sun = solo
+                    .is_none()
+                    .then(|| (sun_model_matrix(translation_sun), solar.sphere()));
+                for (model_matrix, mesh) in bodies.chain(sun) {
+                    let uniforms = Uniforms {
+                        model_matrix,
+                        ..view_uniforms.clone()
+                    };
+                    render_wireframe(framebuffer, &uniforms, mesh, wireframe_color, camera.eye);
+                }
+            }
+
+            // Rayos de luz del Sol, solo si es visible
+            if god_rays_enabled && solo.is_none() {
+                if let Some(sun_screen) = visible_sun_position(
+                    framebuffer,
+                    &view_uniforms,
+                    translation_sun,
+                    SUN_SCALE,
+                    camera.eye,
+                ) {
+                    postprocess::apply_god_rays(framebuffer, sun_screen, &god_ray_params);
+                }
+            }
+
+            // El destello se mide ahora, con el zbuffer de los cuerpos, pero se suma
+            // al final para que quede sobre las estelas (es un reflejo en la lente)
+            let sun_flare = if lens_flare_enabled && solo.is_none() {
+                sun_visibility(
+                    framebuffer,
+                    &view_uniforms,
+                    translation_sun,
+                    SUN_SCALE,
+                    camera.eye,
+                )
+            } else {
+                None
+            };
 
-        // Renderizar la Tierra
-        render(
-            &mut framebuffer,
-            &uniforms_earth,
-            &vertex_array_earth,
-            shader_earth,
-        );
-
-        // Renderizar la Luna
-        render(
-            &mut framebuffer,
-            &uniforms_moon,
-            &vertex_array_moon,
-            shader_moon,
-        );
-
-        render(
-            &mut framebuffer,
-            &uniforms_ring,
-            &vertex_array_ring,
-            shader_ring,
-        );
-
-        render(
-            &mut framebuffer,
-            &uniforms_ring2,
-            &vertex_array_ring,
-            shader_ring,
-        );
-
-        render(
-            &mut framebuffer,
-            &uniforms_venus,
-            &vertex_array_venus,
-            shader_venus,
-        );
-
-        render(
-            &mut framebuffer,
-            &uniforms_mercury,
-            &vertex_array_mercury,
-            shader_mercury,
-        );
-
-        // Renderizar Júpiter
-        render(
-            &mut framebuffer,
-            &uniforms_jupiter,
-            &vertex_array_jupiter,
-            shader_jupiter,
-        );
-
-        // Agregar renderizado de Marte y Phobos
-        render(
-            &mut framebuffer,
-            &uniforms_mars,
-            &vertex_array_mars,
-            shader_mars,
-        );
-
-        render(
-            &mut framebuffer,
-            &uniforms_phobos,
-            &vertex_array_phobos,
-            shader_phobos,
-        );
+            // Post-proceso de contornos sobre la geometría opaca, antes de estelas y overlays
+            if outlines_enabled {
+                postprocess::apply_outlines(framebuffer, outline_color, 0.05, 0.5, camera.eye);
+            }
+
+            // Ejes de coordenadas; los cuerpos los tapan
+            if axes_enabled && solo.is_none() {
+                let thickness = (framebuffer_width / window_width).max(1);
+                for (axis, color) in [
+                    (Vec3::x(), Color::new(230, 60, 60)),
+                    (Vec3::y(), Color::new(60, 200, 80)),
+                    (Vec3::z(), Color::new(70, 110, 240)),
+                ] {
+                    render_line(
+                        framebuffer,
+                        &view_uniforms,
+                        BARYCENTER - axis * axes_length,
+                        BARYCENTER + axis * axes_length,
+                        color,
+                        thickness,
+                    );
+                }
+            }
+
+            // Los cuerpos generados no dejan estela
+            if sandbox.is_none() && solo.is_none() {
+                for trail in trails.iter().take(active_bodies) {
+                    render_trail(framebuffer, &view_uniforms, trail, &trail_style);
+                }
+            }
+
+            if glow_enabled && solo.is_none() {
+                let glow_radius = GLOW_RADIUS * (framebuffer_width / window_width).max(1) as f32;
+                match &sandbox {
+                    Some(system) => render_glows(
+                        framebuffer,
+                        &view_uniforms,
+                        &system.planets,
+                        &sandbox_positions,
+                        camera.eye,
+                        glow_radius,
+                    ),
+                    None => render_glows(
+                        framebuffer,
+                        &view_uniforms,
+                        &solar.planets,
+                        active_positions,
+                        camera.eye,
+                        glow_radius,
+                    ),
+                }
+            }
+
+            // Anillo que marca el planeta enfocado, encima de todo
+            if let (Some(index), None, None) = (focused_planet, &sandbox, &solo) {
+                render_focus_ring(
+                    framebuffer,
+                    &view_uniforms,
+                    planet_positions[index],
+                    solar.planets[index].scale,
+                    ui_time,
+                    (framebuffer_width / window_width).max(1) as f32,
+                );
+            }
+
+            if proximity_highlight && solo.is_none() {
+                render_proximity_highlights(
+                    framebuffer,
+                    &view_uniforms,
+                    if sandbox.is_some() {
+                        &sandbox_positions
+                    } else {
+                        active_positions
+                    },
+                    proximity_threshold,
+                    proximity_color,
+                );
+            }
+
+            if let Some((sun_screen, visibility)) = sun_flare {
+                postprocess::apply_lens_flare(
+                    framebuffer,
+                    sun_screen,
+                    visibility,
+                    &lens_flare_params,
+                );
+            }
+
+            if anaglyph_enabled && left_eye_buffer.is_none() {
+                left_eye_buffer = Some(framebuffer.buffer.clone());
+            }
+        }
+        if let Some(left_eye_buffer) = &left_eye_buffer {
+            postprocess::combine_anaglyph(left_eye_buffer, &mut renderer.framebuffer);
+        }
 
-        render(
-            &mut framebuffer,
-            &uniforms_saturn,
-            &vertex_array_saturn,
-            shader_saturn,
-        );
+        // El desenfoque va sobre la escena compuesta; el HUD se dibuja después.
+        // La captura no lo usa: su historial es el de la ventana.
+        if motion_blur_enabled && !capture_requested {
+            motion_blur.apply(&mut renderer.framebuffer);
+        }
 
-        for i in 0..num_rings {
-            let scale = base_scale + (i as f32 * scale_increment);
-            let rotation = Vec3::new(
-                0.0,
-                1.0,
-                base_rotation.y
-                    + (i as f32 * rotation_increment * if i % 2 == 0 { 1.0 } else { -1.0 }),
-            );
+        // Las vistas de profundidad y normales reemplazan la imagen al presentar; se
+        // muestran tal cual, sin exposición ni tono
+        let shows_buffer = matches!(debug_view, DebugView::Depth | DebugView::Normals);
+        match debug_view {
+            DebugView::Depth => postprocess::show_depth(
+                &mut renderer.framebuffer,
+                NEAR_PLANE,
+                FAR_PLANE,
+                SKYBOX_DEPTH,
+            ),
+            DebugView::Normals => postprocess::show_normals(&mut renderer.framebuffer),
+            DebugView::Off | DebugView::Wireframe => {}
+        }
 
-            let uniforms_ring = Uniforms {
-                model_matrix: create_model_matrix(translation_rings, scale, rotation),
-                view_matrix: create_view_matrix(camera.eye, camera.center, camera.up),
-                projection_matrix,
-                viewport_matrix,
-                time,
-                noises: vec![], // Los anillos no requieren ruido en este ajuste
+        // Presentación: el daltonismo y el tramado van sobre la escena final, pero
+        // no sobre el HUD; el tramado al final, ya en los valores que se muestran
+        // La medición es sobre la escena sin exponer, así no se realimenta
+        if !shows_buffer {
+            let scene_exposure = if auto_exposure_enabled {
+                exposure * auto_exposure.update(&renderer.framebuffer, frame_time)
+            } else {
+                exposure
             };
-
-            render(
-                &mut framebuffer,
-                &uniforms_ring,
-                &vertex_array_rings,
-                shader_ring,
+            postprocess::apply_tone_mapping(
+                &mut renderer.framebuffer,
+                lighting.tone_mapping,
+                lighting.gamma,
+                scene_exposure,
             );
         }
+        postprocess::simulate_color_blindness(&mut renderer.framebuffer, color_blindness);
+        if dither_enabled && debug_view == DebugView::Off {
+            postprocess::apply_dither(&mut renderer.framebuffer, dither_strength);
+        }
 
-        // Renderizar Urano
-        render(
-            &mut framebuffer,
-            &uniforms_urano,
-            &vertex_array_urano,
-            shader_uranus,
-        );
-
-        // Renderizar el Anillo de Urano
-        render(
-            &mut framebuffer,
-            &uniforms_urano_ring,
-            &vertex_array_urano_ring,
-            shader_uranus_ring,
-        );
-
-        render(
-            &mut framebuffer,
-            &uniforms_neptune,
-            &vertex_array_neptune,
-            shader_neptune,
-        );
-
-        render(
-            &mut framebuffer,
-            &uniforms_pluto,
-            &vertex_array_pluto,
-            shader_pluto,
-        );
-
-        render(
-            &mut framebuffer,
-            &uniforms_eris,
-            &vertex_array_eris,
-            shader_eris,
-        );
-
-        render(
-            &mut framebuffer,
-            &uniforms_sedna,
-            &vertex_array_sedna,
-            shader_sedna,
-        );
+        // La captura se guarda sin HUD. La ventana conserva el cuadro anterior, así
+        // que no hay parpadeo; el tiempo de este cuadro tampoco cuenta para la
+        // calidad automática.
+        if capture_requested {
+            let capture = &renderer.framebuffer;
+            match screenshot::next_screenshot_path() {
+                Ok(path) => match screenshot::save_png(capture, &path, transparent_capture) {
+                    Ok(()) => log_info!(
+                        "Saved {}x{} screenshot to {}",
+                        capture.width,
+                        capture.height,
+                        path.display()
+                    ),
+                    Err(error) => log_error!("Could not save screenshot: {}", error),
+                },
+                Err(error) => log_error!("Could not create screenshot folder: {}", error),
+            }
+            renderer.resize(framebuffer_width, framebuffer_height);
+            configure_framebuffer(
+                &mut renderer.framebuffer,
+                cli_options.rasterizer,
+                clear_color,
+                outlines_enabled || debug_view == DebugView::Normals,
+            );
+            window.update();
+            continue;
+        }
 
-        let color_start = Color::new(100, 100, 100); // Blanco
-        let color_end = Color::new(0, 0, 0); // Negro (o el color del fondo)
+        // El HUD mantiene su tamaño en pantalla aunque cambie la resolución de render
+        let hud_scale = (2 * framebuffer_width / window_width).max(1);
+
+        // Minimapa: el Sol y los planetas vistos desde arriba, dibujados en un
+        // framebuffer pequeño aparte y pegados sobre el cuadro
+        if minimap_enabled {
+            let size = (cli_options.minimap_size * framebuffer_width / window_width).max(1);
+            let mut minimap = Framebuffer::new(size, size);
+            minimap.set_rasterizer(cli_options.rasterizer);
+            let minimap_camera = Camera::new(
+                Vec3::new(0.0, MINIMAP_HEIGHT, 0.0),
+                Vec3::zeros(),
+                Vec3::new(0.0, 0.0, -1.0),
+            );
+            let scene = solar.minimap_scene(&solar_frame);
+            render_scene_into(&mut minimap, &scene, &minimap_camera, time);
+
+            let margin = 5 * hud_scale;
+            let x = renderer.framebuffer.width.saturating_sub(size + margin);
+            let y = renderer.framebuffer.height.saturating_sub(size + margin);
+            renderer.framebuffer.blit(&minimap, x, y);
+            // Marco de un píxel alrededor
+            let (left, top) = (x.saturating_sub(1), y.saturating_sub(1));
+            let (right, bottom) = (x + size, y + size);
+            for column in left..=right {
+                renderer.framebuffer.set_pixel(column, top, 0x808080);
+                renderer.framebuffer.set_pixel(column, bottom, 0x808080);
+            }
+            for row in top..=bottom {
+                renderer.framebuffer.set_pixel(left, row, 0x808080);
+                renderer.framebuffer.set_pixel(right, row, 0x808080);
+            }
+        }
+        if let Some(index) = focused_planet {
+            let planet = &solar.planets[index];
+            let distance = (camera.eye - planet_positions[index]).magnitude();
+            render_planet_info(&mut renderer.framebuffer, planet, distance, hud_scale);
+        }
 
-        // Crea uniforms para las estelas si es necesario
-        let uniforms_trail = Uniforms {
-            model_matrix: Mat4::identity(),
-            view_matrix: create_view_matrix(camera.eye, camera.center, camera.up),
-            projection_matrix,
-            viewport_matrix,
-            time,
-            noises: vec![],
-        };
+        if shader_compare_enabled && focused_planet.is_some() {
+            render_shader_compare_overlay(
+                &mut renderer.framebuffer,
+                solar.shader_registry()[compare_shaders[0]].0,
+                solar.shader_registry()[compare_shaders[1]].0,
+                hud_scale,
+            );
+        }
 
-        render_trail(
-            &mut framebuffer,
-            &uniforms_trail,
-            &mercury_trail,
-            color_start,
-            color_end,
-            trail_thickness,
-        );
-        render_trail(
-            &mut framebuffer,
-            &uniforms_trail,
-            &venus_trail,
-            color_start,
-            color_end,
-            trail_thickness,
-        );
-        render_trail(
-            &mut framebuffer,
-            &uniforms_trail,
-            &earth_trail,
-            color_start,
-            color_end,
-            trail_thickness,
-        );
-        render_trail(
-            &mut framebuffer,
-            &uniforms_trail,
-            &mars_trail,
-            color_start,
-            color_end,
-            trail_thickness,
-        );
-        render_trail(
-            &mut framebuffer,
-            &uniforms_trail,
-            &jupiter_trail,
-            color_start,
-            color_end,
-            trail_thickness,
-        );
-        render_trail(
-            &mut framebuffer,
-            &uniforms_trail,
-            &saturn_trail,
-            color_start,
-            color_end,
-            trail_thickness,
-        );
-        render_trail(
-            &mut framebuffer,
-            &uniforms_trail,
-            &uranus_trail,
-            color_start,
-            color_end,
-            trail_thickness,
-        );
-        render_trail(
-            &mut framebuffer,
-            &uniforms_trail,
-            &neptune_trail,
-            color_start,
-            color_end,
-            trail_thickness,
-        );
-        render_trail(
-            &mut framebuffer,
-            &uniforms_trail,
-            &pluto_trail,
-            color_start,
-            color_end,
-            trail_thickness,
-        );
-        render_trail(
-            &mut framebuffer,
-            &uniforms_trail,
-            &eris_trail,
-            color_start,
-            color_end,
-            trail_thickness,
-        );
-        render_trail(
-            &mut framebuffer,
-            &uniforms_trail,
-            &sedna_trail,
-            color_start,
-            color_end,
-            trail_thickness,
-        );
+        // Arriba a la izquierda, una línea por dato: FPS reales frente al límite,
+        // la calidad automática y la vista de depuración si están activas
+        let mut status_lines = Vec::new();
+        if let Some(average) = average_frame_time.filter(|&average| average > 0.0) {
+            status_lines.push(match cli_options.fps_cap {
+                Some(cap) => format!("FPS: {:.0} / {:.0}", 1.0 / average, cap),
+                None => format!("FPS: {:.0}", 1.0 / average),
+            });
+        }
+        if auto_quality_enabled {
+            let level = auto_quality.current();
+            status_lines.push(match auto_quality.average_fps() {
+                Some(fps) => format!("Calidad: {} ({:.0} FPS de trabajo)", level.name, fps),
+                None => format!("Calidad: {}", level.name),
+            });
+        }
+        status_lines.push(format!("Cámara: x{:.2}", camera_speeds.factor));
+        if debug_view != DebugView::Off {
+            status_lines.push(format!("Vista: {}", debug_view.name()));
+        }
+        status_lines.push(format!("Iluminación: {}", lighting.name));
+        if transparent_capture {
+            status_lines.push("Capturas: fondo transparente".to_string());
+        }
+        if auto_exposure_enabled {
+            status_lines.push(format!(
+                "Exposición: x{:.2} (auto x{:.2})",
+                exposure,
+                auto_exposure.exposure()
+            ));
+        } else if exposure != 1.0 {
+            status_lines.push(format!("Exposición: x{:.2}", exposure));
+        }
+        if let Some(view) = &solo {
+            status_lines.push(format!(
+                "Vista individual: {}",
+                solar.planets[view.index].name
+            ));
+        }
+        if color_blindness != ColorBlindness::Off {
+            status_lines.push(format!("Daltonismo: {}", color_blindness.name()));
+        }
+        if surface_frozen {
+            status_lines.push("Superficies congeladas".to_string());
+        }
+        if let Some(system) = &sandbox {
+            status_lines.push(format!("Exploración: semilla {}", system.seed));
+        }
+        if active_bodies < total_bodies {
+            status_lines.push(format!("Cuerpos: {} de {}", active_bodies, total_bodies));
+        }
+        if let Some(index) = focused_planet {
+            let (name, value) = solar.params[index];
+            status_lines.push(format!("Ajuste: {} = {:.3}", name, value));
+        }
+        if let (Some(channel), Some(index)) = (noise_channel, focused_planet) {
+            status_lines.push(format!(
+                "Ruido: canal {} de {}",
+                channel + 1,
+                solar.planet_noises(index).len()
+            ));
+        }
+        for (line, label) in status_lines.iter().enumerate() {
+            let y = 10 + line * hud::line_height(hud_scale);
+            hud::draw_text(&mut renderer.framebuffer, 10, y, label, 0xFFFFFF, hud_scale);
+        }
 
-        render(
-            &mut framebuffer,
-            &uniforms_sun,
-            &vertex_array_sun,
-            fragment_shader,
-        );
+        renderer.finish_frame(time);
+
+        // Con SSAA se promedia cada bloque de píxeles antes de presentar
+        let ssaa_factor = auto_quality.current().ssaa_factor();
+        if ssaa_factor > 1 {
+            let buffer = postprocess::downsample(&renderer.framebuffer, ssaa_factor);
+            window
+                .update_with_buffer(
+                    &buffer,
+                    framebuffer_width / ssaa_factor,
+                    framebuffer_height / ssaa_factor,
+                )
+                .map_err(RenderError::Present)?;
+        } else {
+            window
+                .update_with_buffer(
+                    &renderer.framebuffer.buffer,
+                    framebuffer_width,
+                    framebuffer_height,
+                )
+                .map_err(RenderError::Present)?;
+        }
 
-        window
-            .update_with_buffer(&framebuffer.buffer, framebuffer_width, framebuffer_height)
-            .unwrap();
+        // Límite de FPS: se duerme lo que falta del cuadro descontando lo ya gastado
+        work_time = frame_start.elapsed().as_secs_f32();
+        if let Some(cap) = cli_options.fps_cap {
+            let frame_budget = Duration::from_secs_f32(1.0 / cap);
+            if let Some(remaining) = frame_budget.checked_sub(frame_start.elapsed()) {
+                std::thread::sleep(remaining);
+            }
+        }
     }
+
+    Ok(())
 }
 
 fn handle_input(
     window: &Window,
+    bindings: &KeyBindings,
     camera: &mut Camera,
+    speeds: &CameraSpeeds,
     bird_eye_active: &mut bool,
     mouse_state: &mut MouseState,
 ) {
-    let movement_speed = 2.0;
-    let rotation_speed = std::f32::consts::PI / 400.0; // Reducido para una rotación más suave
-    let zoom_speed = 0.05; // Reducido para zoom más controlado
+    // El vuelo y el zoom se escalan con la distancia al centro; el modo original y
+    // el paneo giran el punto de mira, así que no dependen de ella
+    let distance_scale = speeds.distance_scale(camera);
+    let movement_speed = speeds.movement * speeds.factor;
+    let fly_speed = speeds.fly * speeds.factor * distance_scale; // Unidades por cuadro
+    let rotation_speed = speeds.rotation;
+    let zoom_speed = speeds.zoom * speeds.factor * distance_scale;
 
     // Controles de órbita de la cámara con teclado
-    if window.is_key_down(Key::Left) {
+    if window.is_key_down(bindings.orbit_left) {
         camera.orbit(rotation_speed, 0.0);
     }
-    if window.is_key_down(Key::Right) {
+    if window.is_key_down(bindings.orbit_right) {
         camera.orbit(-rotation_speed, 0.0);
     }
-    if window.is_key_down(Key::W) {
+    if window.is_key_down(bindings.orbit_up) {
         camera.orbit(0.0, -rotation_speed);
     }
-    if window.is_key_down(Key::S) {
+    if window.is_key_down(bindings.orbit_down) {
         camera.orbit(0.0, rotation_speed);
     }
 
     // Controles de movimiento de la cámara con teclado
     let mut movement = Vec3::new(0.0, 0.0, 0.0);
-    if window.is_key_down(Key::A) {
-        movement.x -= movement_speed;
+    if window.is_key_down(bindings.move_left) {
+        movement.x -= 1.0;
     }
-    if window.is_key_down(Key::D) {
-        movement.x += movement_speed;
+    if window.is_key_down(bindings.move_right) {
+        movement.x += 1.0;
     }
-    if window.is_key_down(Key::Q) {
-        movement.y += movement_speed;
+    if window.is_key_down(bindings.move_up) {
+        movement.y += 1.0;
     }
-    if window.is_key_down(Key::E) {
-        movement.y -= movement_speed;
+    if window.is_key_down(bindings.move_down) {
+        movement.y -= 1.0;
+    }
+    if bindings.scheme == MovementScheme::Fly {
+        if window.is_key_down(bindings.move_forward) {
+            movement.z += 1.0;
+        }
+        if window.is_key_down(bindings.move_backward) {
+            movement.z -= 1.0;
+        }
     }
     if movement.magnitude() > 0.0 {
-        camera.move_center(movement);
+        match bindings.scheme {
+            // Vuelo: traslada la cámara relativo a hacia donde mira
+            MovementScheme::Fly => camera.move_relative(movement * fly_speed),
+            // Original: gira el punto de mira alrededor del ojo
+            MovementScheme::Legacy => camera.move_center(movement * movement_speed),
+        }
     }
 
     // Controles de zoom de la cámara con teclado
-    if window.is_key_down(Key::Up) {
+    if window.is_key_down(bindings.zoom_in) {
         camera.zoom(zoom_speed);
     }
-    if window.is_key_down(Key::Down) {
+    if window.is_key_down(bindings.zoom_out) {
         camera.zoom(-zoom_speed);
     }
 
@@ -1393,7 +1556,7 @@ fn handle_input(
             mouse_state.last_mouse_pos_middle = mouse_pos;
 
             // Por ejemplo, mover el centro de la cámara para hacer panning
-            let pan_speed = 0.05;
+            let pan_speed = speeds.pan * speeds.factor;
             camera.move_center(Vec3::new(-delta_x * pan_speed, delta_y * pan_speed, 0.0));
         }
     } else {
@@ -1402,7 +1565,7 @@ fn handle_input(
     }
 
     // Alternar vista aérea con la tecla 'B'
-    if window.is_key_pressed(Key::B, minifb::KeyRepeat::No) {
+    if window.is_key_pressed(bindings.bird_eye, minifb::KeyRepeat::No) {
         if *bird_eye_active {
             // Resetear la cámara a la posición y orientación normal
             camera.eye = Vec3::new(0.0, 10.0, 100.0);
@@ -1424,50 +1587,256 @@ fn handle_input(
     }
 }
 
-fn render_trail(
+// Radio orbital durante la animación de explotar órbitas: interpola con suavizado
+// entre el radio configurado y uno equiespaciado según el orden del planeta
+fn exploded_orbit_radius(planet: &Planet, index: usize, progress: f32) -> f32 {
+    let exploded = EXPLODED_INNER_RADIUS + index as f32 * EXPLODED_ORBIT_SPACING;
+    let t = progress.clamp(0.0, 1.0);
+    let eased = t * t * (3.0 - 2.0 * t);
+    planet.orbit_radius + (exploded - planet.orbit_radius) * eased
+}
+
+// Línea divisoria y nombre de cada shader en la comparación en pantalla dividida
+fn render_shader_compare_overlay(
     framebuffer: &mut Framebuffer,
-    uniforms: &Uniforms,
-    trail: &PlanetTrail,
-    color_start: Color,
-    color_end: Color,
-    thickness: usize,
+    left_name: &str,
+    right_name: &str,
+    scale: usize,
 ) {
-    let num_positions = trail.positions.len();
-    if num_positions < 2 {
-        return; // No hay suficientes puntos para dibujar
+    let divider_x = framebuffer.width / 2;
+    for y in 0..framebuffer.height {
+        framebuffer.set_pixel(divider_x, y, 0xFFFFFF);
     }
 
-    // Proyectar las posiciones al espacio de pantalla
-    let mut screen_positions = Vec::with_capacity(num_positions);
-    for position in &trail.positions {
-        let model_matrix = create_model_matrix(*position, 1.0, Vec3::zeros());
-        let mvp_matrix = uniforms.projection_matrix * uniforms.view_matrix * model_matrix;
-        let clip_space_pos = mvp_matrix * Vec4::new(0.0, 0.0, 0.0, 1.0);
-        let ndc_space_pos = clip_space_pos / clip_space_pos.w;
+    let margin = 5 * scale;
+    let label_y = framebuffer
+        .height
+        .saturating_sub(hud::line_height(scale) + margin);
+    hud::draw_text(framebuffer, margin, label_y, left_name, 0xFFFFFF, scale);
+    let right_x = framebuffer
+        .width
+        .saturating_sub(hud::text_width(right_name, scale) + margin);
+    hud::draw_text(framebuffer, right_x, label_y, right_name, 0xFFFFFF, scale);
+}
 
-        let viewport_pos = uniforms.viewport_matrix * ndc_space_pos;
-        screen_positions.push(Vec2::new(viewport_pos.x, viewport_pos.y));
+// Panel lateral con los datos configurados del planeta enfocado
+fn render_planet_info(framebuffer: &mut Framebuffer, planet: &Planet, distance: f32, scale: usize) {
+    let mut lines = vec![
+        planet.name.clone(),
+        format!("Escala: {:.2}", planet.scale),
+        format!("Radio orbital: {:.1}", planet.orbit_radius),
+        format!("Vel. orbital: {:.4}", planet.signed_orbit_speed()),
+        format!("Distancia: {:.1}", distance),
+    ];
+    match (planet.retrograde_orbit, planet.retrograde_spin) {
+        (true, true) => lines.push("Órbita y giro retrógrados".to_string()),
+        (true, false) => lines.push("Órbita retrógrada".to_string()),
+        (false, true) => lines.push("Giro retrógrado".to_string()),
+        (false, false) => {}
     }
+    hud::draw_text_panel_right(framebuffer, 10 * scale / 2, &lines, 0xFFFFFF, scale);
+}
 
-    // Dibujar líneas entre las posiciones con efecto de desvanecimiento
-    for i in 0..(screen_positions.len() - 1) {
-        let start_pos = screen_positions[i];
-        let end_pos = screen_positions[i + 1];
+// Largo de cada estela: el ajustado a mano por planeta o, con `--trail-budget`,
+// el presupuesto repartido entre los cuerpos activos según su período orbital.
+// `scale` es el factor de '[' y ']', que escala también el presupuesto.
+fn apply_trail_lengths(
+    planets: &[Planet],
+    trails: &mut [PlanetTrail],
+    active_bodies: usize,
+    budget: Option<usize>,
+    scale: f32,
+) {
+    match budget {
+        Some(budget) => {
+            let periods: Vec<f32> = planets
+                .iter()
+                .take(active_bodies)
+                .map(Planet::orbital_period)
+                .collect();
+            let budget = (budget as f32 * scale).round() as usize;
+            let lengths = distribute_trail_budget(&periods, budget);
+            log_debug!("trail budget {} split as {:?}", budget, lengths);
+            for (trail, length) in trails.iter_mut().zip(lengths) {
+                trail.set_max_length(length);
+            }
+        }
+        None => {
+            for (planet, trail) in planets.iter().zip(trails.iter_mut()) {
+                let length = (planet.max_trail_length as f32 * scale).round();
+                trail.set_max_length((length as usize).max(2));
+            }
+        }
+    }
+}
 
-        // Interpolar el color para el efecto de desvanecimiento
-        let t = i as f32 / (screen_positions.len() - 1) as f32;
-        let color = color_start.lerp(&color_end, t);
+// Configuración del framebuffer de dibujo, que se pierde cuando se crea otro
+// (cambio de calidad o captura en alta resolución)
+fn configure_framebuffer(
+    framebuffer: &mut Framebuffer,
+    rasterizer: Rasterizer,
+    background: u32,
+    gbuffer_enabled: bool,
+) {
+    framebuffer.set_rasterizer(rasterizer);
+    framebuffer.set_background_color(background);
+    framebuffer.set_gbuffer_enabled(gbuffer_enabled);
+}
 
-        framebuffer.set_current_color(color.to_hex());
+// Un halo por cuerpo, que se enciende a medida que el cuerpo se vuelve más
+// pequeño que unos píxeles; de cerca no cambia nada
+fn render_glows(
+    framebuffer: &mut Framebuffer,
+    uniforms: &Uniforms,
+    planets: &[Planet],
+    positions: &[Vec3],
+    camera_position: Vec3,
+    glow_radius: f32,
+) {
+    for (planet, &position) in planets.iter().zip(positions) {
+        let Some(radius) = projected_radius(position, planet.scale, uniforms) else {
+            continue;
+        };
+        let fade = ((GLOW_FADE_START - radius) / (GLOW_FADE_START - GLOW_FADE_END)).clamp(0.0, 1.0);
+        if fade <= 0.0 {
+            continue;
+        }
+        // Se compara con la cara frontal del cuerpo, para que él mismo no tape su halo
+        let front = position + (camera_position - position).normalize() * planet.scale;
+        let (Some((center, _)), Some((front, _))) = (
+            project_to_screen(position, uniforms),
+            project_to_screen(front, uniforms),
+        ) else {
+            continue;
+        };
+        framebuffer.add_glow(
+            center.x,
+            center.y,
+            front.z - 1e-6,
+            planet.glow(),
+            glow_radius,
+            GLOW_INTENSITY * planet.glow_intensity * fade,
+        );
+    }
+}
 
-        let x0 = start_pos.x.round() as usize;
-        let y0 = start_pos.y.round() as usize;
-        let x1 = end_pos.x.round() as usize;
-        let y1 = end_pos.y.round() as usize;
+// Acerca la cámara al planeta `index` (ya enfocado, así que el centro es el planeta)
+// por la misma dirección desde la que se miraba. Sin distancia de seguimiento fija
+// el zoom sigue funcionando dentro de la vista.
+// El planeta gira mientras dure la vista.
+fn enter_solo(
+    camera: &mut Camera,
+    turntable_enabled: &mut bool,
+    index: usize,
+    scale: f32,
+) -> SoloView {
+    let offset = camera.eye - camera.center;
+    let view = SoloView {
+        index,
+        offset,
+        up: camera.up,
+        track_distance: camera.track_distance.take(),
+        turntable: std::mem::replace(turntable_enabled, true),
+    };
+    camera.animate_to(
+        camera.center + offset.normalize() * scale * SOLO_DISTANCE,
+        camera.center,
+        camera.up,
+        VIEW_PRESET_TRANSITION,
+    );
+    view
+}
 
-        // Usa la profundidad promedio o la del punto inicial
-        let depth = 0.0; // O calcula la profundidad si es necesario
+fn leave_solo(camera: &mut Camera, turntable_enabled: &mut bool, view: SoloView) {
+    camera.track_distance = view.track_distance;
+    *turntable_enabled = view.turntable;
+    camera.animate_to(
+        camera.center + view.offset,
+        camera.center,
+        view.up,
+        VIEW_PRESET_TRANSITION,
+    );
+}
+
+// Circunferencia de trazos alrededor del contorno en pantalla de un cuerpo; los
+// trazos giran con `time` (segundos). `pixel_scale` pasa de píxeles de la
+// ventana a píxeles del framebuffer.
+fn render_focus_ring(
+    framebuffer: &mut Framebuffer,
+    uniforms: &Uniforms,
+    position: Vec3,
+    world_radius: f32,
+    time: f32,
+    pixel_scale: f32,
+) {
+    let (Some((center, _)), Some(body_radius)) = (
+        project_to_screen(position, uniforms),
+        projected_radius(position, world_radius, uniforms),
+    ) else {
+        return;
+    };
+    let radius =
+        (body_radius + FOCUS_RING_MARGIN * pixel_scale).max(FOCUS_RING_MIN_RADIUS * pixel_scale);
+    framebuffer.set_current_color(Color::new(255, 210, 90).to_hex());
+
+    // Cada trazo ocupa la mitad de su tramo y se dibuja como una poligonal
+    let dash = TAU / FOCUS_RING_DASHES as f32;
+    let steps = 6;
+    let offset = time * FOCUS_RING_SPEED;
+    for thickness in 0..pixel_scale as usize {
+        let radius = radius + thickness as f32;
+        let point = |angle: f32| center.xy() + Vec2::new(angle.cos(), angle.sin()) * radius;
+        for index in 0..FOCUS_RING_DASHES {
+            let start = offset + index as f32 * dash;
+            for step in 0..steps {
+                let a = point(start + dash * 0.5 * step as f32 / steps as f32);
+                let b = point(start + dash * 0.5 * (step + 1) as f32 / steps as f32);
+                framebuffer.draw_line_antialiased(a.x, a.y, b.x, b.y, f32::NEG_INFINITY);
+            }
+        }
+    }
+}
 
-        framebuffer.draw_line(x0, y0, x1, y1, depth, thickness);
+fn render_proximity_highlights(
+    framebuffer: &mut Framebuffer,
+    uniforms: &Uniforms,
+    positions: &[Vec3],
+    threshold: f32,
+    color: Color,
+) {
+    let screen_positions: Vec<Option<Vec2>> = positions
+        .iter()
+        .map(|position| project_to_screen(*position, uniforms).map(|(screen, _)| screen.xy()))
+        .collect();
+
+    // Se dibuja encima de todo, como una capa de overlay
+    let depth = f32::NEG_INFINITY;
+    let ring_radius = (threshold * 0.5) as isize;
+    framebuffer.set_current_color(color.to_hex());
+
+    for i in 0..screen_positions.len() {
+        for j in (i + 1)..screen_positions.len() {
+            let (a, b) = match (screen_positions[i], screen_positions[j]) {
+                (Some(a), Some(b)) => (a, b),
+                _ => continue,
+            };
+            if (a - b).magnitude() >= threshold {
+                continue;
+            }
+
+            framebuffer.draw_circle(
+                a.x.round() as isize,
+                a.y.round() as isize,
+                ring_radius,
+                depth,
+            );
+            framebuffer.draw_circle(
+                b.x.round() as isize,
+                b.y.round() as isize,
+                ring_radius,
+                depth,
+            );
+            framebuffer.draw_line_antialiased(a.x, a.y, b.x, b.y, depth);
+        }
     }
 }
@@ -0,0 +1,80 @@
+// mesh.rs
+// Geometría generada por código, sin depender de archivos OBJ
+
+use crate::obj::IndexedMesh;
+use crate::vertex::Vertex;
+use nalgebra_glm::{Vec2, Vec3};
+use std::f32::consts::PI;
+
+// Esfera UV de radio 1 centrada en el origen. `stacks` divide de polo a polo y
+// `slices` alrededor del eje Y. La costura en u = 1 repite vértices para que las
+// coordenadas de textura no salten; en los polos solo hay un triángulo por sector.
+// Con 16x32 tiene los mismos 960 triángulos que assets/models/sphere.obj.
+pub fn uv_sphere(stacks: u32, slices: u32) -> IndexedMesh {
+    let stacks = stacks.max(2);
+    let slices = slices.max(3);
+
+    let mut vertices = Vec::with_capacity(((stacks + 1) * (slices + 1)) as usize);
+    for stack in 0..=stacks {
+        let v = stack as f32 / stacks as f32;
+        let polar = v * PI;
+        for slice in 0..=slices {
+            let u = slice as f32 / slices as f32;
+            let azimuth = u * 2.0 * PI;
+            let normal = Vec3::new(
+                polar.sin() * azimuth.cos(),
+                polar.cos(),
+                polar.sin() * azimuth.sin(),
+            );
+            let mut vertex = Vertex::new(normal, normal, Vec2::new(u, v));
+            // Derivada de la posición respecto de u: tangente al paralelo, definida
+            // incluso en los polos
+            vertex.tangent = Vec3::new(-azimuth.sin(), 0.0, azimuth.cos());
+            vertices.push(vertex);
+        }
+    }
+
+    let row = slices + 1;
+    let mut indices = Vec::with_capacity((6 * slices * (stacks - 1)) as usize);
+    for stack in 0..stacks {
+        for slice in 0..slices {
+            let top_left = stack * row + slice;
+            let bottom_left = top_left + row;
+            if stack != 0 {
+                indices.extend([top_left, bottom_left, top_left + 1]);
+            }
+            if stack != stacks - 1 {
+                indices.extend([top_left + 1, bottom_left, bottom_left + 1]);
+            }
+        }
+    }
+
+    IndexedMesh { vertices, indices }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uv_sphere_counts_and_unit_normals() {
+        let (stacks, slices) = (16, 32);
+        let mesh = uv_sphere(stacks, slices);
+
+        assert_eq!(mesh.vertices.len(), ((stacks + 1) * (slices + 1)) as usize);
+        assert_eq!(mesh.indices.len(), (6 * slices * (stacks - 1)) as usize);
+        assert!(mesh
+            .indices
+            .iter()
+            .all(|&index| (index as usize) < mesh.vertices.len()));
+
+        for vertex in &mesh.vertices {
+            assert!((vertex.normal.magnitude() - 1.0).abs() < 1e-5);
+            assert!((vertex.position.magnitude() - 1.0).abs() < 1e-5);
+            assert!((vertex.tangent.magnitude() - 1.0).abs() < 1e-5);
+            assert!(vertex.tangent.dot(&vertex.normal).abs() < 1e-5);
+            assert!((0.0..=1.0).contains(&vertex.tex_coords.x));
+            assert!((0.0..=1.0).contains(&vertex.tex_coords.y));
+        }
+    }
+}
@@ -1,43 +1,115 @@
 use tobj;
 use nalgebra_glm::{Vec2, Vec3};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, ErrorKind};
+use std::sync::Arc;
 use crate::vertex::Vertex;
+use crate::{log_info, log_warn};
+
+// Ring model baked into the binary, used when the asset file is missing
+pub const EMBEDDED_RING_OBJ: &str = include_str!("../assets/models/ring.obj");
+
+// Shared vertices plus the triangle list that references them
+pub struct IndexedMesh {
+    pub vertices: Vec<Vertex>,
+    pub indices: Vec<u32>,
+}
 
 pub struct Obj {
     meshes: Vec<Mesh>,
+    // Center and radius in model space, computed once after loading
+    bounding_sphere: (Vec3, f32),
 }
 
 struct Mesh {
     vertices: Vec<Vec3>,
     normals: Vec<Vec3>,
     texcoords: Vec<Vec2>,
+    tangents: Vec<Vec3>,
     indices: Vec<u32>,
 }
 
 impl Obj {
     pub fn load(filename: &str) -> Result<Self, tobj::LoadError> {
-        let (models, _) = tobj::load_obj(filename, &tobj::LoadOptions {
-            single_index: true,
-            triangulate: true,
-            ..Default::default()
-        })?;
+        let file = File::open(filename).map_err(|_| tobj::LoadError::OpenFileFailed)?;
+        let mut reader = BufReader::new(file);
+        Self::load_from_reader(&mut reader)
+    }
 
+    // Load from any buffered source (include_bytes!, network streams, etc.)
+    // Materials are ignored, the shaders provide all the color.
+    pub fn load_from_reader<R: BufRead>(reader: &mut R) -> Result<Self, tobj::LoadError> {
+        let (models, _) = tobj::load_obj_buf(
+            reader,
+            &tobj::LoadOptions {
+                single_index: true,
+                triangulate: true,
+                ..Default::default()
+            },
+            |_| Ok(Default::default()),
+        )?;
+
+        // tobj already resolves every face form (`v`, `v/vt`, `v//vn`, `v/vt/vn`)
+        // and negative indices, which count back from the last vertex read.
+        // Missing texture coordinates become (0, 0) in `get_vertex_array`.
         let meshes = models.into_iter().map(|model| {
             let mesh = model.mesh;
-            Mesh {
-                vertices: mesh.positions.chunks(3)
-                    .map(|v| Vec3::new(v[0], v[1], v[2]))
-                    .collect(),
-                normals: mesh.normals.chunks(3)
+            let vertices: Vec<Vec3> = mesh.positions.chunks(3)
+                .map(|v| Vec3::new(v[0], v[1], v[2]))
+                .collect();
+            // Without one normal per vertex, smooth normals are computed from the faces
+            let normals = if mesh.normals.len() == mesh.positions.len() {
+                mesh.normals.chunks(3)
                     .map(|n| Vec3::new(n[0], n[1], n[2]))
-                    .collect(),
-                texcoords: mesh.texcoords.chunks(2)
-                    .map(|t| Vec2::new(t[0], 1.0 - t[1]))
-                    .collect(),
+                    .collect()
+            } else {
+                compute_normals(&vertices, &mesh.indices)
+            };
+            let texcoords: Vec<Vec2> = mesh.texcoords.chunks(2)
+                .map(|t| Vec2::new(t[0], 1.0 - t[1]))
+                .collect();
+            // Tangents need texture coordinates; without them normal maps don't apply
+            let tangents = if texcoords.len() == vertices.len() {
+                compute_tangents(&vertices, &normals, &texcoords, &mesh.indices)
+            } else {
+                Vec::new()
+            };
+            Mesh {
+                vertices,
+                normals,
+                texcoords,
+                tangents,
                 indices: mesh.indices,
             }
-        }).collect();
+        }).collect::<Vec<Mesh>>();
 
-        Ok(Obj { meshes })
+        let bounding_sphere =
+            bounding_sphere(meshes.iter().flat_map(|mesh| mesh.vertices.iter().copied()));
+        Ok(Obj { meshes, bounding_sphere })
+    }
+
+    // Sphere enclosing every vertex, for culling, LOD and picking.
+    // Multiply the radius by the body's scale to get it in world space.
+    pub fn bounding_sphere(&self) -> (Vec3, f32) {
+        self.bounding_sphere
+    }
+
+    // Try the file on disk first and fall back to the embedded copy only when the
+    // file doesn't exist; a file that is there but broken is reported as an error
+    pub fn load_or_embedded(filename: &str, embedded: &str) -> Result<Self, tobj::LoadError> {
+        match File::open(filename) {
+            Ok(file) => {
+                let obj = Self::load_from_reader(&mut BufReader::new(file))?;
+                log_info!("loaded model {}", filename);
+                Ok(obj)
+            }
+            Err(error) if error.kind() == ErrorKind::NotFound => {
+                log_warn!("{} not found, using the embedded copy", filename);
+                Self::load_from_reader(&mut embedded.as_bytes())
+            }
+            Err(_) => Err(tobj::LoadError::OpenFileFailed),
+        }
     }
 
     pub fn get_vertex_array(&self) -> Vec<Vertex> {
@@ -53,10 +125,341 @@ impl Obj {
                     .cloned()
                     .unwrap_or(Vec2::new(0.0, 0.0));
 
-                vertices.push(Vertex::new(position, normal, tex_coords));
+                let mut vertex = Vertex::new(position, normal, tex_coords);
+                if let Some(&tangent) = mesh.tangents.get(index as usize) {
+                    vertex.tangent = tangent;
+                }
+                vertices.push(vertex);
             }
         }
 
         vertices
     }
+
+    // Shared-vertex version for `render_indexed`; every part of the model ends up
+    // in a single mesh
+    pub fn to_indexed_mesh(&self) -> IndexedMesh {
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+
+        for mesh in &self.meshes {
+            let offset = vertices.len() as u32;
+            for (index, &position) in mesh.vertices.iter().enumerate() {
+                let normal = mesh.normals.get(index)
+                    .cloned()
+                    .unwrap_or(Vec3::new(0.0, 1.0, 0.0));
+                let tex_coords = mesh.texcoords.get(index)
+                    .cloned()
+                    .unwrap_or(Vec2::new(0.0, 0.0));
+
+                let mut vertex = Vertex::new(position, normal, tex_coords);
+                if let Some(&tangent) = mesh.tangents.get(index) {
+                    vertex.tangent = tangent;
+                }
+                vertices.push(vertex);
+            }
+            indices.extend(mesh.indices.iter().map(|&index| index + offset));
+        }
+
+        IndexedMesh { vertices, indices }
+    }
+
+    // Former name of `to_indexed_mesh`, kept so existing callers still build
+    #[deprecated(note = "use `to_indexed_mesh`, which also fills in tangents")]
+    pub fn get_indexed_mesh(&self) -> IndexedMesh {
+        self.to_indexed_mesh()
+    }
+}
+
+// Models loaded from disk, one per path. Bodies that share a path share the same
+// `Arc`, so the file is parsed and its normals, tangents and bounding sphere are
+// computed only once.
+#[derive(Default)]
+pub struct ObjCache {
+    models: HashMap<String, Arc<Obj>>,
+}
+
+impl ObjCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Failed loads are not cached, so a later call tries the file again
+    pub fn load(&mut self, filename: &str) -> Result<Arc<Obj>, tobj::LoadError> {
+        if let Some(obj) = self.models.get(filename) {
+            return Ok(Arc::clone(obj));
+        }
+        let obj = Arc::new(Obj::load(filename)?);
+        self.models.insert(filename.to_string(), Arc::clone(&obj));
+        Ok(obj)
+    }
+
+    // Number of distinct models loaded
+    pub fn len(&self) -> usize {
+        self.models.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.models.is_empty()
+    }
+}
+
+// Centered on the bounding box, with the distance to the farthest point as radius.
+// Not the tightest possible sphere, but exact for shapes symmetric about their center.
+pub fn bounding_sphere<I: Iterator<Item = Vec3> + Clone>(points: I) -> (Vec3, f32) {
+    let mut min = Vec3::repeat(f32::INFINITY);
+    let mut max = Vec3::repeat(f32::NEG_INFINITY);
+    for point in points.clone() {
+        min = min.inf(&point);
+        max = max.sup(&point);
+    }
+    if min.x > max.x {
+        return (Vec3::zeros(), 0.0);
+    }
+
+    let center = (min + max) * 0.5;
+    let radius = points
+        .map(|point| (point - center).magnitude())
+        .fold(0.0, f32::max);
+    (center, radius)
+}
+
+impl IndexedMesh {
+    pub fn bounding_sphere(&self) -> (Vec3, f32) {
+        bounding_sphere(self.vertices.iter().map(|vertex| vertex.position))
+    }
+
+    // Moves the bounding sphere to the origin with radius 1, like `uv_sphere`, so a
+    // body's scale means the same whatever model it uses
+    pub fn normalized(mut self) -> Self {
+        let (center, radius) = self.bounding_sphere();
+        if radius > 0.0 {
+            for vertex in &mut self.vertices {
+                vertex.position = (vertex.position - center) / radius;
+                vertex.transformed_position = vertex.position;
+            }
+        }
+        self
+    }
+}
+
+// Area-weighted average of the normals of the faces that share each vertex
+fn compute_normals(vertices: &[Vec3], indices: &[u32]) -> Vec<Vec3> {
+    let mut normals = vec![Vec3::zeros(); vertices.len()];
+
+    for face in indices.chunks_exact(3) {
+        let [a, b, c] = [face[0] as usize, face[1] as usize, face[2] as usize];
+        // The cross product's length is twice the area, so bigger faces weigh more
+        let face_normal = (vertices[b] - vertices[a]).cross(&(vertices[c] - vertices[a]));
+        for index in [a, b, c] {
+            normals[index] += face_normal;
+        }
+    }
+
+    normals.into_iter()
+        .map(|normal| {
+            if normal.magnitude() > 0.0 {
+                normal.normalize()
+            } else {
+                Vec3::new(0.0, 1.0, 0.0)
+            }
+        })
+        .collect()
+}
+
+// Per-vertex direction in which u grows, from each face's UV gradient. The sum
+// over the shared faces is made perpendicular to the normal (Gram-Schmidt).
+// Mirrored UVs are not handled: the bitangent is always normal x tangent.
+fn compute_tangents(vertices: &[Vec3], normals: &[Vec3], texcoords: &[Vec2], indices: &[u32]) -> Vec<Vec3> {
+    let mut tangents = vec![Vec3::zeros(); vertices.len()];
+
+    for face in indices.chunks_exact(3) {
+        let [a, b, c] = [face[0] as usize, face[1] as usize, face[2] as usize];
+        let (edge1, edge2) = (vertices[b] - vertices[a], vertices[c] - vertices[a]);
+        let (duv1, duv2) = (texcoords[b] - texcoords[a], texcoords[c] - texcoords[a]);
+        let determinant = duv1.x * duv2.y - duv2.x * duv1.y;
+        if determinant.abs() < 1e-12 {
+            continue; // Degenerate UVs give no direction
+        }
+        let face_tangent = (edge1 * duv2.y - edge2 * duv1.y) / determinant;
+        for index in [a, b, c] {
+            tangents[index] += face_tangent;
+        }
+    }
+
+    tangents.into_iter()
+        .zip(normals)
+        .map(|(tangent, normal)| {
+            let tangent = tangent - normal * normal.dot(&tangent);
+            if tangent.magnitude() > 1e-6 {
+                tangent.normalize()
+            } else {
+                Vec3::zeros()
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source: &str) -> Vec<Vertex> {
+        Obj::load_from_reader(&mut source.as_bytes())
+            .expect("valid OBJ")
+            .get_vertex_array()
+    }
+
+    const POSITIONS: &str = "v 0 0 0\nv 1 0 0\nv 0 1 0\n";
+
+    #[test]
+    fn positions_only_faces_get_computed_normals_and_zero_uvs() {
+        let vertices = parse(&format!("{}f 1 2 3\n", POSITIONS));
+
+        assert_eq!(vertices.len(), 3);
+        assert_eq!(vertices[1].position, Vec3::new(1.0, 0.0, 0.0));
+        for vertex in &vertices {
+            assert!((vertex.normal - Vec3::new(0.0, 0.0, 1.0)).magnitude() < 1e-6);
+            assert_eq!(vertex.tex_coords, Vec2::new(0.0, 0.0));
+        }
+    }
+
+    #[test]
+    fn faces_with_texture_coordinates_only() {
+        let vertices = parse(&format!(
+            "{}vt 0 0\nvt 1 0\nvt 0 1\nf 1/1 2/2 3/3\n",
+            POSITIONS
+        ));
+
+        assert_eq!(vertices.len(), 3);
+        // V is flipped so (0, 1) in the file is the top row of the texture
+        assert_eq!(vertices[2].tex_coords, Vec2::new(0.0, 0.0));
+        assert_eq!(vertices[0].tex_coords, Vec2::new(0.0, 1.0));
+        assert!((vertices[0].normal - Vec3::new(0.0, 0.0, 1.0)).magnitude() < 1e-6);
+        // U grows along +x, so that's the tangent at every corner
+        for vertex in &vertices {
+            assert!((vertex.tangent - Vec3::new(1.0, 0.0, 0.0)).magnitude() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn faces_with_normals_only() {
+        let vertices = parse(&format!("{}vn 0 0 -1\nf 1//1 2//1 3//1\n", POSITIONS));
+
+        assert_eq!(vertices.len(), 3);
+        for vertex in &vertices {
+            assert_eq!(vertex.normal, Vec3::new(0.0, 0.0, -1.0));
+            assert_eq!(vertex.tex_coords, Vec2::new(0.0, 0.0));
+        }
+    }
+
+    #[test]
+    fn faces_with_full_triples() {
+        let vertices = parse(&format!(
+            "{}vt 0.5 0.5\nvn 0 0 1\nf 1/1/1 2/1/1 3/1/1\n",
+            POSITIONS
+        ));
+
+        assert_eq!(vertices.len(), 3);
+        for vertex in &vertices {
+            assert_eq!(vertex.normal, Vec3::new(0.0, 0.0, 1.0));
+            assert_eq!(vertex.tex_coords, Vec2::new(0.5, 0.5));
+        }
+    }
+
+    #[test]
+    fn negative_indices_count_from_the_end() {
+        let relative = parse(&format!(
+            "v 9 9 9\n{}vt 0 0\nvt 1 0\nvt 0 1\nvn 0 0 1\nf -3/-3/-1 -2/-2/-1 -1/-1/-1\n",
+            POSITIONS
+        ));
+        let absolute = parse(&format!(
+            "v 9 9 9\n{}vt 0 0\nvt 1 0\nvt 0 1\nvn 0 0 1\nf 2/1/1 3/2/1 4/3/1\n",
+            POSITIONS
+        ));
+
+        let positions = |vertices: &[Vertex]| -> Vec<Vec3> {
+            vertices.iter().map(|vertex| vertex.position).collect()
+        };
+        assert_eq!(positions(&relative), positions(&absolute));
+        assert_eq!(relative[1].position, Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(relative[1].tex_coords, absolute[1].tex_coords);
+    }
+
+    #[test]
+    fn unit_sphere_bounding_sphere() {
+        let (center, radius) = crate::mesh::uv_sphere(16, 32).bounding_sphere();
+        assert!(center.magnitude() < 1e-5);
+        assert!((radius - 1.0).abs() < 1e-5);
+
+        // Unit octahedron shifted along X: the sphere follows the model's center
+        let obj = Obj::load_from_reader(
+            &mut "v 3 0 0\nv 1 0 0\nv 2 1 0\nv 2 -1 0\nv 2 0 1\nv 2 0 -1\n\
+                  f 1 3 5\nf 3 2 5\nf 2 4 5\nf 4 1 5\n\
+                  f 3 1 6\nf 2 3 6\nf 4 2 6\nf 1 4 6\n"
+                .as_bytes(),
+        )
+        .expect("valid OBJ");
+        let (center, radius) = obj.bounding_sphere();
+        assert!((center - Vec3::new(2.0, 0.0, 0.0)).magnitude() < 1e-6);
+        assert!((radius - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn empty_model_has_empty_bounding_sphere() {
+        let obj = Obj::load_from_reader(&mut "# nothing here\n".as_bytes()).expect("valid OBJ");
+        assert_eq!(obj.bounding_sphere(), (Vec3::zeros(), 0.0));
+    }
+
+    #[test]
+    fn indexed_mesh_is_normalized_to_the_unit_sphere() {
+        let obj = Obj::load_from_reader(
+            &mut "v 4 0 0\nv 2 0 0\nv 3 2 0\nv 3 -2 0\nf 1 3 2\nf 1 2 4\n".as_bytes(),
+        )
+        .expect("valid OBJ");
+        let mesh = obj.to_indexed_mesh();
+        assert_eq!(mesh.vertices.len(), 4);
+        assert_eq!(mesh.indices.len(), 6);
+
+        let (center, radius) = mesh.normalized().bounding_sphere();
+        assert!(center.magnitude() < 1e-6);
+        assert!((radius - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn cache_shares_models_loaded_from_the_same_path() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/models/phobos.obj");
+        let mut cache = ObjCache::new();
+        let first = cache.load(path).expect("Phobos model");
+        let second = cache.load(path).expect("Phobos model");
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(cache.len(), 1);
+        assert!(first.bounding_sphere().1 > 0.0);
+
+        assert!(cache.load("assets/models/missing.obj").is_err());
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn embedded_copy_replaces_only_missing_files() {
+        let obj = Obj::load_or_embedded("assets/models/missing.obj", EMBEDDED_RING_OBJ)
+            .expect("embedded ring");
+        assert!(!obj.get_vertex_array().is_empty());
+
+        let path = std::env::temp_dir().join(format!("broken_{}.obj", std::process::id()));
+        std::fs::write(&path, "v 0 0 zero\nf 1 1 1\n").unwrap();
+        let broken = Obj::load_or_embedded(path.to_str().unwrap(), EMBEDDED_RING_OBJ);
+        std::fs::remove_file(&path).unwrap();
+        assert!(broken.is_err());
+    }
+
+    #[test]
+    fn quads_are_triangulated() {
+        let vertices = parse("v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nf 1 2 3 4\n");
+
+        assert_eq!(vertices.len(), 6);
+        for vertex in &vertices {
+            assert!((vertex.normal - Vec3::new(0.0, 0.0, 1.0)).magnitude() < 1e-6);
+        }
+    }
 }
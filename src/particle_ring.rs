@@ -0,0 +1,161 @@
+// particle_ring.rs
+// Anillo de partículas: miles de puntos en una corona circular que orbitan con
+// velocidad kepleriana (las interiores más rápido que las exteriores)
+
+use crate::color::Color;
+use crate::render::view_distance;
+use crate::{Framebuffer, Uniforms};
+use nalgebra_glm::Vec4;
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use std::f32::consts::PI;
+
+// Velocidad angular (rad/s) en el borde interior; hacia afuera decae como r^(-3/2)
+const INNER_ANGULAR_SPEED: f32 = 0.4;
+// Grosor vertical máximo del anillo, como fracción del radio interior
+const THICKNESS: f32 = 0.01;
+// Radio en píxeles de cada partícula, dibujada como disco suave
+const PARTICLE_RADIUS: f32 = 0.7;
+// Distancia (en unidades del mundo) delante de la geometría en la que una
+// partícula se desvanece, para que el anillo no se corte en seco contra Saturno
+pub const DEFAULT_SOFT_DISTANCE: f32 = 0.4;
+
+struct RingParticle {
+    radius: f32,
+    phase: f32,
+    height: f32,
+    brightness: f32,
+}
+
+pub struct ParticleRing {
+    particles: Vec<RingParticle>,
+    inner_radius: f32,
+    color: Color,
+    // Partículas suaves: 0 las dibuja con la prueba de profundidad normal
+    pub soft_distance: f32,
+}
+
+impl ParticleRing {
+    pub fn new(count: usize, inner_radius: f32, outer_radius: f32, color: Color) -> Self {
+        Self::generate(
+            count,
+            inner_radius,
+            outer_radius,
+            color,
+            &mut rand::thread_rng(),
+        )
+    }
+
+    // Misma distribución de partículas en cada ejecución para una semilla dada
+    pub fn with_seed(
+        count: usize,
+        inner_radius: f32,
+        outer_radius: f32,
+        color: Color,
+        seed: u64,
+    ) -> Self {
+        Self::generate(
+            count,
+            inner_radius,
+            outer_radius,
+            color,
+            &mut StdRng::seed_from_u64(seed),
+        )
+    }
+
+    fn generate<R: Rng>(
+        count: usize,
+        inner_radius: f32,
+        outer_radius: f32,
+        color: Color,
+        rng: &mut R,
+    ) -> Self {
+        let outer_radius = outer_radius.max(inner_radius);
+        let mut particles = Vec::with_capacity(count);
+
+        for _ in 0..count {
+            // Uniforme en área: r^2 uniforme entre los dos bordes
+            let t = rng.gen::<f32>();
+            let radius = (inner_radius * inner_radius
+                + t * (outer_radius.powi(2) - inner_radius.powi(2)))
+            .sqrt();
+            particles.push(RingParticle {
+                radius,
+                phase: rng.gen::<f32>() * 2.0 * PI,
+                height: (rng.gen::<f32>() - 0.5) * THICKNESS * inner_radius,
+                brightness: rng.gen_range(0.5..=1.0),
+            });
+        }
+
+        ParticleRing {
+            particles,
+            inner_radius,
+            color,
+            soft_distance: DEFAULT_SOFT_DISTANCE,
+        }
+    }
+
+    // Dibuja las partículas como puntos con prueba de profundidad. El anillo vive
+    // en el plano XZ del espacio modelo (`uniforms.model_matrix` lo coloca e inclina)
+    // y `seconds` es el tiempo de simulación que fija el ángulo de cada partícula.
+    // Con `soft_distance` cada partícula se compara contra la profundidad de la
+    // escena de antes del anillo (no contra las demás partículas) y se desvanece
+    // al acercarse a ella.
+    pub fn render(&self, framebuffer: &mut Framebuffer, uniforms: &Uniforms, seconds: f32) {
+        let transform = uniforms.projection_matrix * uniforms.view_matrix * uniforms.model_matrix;
+        let scene_depth = (self.soft_distance > 0.0).then(|| framebuffer.zbuffer.clone());
+        let width = framebuffer.width;
+
+        for particle in &self.particles {
+            // Tercera ley de Kepler: omega ∝ r^(-3/2)
+            let angular_speed =
+                INNER_ANGULAR_SPEED * (self.inner_radius / particle.radius).powf(1.5);
+            let angle = particle.phase + angular_speed * seconds;
+
+            let local = Vec4::new(
+                particle.radius * angle.cos(),
+                particle.height,
+                particle.radius * angle.sin(),
+                1.0,
+            );
+            let clip = transform * local;
+            if clip.w <= 0.0 {
+                continue; // Detrás de la cámara
+            }
+            let ndc = clip / clip.w;
+            if !(-1.0..=1.0).contains(&ndc.z) {
+                continue;
+            }
+            let screen = uniforms.viewport_matrix * ndc;
+            if screen.x < 0.0 || screen.y < 0.0 {
+                continue;
+            }
+
+            let color = (self.color * particle.brightness).to_hex();
+            match &scene_depth {
+                Some(scene_depth) => {
+                    let distance = view_distance(&uniforms.projection_matrix, ndc.z);
+                    framebuffer.draw_soft_point(
+                        screen.x,
+                        screen.y,
+                        ndc.z,
+                        color,
+                        PARTICLE_RADIUS,
+                        1.0,
+                        |x, y| {
+                            let stored = scene_depth[y * width + x];
+                            if !stored.is_finite() {
+                                return 1.0; // Nada dibujado detrás
+                            }
+                            let gap = view_distance(&uniforms.projection_matrix, stored) - distance;
+                            (gap / self.soft_distance).clamp(0.0, 1.0)
+                        },
+                    );
+                }
+                None => {
+                    framebuffer.draw_point(screen.x, screen.y, ndc.z, color, PARTICLE_RADIUS, 1.0)
+                }
+            }
+        }
+    }
+}
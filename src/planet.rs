@@ -1,35 +1,216 @@
-use crate::{Color, Fragment, Uniforms};
-use fastnoise_lite::FastNoiseLite; // For FastNoiseLite type
-use nalgebra_glm::Vec3; // For Vec3 type // Replace `some_crate` with the actual crate name where Fragment, Uniforms, and Color are defined
-
-// Your existing code
-type PlanetShaderFn = fn(&Fragment, &Uniforms) -> Color;
+use crate::color::Color;
+use nalgebra_glm::Vec3;
 
+// Datos configurables de un cuerpo en órbita alrededor del Sol
 pub struct Planet {
-    pub translation: Vec3,
-    pub rotation: Vec3,
+    pub name: String,
     pub scale: f32,
-    pub obj_path: String,
-    pub shader: PlanetShaderFn,
-    pub noise: FastNoiseLite,
+    pub orbit_radius: f32,
+    pub orbit_speed: f32,
+    pub max_trail_length: usize,
+    // Luz propia, independiente de la dirección del Sol (cero en planetas normales)
+    pub emissive: Color,
+    pub emissive_strength: f32,
+    // Tinte de la luz ambiental global sobre este cuerpo (blanco = sin tinte)
+    pub ambient_tint: Color,
+    // Color plano del cuerpo en el modo sin iluminación (ver `shader_flat`)
+    pub albedo: Color,
+    // Giro sobre su eje en radianes por unidad de tiempo de simulación (0 = sin giro)
+    pub spin_speed: f32,
+    // Sentido contrario al de los demás cuerpos, para la órbita y para el giro
+    pub retrograde_orbit: bool,
+    pub retrograde_spin: bool,
+    // Halo que lo mantiene visible cuando se ve más pequeño que unos píxeles;
+    // sin color propio usa el albedo, e intensidad 0 lo desactiva
+    pub glow_color: Option<Color>,
+    pub glow_intensity: f32,
+    // Modelo OBJ propio; sin él se usa la esfera generada por código
+    pub model: Option<String>,
+    // Ángulo de la órbita (radianes) en el instante 0, para que los cuerpos no
+    // empiecen todos alineados sobre +X
+    pub phase_offset: f32,
 }
 
 impl Planet {
-    fn new(
-        translation: Vec3,
-        rotation: Vec3,
+    pub fn new(
+        name: &str,
         scale: f32,
-        obj_path: &str,
-        shader: PlanetShaderFn,
-        noise: FastNoiseLite,
+        orbit_radius: f32,
+        orbit_speed: f32,
+        max_trail_length: usize,
     ) -> Self {
         Planet {
-            translation,
-            rotation,
+            name: name.to_string(),
             scale,
-            obj_path: obj_path.to_string(),
-            shader,
-            noise,
+            orbit_radius,
+            orbit_speed,
+            max_trail_length,
+            emissive: Color::black(),
+            emissive_strength: 0.0,
+            ambient_tint: Color::white(),
+            albedo: Color::new(180, 180, 180),
+            spin_speed: 0.0,
+            retrograde_orbit: false,
+            retrograde_spin: false,
+            glow_color: None,
+            glow_intensity: 1.0,
+            model: None,
+            phase_offset: 0.0,
         }
     }
+
+    // Para cuerpos que brillan por sí mismos, p. ej. una luna incandescente
+    pub fn with_emissive(mut self, color: Color, strength: f32) -> Self {
+        self.emissive = color;
+        self.emissive_strength = strength;
+        self
+    }
+
+    // Radio en el mundo a partir del radio de la malla en espacio modelo
+    // (ver `Obj::bounding_sphere`)
+    pub fn world_radius(&self, model_radius: f32) -> f32 {
+        model_radius * self.scale
+    }
+
+    pub fn with_ambient_tint(mut self, tint: Color) -> Self {
+        self.ambient_tint = tint;
+        self
+    }
+
+    pub fn with_albedo(mut self, albedo: Color) -> Self {
+        self.albedo = albedo;
+        self
+    }
+
+    pub fn with_spin(mut self, spin_speed: f32) -> Self {
+        self.spin_speed = spin_speed;
+        self
+    }
+
+    pub fn with_glow(mut self, color: Color, intensity: f32) -> Self {
+        self.glow_color = Some(color);
+        self.glow_intensity = intensity;
+        self
+    }
+
+    pub fn glow(&self) -> Color {
+        self.glow_color.unwrap_or(self.albedo)
+    }
+
+    // Venus, por ejemplo, orbita como los demás pero gira al revés
+    // Ruta del OBJ del cuerpo, p. ej. para lunas irregulares
+    pub fn with_model(mut self, path: &str) -> Self {
+        self.model = Some(path.to_string());
+        self
+    }
+
+    pub fn with_phase(mut self, phase_offset: f32) -> Self {
+        self.phase_offset = phase_offset;
+        self
+    }
+
+    pub fn with_retrograde(mut self, orbit: bool, spin: bool) -> Self {
+        self.retrograde_orbit = orbit;
+        self.retrograde_spin = spin;
+        self
+    }
+
+    // Velocidad orbital con signo, para `orbit_translation` (y así para las estelas)
+    pub fn signed_orbit_speed(&self) -> f32 {
+        if self.retrograde_orbit {
+            -self.orbit_speed
+        } else {
+            self.orbit_speed
+        }
+    }
+
+    // Tiempo simulado de una vuelta completa (ver `orbit_translation`); infinito
+    // si no orbita
+    pub fn orbital_period(&self) -> f32 {
+        std::f32::consts::TAU / (self.orbit_speed * 0.01)
+    }
+
+    // Ángulo de giro sobre su eje en el instante `time`
+    pub fn spin_angle(&self, time: f32) -> f32 {
+        let direction = if self.retrograde_spin { -1.0 } else { 1.0 };
+        time * self.spin_speed * direction
+    }
+
+    // Color que se suma tras la iluminación
+    pub fn emission(&self) -> Color {
+        self.emissive * self.emissive_strength
+    }
+}
+
+// Posición sobre una órbita circular en el plano XZ, partiendo del ángulo `phase`;
+// función pura del tiempo
+pub fn orbit_translation(center: Vec3, radius: f32, speed: f32, phase: f32, time: f32) -> Vec3 {
+    let angle = phase + time * speed * 0.01;
+    Vec3::new(
+        center.x + radius * angle.cos(),
+        center.y,
+        center.z + radius * angle.sin(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::FRAC_PI_2;
+
+    #[test]
+    fn orbit_position_depends_only_on_the_time() {
+        let planet = Planet::new("Prueba", 1.0, 12.0, 0.01, 10).with_retrograde(true, false);
+        let center = Vec3::new(1.0, 0.0, -2.0);
+        let target = 90.0 / (1.0 / 60.0) * 100.0;
+        let direct = orbit_translation(
+            center,
+            planet.orbit_radius,
+            planet.signed_orbit_speed(),
+            planet.phase_offset,
+            target,
+        );
+
+        // Llegar al mismo instante con pasos de distinto tamaño (cuadros a
+        // distintos FPS) deja al cuerpo en el mismo lugar
+        for steps in [1, 7, 540, 5400] {
+            let step = target / steps as f32;
+            let mut time = 0.0f32;
+            let mut position = center;
+            for _ in 0..steps {
+                time += step;
+                position = orbit_translation(
+                    center,
+                    planet.orbit_radius,
+                    planet.signed_orbit_speed(),
+                    planet.phase_offset,
+                    time,
+                );
+            }
+            assert!((position - direct).magnitude() < 0.05);
+        }
+    }
+
+    #[test]
+    fn phase_offset_sets_the_starting_angle() {
+        let planet = Planet::new("Prueba", 1.0, 10.0, 0.01, 10).with_phase(FRAC_PI_2);
+        let start = orbit_translation(
+            Vec3::zeros(),
+            planet.orbit_radius,
+            planet.signed_orbit_speed(),
+            planet.phase_offset,
+            0.0,
+        );
+        assert!((start - Vec3::new(0.0, 0.0, 10.0)).magnitude() < 1e-4);
+
+        // Media vuelta después está en el lado opuesto
+        let half = orbit_translation(
+            Vec3::zeros(),
+            planet.orbit_radius,
+            planet.signed_orbit_speed(),
+            planet.phase_offset,
+            planet.orbital_period() * 0.5,
+        );
+        assert!((half + start).magnitude() < 1e-3);
+    }
 }
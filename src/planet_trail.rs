@@ -1,7 +1,58 @@
 use nalgebra_glm::Vec3;
+
+// Largo mínimo de una estela con presupuesto: con menos no hay segmento que dibujar
+pub const MIN_BUDGET_TRAIL_LENGTH: usize = 2;
+
+// Reparte `budget` puntos de estela entre cuerpos con los períodos orbitales
+// dados, en proporción al período: los lentos de afuera reciben más puntos y
+// así todos cubren la misma fracción de su órbita. La suma es exactamente el
+// presupuesto (salvo que no alcance para el mínimo de cada uno).
+pub fn distribute_trail_budget(periods: &[f32], budget: usize) -> Vec<usize> {
+    let minimum = MIN_BUDGET_TRAIL_LENGTH * periods.len();
+    if budget <= minimum {
+        return vec![MIN_BUDGET_TRAIL_LENGTH; periods.len()];
+    }
+    let weights: Vec<f32> = periods
+        .iter()
+        .map(|&period| {
+            if period.is_finite() {
+                period.max(0.0)
+            } else {
+                0.0
+            }
+        })
+        .collect();
+    let total: f32 = weights.iter().sum();
+    if total <= 0.0 {
+        return vec![budget / periods.len().max(1); periods.len()];
+    }
+
+    // Cuotas de lo que sobra tras el mínimo; los restos de redondear se dan a las
+    // de mayor parte fraccionaria (método del resto mayor)
+    let spare = (budget - minimum) as f32;
+    let shares: Vec<f32> = weights
+        .iter()
+        .map(|weight| spare * weight / total)
+        .collect();
+    let mut lengths: Vec<usize> = shares
+        .iter()
+        .map(|share| MIN_BUDGET_TRAIL_LENGTH + share.floor() as usize)
+        .collect();
+    let mut order: Vec<usize> = (0..shares.len()).collect();
+    order.sort_by(|&a, &b| {
+        (shares[b] - shares[b].floor()).total_cmp(&(shares[a] - shares[a].floor()))
+    });
+    let assigned: usize = lengths.iter().sum();
+    for &index in order.iter().take(budget.saturating_sub(assigned)) {
+        lengths[index] += 1;
+    }
+    lengths
+}
 pub struct PlanetTrail {
     pub positions: Vec<Vec3>,
     pub max_length: usize,
+    // Puntos que faltan por recoger tras invertir el tiempo
+    pending_retract: usize,
 }
 
 impl PlanetTrail {
@@ -9,9 +60,47 @@ impl PlanetTrail {
         PlanetTrail {
             positions: Vec::with_capacity(max_length),
             max_length,
+            pending_retract: 0,
+        }
+    }
+
+    // Al invertir el tiempo el planeta vuelve exactamente por los puntos ya
+    // dibujados, así que la estela se recoge desde el extremo más reciente y,
+    // al agotarse, vuelve a crecer detrás del planeta en el nuevo sentido.
+    pub fn reverse(&mut self) {
+        self.pending_retract = self.positions.len().saturating_sub(1);
+    }
+
+    // Avanza un paso de simulación: recoge un punto pendiente o agrega la posición
+    pub fn advance(&mut self, position: Vec3) {
+        if self.pending_retract > 0 {
+            self.positions.pop();
+            self.pending_retract -= 1;
+        } else {
+            self.add_position(position);
         }
     }
 
+    // Cambia la longitud máxima; al acortar se descartan las posiciones más antiguas
+    pub fn set_max_length(&mut self, max_length: usize) {
+        self.max_length = max_length;
+        if self.positions.len() > max_length {
+            let excess = self.positions.len() - max_length;
+            self.positions.drain(..excess);
+        }
+        // Libera lo reservado de más, para que el largo máximo acote la memoria
+        self.positions.shrink_to(max_length);
+        self.pending_retract = self
+            .pending_retract
+            .min(self.positions.len().saturating_sub(1));
+    }
+
+    // Vacía la estela y libera su memoria
+    pub fn clear(&mut self) {
+        self.positions = Vec::new();
+        self.pending_retract = 0;
+    }
+
     pub fn add_position(&mut self, position: Vec3) {
         if self.positions.len() >= self.max_length {
             self.positions.remove(0); // Elimina la posición más antigua
@@ -19,3 +108,30 @@ impl PlanetTrail {
         self.positions.push(position);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trail_budget_is_split_by_orbital_period() {
+        let periods = [50.0, 100.0, 400.0, 1000.0];
+        for budget in [20, 1001, 5000] {
+            let lengths = distribute_trail_budget(&periods, budget);
+            assert_eq!(lengths.iter().sum::<usize>(), budget);
+            assert!(lengths.windows(2).all(|pair| pair[0] <= pair[1]));
+            assert!(lengths
+                .iter()
+                .all(|&length| length >= MIN_BUDGET_TRAIL_LENGTH));
+        }
+        // El doble de período, (casi) el doble de puntos
+        let lengths = distribute_trail_budget(&periods, 5000);
+        assert!((lengths[1] as f32 / lengths[0] as f32 - 2.0).abs() < 0.05);
+
+        // Si no alcanza, cada estela se queda con el mínimo
+        assert_eq!(
+            distribute_trail_budget(&periods, 3),
+            vec![MIN_BUDGET_TRAIL_LENGTH; 4]
+        );
+    }
+}
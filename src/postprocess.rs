@@ -0,0 +1,725 @@
+use crate::color::{srgb_to_linear, Color};
+use crate::framebuffer::Framebuffer;
+use nalgebra_glm::{Mat3, Vec2, Vec3};
+
+// Contornos estilo cómic: marca los píxeles donde cambia la cobertura, la
+// profundidad o la orientación de la normal. Necesita el G-buffer.
+pub fn apply_outlines(
+    framebuffer: &mut Framebuffer,
+    color: u32,
+    depth_threshold: f32,
+    normal_threshold: f32,
+    camera_position: Vec3,
+) {
+    if !framebuffer.has_gbuffer() {
+        return;
+    }
+
+    let width = framebuffer.width;
+    let height = framebuffer.height;
+    let mut edges = vec![false; width * height];
+
+    for y in 0..height {
+        for x in 0..width {
+            let sample = framebuffer
+                .normal_at(x, y)
+                .zip(framebuffer.world_position_at(x, y));
+
+            for (nx, ny) in [(x + 1, y), (x, y + 1)] {
+                if nx >= width || ny >= height {
+                    continue;
+                }
+                let neighbor = framebuffer
+                    .normal_at(nx, ny)
+                    .zip(framebuffer.world_position_at(nx, ny));
+
+                let is_edge = match (sample, neighbor) {
+                    // Silueta contra el fondo
+                    (Some(_), None) | (None, Some(_)) => true,
+                    (Some((normal, position)), Some((neighbor_normal, neighbor_position))) => {
+                        // El salto tolerado crece con la distancia, como el tamaño del píxel
+                        let distance = (position - camera_position).magnitude();
+                        let depth_jump =
+                            (position - neighbor_position).magnitude() > depth_threshold * distance;
+                        let crease =
+                            normal.normalize().dot(&neighbor_normal.normalize()) < normal_threshold;
+                        depth_jump || crease
+                    }
+                    (None, None) => false,
+                };
+
+                if is_edge {
+                    // Se marca el lado más cercano para que el contorno quede sobre el planeta
+                    let index = y * width + x;
+                    let neighbor_index = ny * width + nx;
+                    let sample_is_nearer = match (sample, neighbor) {
+                        (Some((_, a)), Some((_, b))) => {
+                            (a - camera_position).magnitude() <= (b - camera_position).magnitude()
+                        }
+                        (Some(_), None) => true,
+                        _ => false,
+                    };
+                    if sample_is_nearer {
+                        edges[index] = true;
+                    } else {
+                        edges[neighbor_index] = true;
+                    }
+                }
+            }
+        }
+    }
+
+    for (pixel, is_edge) in framebuffer.buffer.iter_mut().zip(edges) {
+        if is_edge {
+            *pixel = color;
+        }
+    }
+}
+
+// Parámetros del desenfoque radial de los rayos de luz (estilo GPU Gems 3)
+pub struct GodRayParams {
+    // Fracción del trayecto píxel→fuente que recorren las muestras
+    pub density: f32,
+    // Atenuación multiplicativa por muestra, cuanto más lejos menos aporta
+    pub decay: f32,
+    // Peso de cada muestra acumulada
+    pub weight: f32,
+    // Intensidad final sumada sobre la imagen
+    pub exposure: f32,
+    pub samples: usize,
+    // Luminancia mínima (0..1) para que un píxel emita rayos
+    pub threshold: f32,
+}
+
+impl Default for GodRayParams {
+    fn default() -> Self {
+        GodRayParams {
+            density: 0.9,
+            decay: 0.95,
+            weight: 0.08,
+            exposure: 0.6,
+            samples: 32,
+            threshold: 0.6,
+        }
+    }
+}
+
+// Rayos de luz: cada píxel acumula los píxeles brillantes que hay entre él y la
+// fuente en pantalla, y el resultado se suma a la imagen.
+pub fn apply_god_rays(framebuffer: &mut Framebuffer, light_position: Vec2, params: &GodRayParams) {
+    let width = framebuffer.width;
+    let height = framebuffer.height;
+
+    // Paso de brillo: solo los píxeles sobre el umbral generan rayos
+    let bright: Vec<[f32; 3]> = framebuffer
+        .buffer
+        .iter()
+        .map(|&pixel| {
+            let color = Color::from_hex(pixel);
+            let rgb = [
+                color.r as f32 / 255.0,
+                color.g as f32 / 255.0,
+                color.b as f32 / 255.0,
+            ];
+            let luminance = 0.2126 * rgb[0] + 0.7152 * rgb[1] + 0.0722 * rgb[2];
+            if luminance > params.threshold {
+                rgb
+            } else {
+                [0.0; 3]
+            }
+        })
+        .collect();
+
+    let samples = params.samples.max(1);
+    for y in 0..height {
+        for x in 0..width {
+            let mut coord = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+            let delta = (coord - light_position) * (params.density / samples as f32);
+            let mut illumination_decay = 1.0;
+            let mut accumulated = [0.0f32; 3];
+
+            for _ in 0..samples {
+                coord -= delta;
+                let sx = coord.x as isize;
+                let sy = coord.y as isize;
+                if sx < 0 || sy < 0 || sx >= width as isize || sy >= height as isize {
+                    break;
+                }
+                let sample = bright[sy as usize * width + sx as usize];
+                for channel in 0..3 {
+                    accumulated[channel] += sample[channel] * illumination_decay * params.weight;
+                }
+                illumination_decay *= params.decay;
+            }
+
+            if accumulated.iter().all(|&value| value <= 0.0) {
+                continue;
+            }
+            let index = y * width + x;
+            let rays = Color::from_float(
+                accumulated[0] * params.exposure,
+                accumulated[1] * params.exposure,
+                accumulated[2] * params.exposure,
+            );
+            framebuffer.buffer[index] =
+                (Color::from_hex(framebuffer.buffer[index]) + rays).to_hex();
+        }
+    }
+}
+
+// Exposición, curva fílmica (aproximación de ACES de Narkowicz) sobre cada canal
+// lineal y luego la gamma de salida; con `tone_mapping` en false solo se aplican la
+// exposición y la gamma. Va sobre la escena, antes del HUD (ver `LightingSettings`).
+pub fn apply_tone_mapping(
+    framebuffer: &mut Framebuffer,
+    tone_mapping: bool,
+    gamma: f32,
+    exposure: f32,
+) {
+    if !tone_mapping && gamma == 1.0 && exposure == 1.0 {
+        return;
+    }
+    // Solo hay 256 valores por canal: se calcula la curva una vez
+    let curve: Vec<u8> = (0..=255)
+        .map(|value| {
+            let mut x = value as f32 / 255.0 * exposure;
+            if tone_mapping {
+                x = (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14);
+            }
+            (x.clamp(0.0, 1.0).powf(1.0 / gamma) * 255.0).round() as u8
+        })
+        .collect();
+    for pixel in &mut framebuffer.buffer {
+        let [_, r, g, b] = pixel.to_be_bytes();
+        *pixel = u32::from_be_bytes([0, curve[r as usize], curve[g as usize], curve[b as usize]]);
+    }
+}
+
+// Exposición automática predeterminada: luminancia media buscada, rapidez de
+// adaptación (por segundo) y límites de la exposición
+pub const DEFAULT_EXPOSURE_TARGET: f32 = 0.08;
+pub const DEFAULT_EXPOSURE_SPEED: f32 = 1.5;
+pub const DEFAULT_EXPOSURE_RANGE: (f32, f32) = (0.25, 2.5);
+// Se mide un píxel de cada tantos; la media apenas cambia y cuesta mucho menos
+const LUMINANCE_SAMPLE_STEP: usize = 7;
+
+// Exposición automática: mide la luminancia media de la escena recién dibujada
+// (antes de aplicarle la exposición) y lleva la exposición hacia la que la dejaría
+// en `target`, suavizada en el tiempo como el ojo que se adapta. Con el Sol en
+// pantalla la escena se oscurece; mirando al espacio vacío se aclara.
+pub struct AutoExposure {
+    pub target: f32,
+    pub speed: f32,
+    pub min: f32,
+    pub max: f32,
+    exposure: f32,
+}
+
+impl AutoExposure {
+    pub fn new(target: f32, speed: f32, (min, max): (f32, f32)) -> Self {
+        AutoExposure {
+            target,
+            speed,
+            min,
+            max,
+            exposure: 1.0f32.clamp(min, max),
+        }
+    }
+
+    pub fn exposure(&self) -> f32 {
+        self.exposure
+    }
+
+    // Mide `framebuffer` y avanza la adaptación `delta_time` segundos reales
+    pub fn update(&mut self, framebuffer: &Framebuffer, delta_time: f32) -> f32 {
+        let luminance = average_luminance(framebuffer);
+        let desired = (self.target / luminance.max(1e-4)).clamp(self.min, self.max);
+        let blend = 1.0 - (-self.speed * delta_time).exp();
+        self.exposure += (desired - self.exposure) * blend;
+        self.exposure
+    }
+}
+
+impl Default for AutoExposure {
+    fn default() -> Self {
+        Self::new(
+            DEFAULT_EXPOSURE_TARGET,
+            DEFAULT_EXPOSURE_SPEED,
+            DEFAULT_EXPOSURE_RANGE,
+        )
+    }
+}
+
+// Luminancia media (0..1) de una muestra de los píxeles
+pub fn average_luminance(framebuffer: &Framebuffer) -> f32 {
+    let (sum, count) = framebuffer
+        .buffer
+        .iter()
+        .step_by(LUMINANCE_SAMPLE_STEP)
+        .fold((0.0f32, 0usize), |(sum, count), &pixel| {
+            let color = Color::from_hex(pixel);
+            let luminance =
+                (0.2126 * color.r as f32 + 0.7152 * color.g as f32 + 0.0722 * color.b as f32)
+                    / 255.0;
+            (sum + luminance, count + 1)
+        });
+    if count == 0 {
+        0.0
+    } else {
+        sum / count as f32
+    }
+}
+
+// Destello de lente: "fantasmas" del Sol a lo largo de la recta que va de su
+// posición en pantalla al centro y sigue del otro lado
+pub const DEFAULT_FLARE_GHOSTS: usize = 6;
+pub const DEFAULT_FLARE_INTENSITY: f32 = 0.5;
+
+pub struct LensFlareParams {
+    pub ghosts: usize,
+    // Intensidad del fantasma más brillante; los demás se escalan por `LENS_GHOSTS`
+    pub intensity: f32,
+    // Fracción de media pantalla junto al borde en la que el destello se apaga
+    pub edge_fade: f32,
+}
+
+impl Default for LensFlareParams {
+    fn default() -> Self {
+        LensFlareParams {
+            ghosts: DEFAULT_FLARE_GHOSTS,
+            intensity: DEFAULT_FLARE_INTENSITY,
+            edge_fade: 0.2,
+        }
+    }
+}
+
+// Por fantasma: posición en la recta (0 = Sol, 1 = centro de la pantalla),
+// radio en fracción del alto, brillo relativo y color. Si hay más fantasmas que
+// entradas se repite la tabla, desplazada para no superponerlos.
+struct LensGhost {
+    position: f32,
+    size: f32,
+    brightness: f32,
+    color: (u8, u8, u8),
+}
+
+const fn ghost(position: f32, size: f32, brightness: f32, color: (u8, u8, u8)) -> LensGhost {
+    LensGhost {
+        position,
+        size,
+        brightness,
+        color,
+    }
+}
+
+const LENS_GHOSTS: [LensGhost; 6] = [
+    ghost(0.45, 0.03, 0.8, (255, 220, 150)),
+    ghost(0.8, 0.015, 1.0, (180, 255, 200)),
+    ghost(1.25, 0.06, 0.45, (140, 180, 255)),
+    ghost(1.5, 0.02, 0.9, (255, 170, 210)),
+    ghost(1.8, 0.09, 0.3, (200, 160, 255)),
+    ghost(2.1, 0.04, 0.6, (255, 240, 200)),
+];
+
+// Suma los fantasmas sobre la imagen. `visibility` (0..1) es la parte del disco
+// del Sol que se ve (ver `render::sun_visibility`); además se apagan cuando el
+// Sol se acerca al borde de la pantalla.
+pub fn apply_lens_flare(
+    framebuffer: &mut Framebuffer,
+    sun_screen: Vec2,
+    visibility: f32,
+    params: &LensFlareParams,
+) {
+    let (width, height) = (framebuffer.width as f32, framebuffer.height as f32);
+    let center = Vec2::new(width, height) * 0.5;
+    // Distancia normalizada al borde más cercano: 0 en el borde, 1 en el centro
+    let edge = (1.0 - ((sun_screen.x - center.x) / center.x).abs())
+        .min(1.0 - ((sun_screen.y - center.y) / center.y).abs());
+    let edge_fade = (edge / params.edge_fade.max(f32::EPSILON)).clamp(0.0, 1.0);
+    let strength = params.intensity * visibility.clamp(0.0, 1.0) * edge_fade;
+    if strength <= 0.0 {
+        return;
+    }
+
+    let axis = center - sun_screen;
+    for index in 0..params.ghosts {
+        let LensGhost {
+            position,
+            size,
+            brightness,
+            color: (r, g, b),
+        } = LENS_GHOSTS[index % LENS_GHOSTS.len()];
+        let position = position + (index / LENS_GHOSTS.len()) as f32 * 0.13;
+        let ghost = sun_screen + axis * position;
+        framebuffer.add_glow(
+            ghost.x,
+            ghost.y,
+            f32::NEG_INFINITY,
+            Color::new(r, g, b),
+            size * height,
+            brightness * strength,
+        );
+    }
+}
+
+// Matriz de Bayer 4x4 para el tramado ordenado
+const BAYER_4X4: [[f32; 4]; 4] = [
+    [0.0, 8.0, 2.0, 10.0],
+    [12.0, 4.0, 14.0, 6.0],
+    [3.0, 11.0, 1.0, 9.0],
+    [15.0, 7.0, 13.0, 5.0],
+];
+
+// Tramado ordenado al presentar la imagen: suma un desplazamiento de hasta
+// ±strength/2 niveles (de 255) que varía por píxel, rompiendo las bandas de los
+// degradados suaves. Como los shaders ya entregan colores de 8 bits, `strength`
+// debe superar 1 para tener efecto. Debe aplicarse antes de dibujar el HUD.
+pub fn apply_dither(framebuffer: &mut Framebuffer, strength: f32) {
+    let width = framebuffer.width;
+    for (index, pixel) in framebuffer.buffer.iter_mut().enumerate() {
+        let (x, y) = (index % width, index / width);
+        let offset = ((BAYER_4X4[y % 4][x % 4] + 0.5) / 16.0 - 0.5) * strength;
+        let color = Color::from_hex(*pixel);
+        let dither = |channel: u8| (channel as f32 + offset).round().clamp(0.0, 255.0) as u8;
+        *pixel = Color::new(dither(color.r), dither(color.g), dither(color.b)).to_hex();
+    }
+}
+
+// Reduce el framebuffer promediando bloques de `factor` x `factor` píxeles (SSAA).
+// Devuelve el buffer de (width / factor) x (height / factor) listo para la ventana.
+pub fn downsample(framebuffer: &Framebuffer, factor: usize) -> Vec<u32> {
+    let factor = factor.max(1);
+    let width = framebuffer.width / factor;
+    let height = framebuffer.height / factor;
+    let samples = (factor * factor) as u32;
+    let mut output = Vec::with_capacity(width * height);
+
+    for y in 0..height {
+        for x in 0..width {
+            let (mut r, mut g, mut b) = (0u32, 0u32, 0u32);
+            for sy in 0..factor {
+                let row = (y * factor + sy) * framebuffer.width;
+                for sx in 0..factor {
+                    let pixel = framebuffer.buffer[row + x * factor + sx];
+                    r += (pixel >> 16) & 0xFF;
+                    g += (pixel >> 8) & 0xFF;
+                    b += pixel & 0xFF;
+                }
+            }
+            output.push(((r / samples) << 16) | ((g / samples) << 8) | (b / samples));
+        }
+    }
+
+    output
+}
+
+// Anaglifo rojo/cian: el canal rojo sale de la vista del ojo izquierdo y el verde
+// y azul de la del derecho, que es la que ya está en el framebuffer
+pub fn combine_anaglyph(left_eye: &[u32], framebuffer: &mut Framebuffer) {
+    for (right, left) in framebuffer.buffer.iter_mut().zip(left_eye) {
+        *right = (left & 0xFF0000) | (*right & 0x00FFFF);
+    }
+}
+
+// Desenfoque de movimiento por acumulación: cada cuadro se mezcla con la imagen
+// acumulada de los anteriores, que se guarda en flotantes para que las colas se
+// apaguen suavemente en lugar de quedarse pegadas por el redondeo a 8 bits.
+// Lo que se mueve rápido deja una estela; lo quieto converge a su color real.
+pub struct MotionBlur {
+    // Peso (0..1) de la imagen acumulada; más alto deja colas más largas
+    pub decay: f32,
+    history: Vec<[f32; 3]>,
+}
+
+impl MotionBlur {
+    pub fn new(decay: f32) -> Self {
+        MotionBlur {
+            decay: decay.clamp(0.0, 0.99),
+            history: Vec::new(),
+        }
+    }
+
+    // Olvida los cuadros anteriores, p. ej. al activar el efecto o cambiar la resolución
+    pub fn reset(&mut self) {
+        self.history.clear();
+    }
+
+    // Debe aplicarse sobre la escena y antes del HUD, para que el texto no se emborrone
+    pub fn apply(&mut self, framebuffer: &mut Framebuffer) {
+        if self.history.len() != framebuffer.buffer.len() {
+            self.history = framebuffer
+                .buffer
+                .iter()
+                .map(|&pixel| {
+                    let color = Color::from_hex(pixel);
+                    [color.r as f32, color.g as f32, color.b as f32]
+                })
+                .collect();
+            return;
+        }
+
+        let decay = self.decay;
+        for (pixel, accumulated) in framebuffer.buffer.iter_mut().zip(&mut self.history) {
+            let color = Color::from_hex(*pixel);
+            let current = [color.r as f32, color.g as f32, color.b as f32];
+            for channel in 0..3 {
+                accumulated[channel] =
+                    current[channel] + (accumulated[channel] - current[channel]) * decay;
+            }
+            *pixel = Color::new(
+                accumulated[0].round() as u8,
+                accumulated[1].round() as u8,
+                accumulated[2].round() as u8,
+            )
+            .to_hex();
+        }
+    }
+}
+
+// Simulación de daltonismo (dicromacias) sobre la imagen final, para revisar que
+// la paleta de los planetas se siga distinguiendo; se recorre con una tecla
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ColorBlindness {
+    Off,
+    // Sin conos L (rojo)
+    Protanopia,
+    // Sin conos M (verde)
+    Deuteranopia,
+    // Sin conos S (azul)
+    Tritanopia,
+}
+
+// RGB lineal a LMS y de vuelta (Viénot, Brettel y Mollon, 1999)
+const RGB_TO_LMS: [[f32; 3]; 3] = [
+    [17.8824, 43.5161, 4.11935],
+    [3.45565, 27.1554, 3.86714],
+    [0.0299566, 0.184309, 1.46709],
+];
+const LMS_TO_RGB: [[f32; 3]; 3] = [
+    [0.08094445, -0.13050441, 0.116721066],
+    [-0.010248533, 0.05401933, -0.11361471],
+    [-0.00036529693, -0.0041216146, 0.6935114],
+];
+
+impl ColorBlindness {
+    pub fn next(self) -> Self {
+        match self {
+            ColorBlindness::Off => ColorBlindness::Protanopia,
+            ColorBlindness::Protanopia => ColorBlindness::Deuteranopia,
+            ColorBlindness::Deuteranopia => ColorBlindness::Tritanopia,
+            ColorBlindness::Tritanopia => ColorBlindness::Off,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ColorBlindness::Off => "Normal",
+            ColorBlindness::Protanopia => "Protanopía",
+            ColorBlindness::Deuteranopia => "Deuteranopía",
+            ColorBlindness::Tritanopia => "Tritanopía",
+        }
+    }
+
+    // El cono que falta se reconstruye a partir de los otros dos, proyectando
+    // sobre el plano de colores que se siguen viendo igual (pasa por el blanco)
+    fn lms_projection(self) -> Option<[[f32; 3]; 3]> {
+        match self {
+            ColorBlindness::Off => None,
+            ColorBlindness::Protanopia => {
+                Some([[0.0, 2.02344, -2.52581], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]])
+            }
+            ColorBlindness::Deuteranopia => {
+                Some([[1.0, 0.0, 0.0], [0.494207, 0.0, 1.24827], [0.0, 0.0, 1.0]])
+            }
+            // Plano por el blanco y el primario rojo, que los tritanopes ven igual
+            ColorBlindness::Tritanopia => Some([
+                [1.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0],
+                [-0.0122450, 0.0720345, 0.0],
+            ]),
+        }
+    }
+
+    // Matriz completa en RGB lineal: RGB -> LMS -> proyección -> RGB
+    fn rgb_matrix(self) -> Option<Mat3> {
+        let to_mat3 = |rows: [[f32; 3]; 3]| {
+            Mat3::new(
+                rows[0][0], rows[0][1], rows[0][2], rows[1][0], rows[1][1], rows[1][2], rows[2][0],
+                rows[2][1], rows[2][2],
+            )
+        };
+        self.lms_projection()
+            .map(|projection| to_mat3(LMS_TO_RGB) * to_mat3(projection) * to_mat3(RGB_TO_LMS))
+    }
+}
+
+// Aplica la simulación a todo el framebuffer. Se trabaja en RGB lineal: los
+// conos responden a la luz, no a los valores con la curva sRGB.
+pub fn simulate_color_blindness(framebuffer: &mut Framebuffer, kind: ColorBlindness) {
+    let Some(matrix) = kind.rgb_matrix() else {
+        return;
+    };
+    let decode: [f32; 256] = std::array::from_fn(|value| srgb_to_linear(value as f32 / 255.0));
+    for pixel in framebuffer.buffer.iter_mut() {
+        let color = Color::from_hex(*pixel);
+        let linear = Vec3::new(
+            decode[color.r as usize],
+            decode[color.g as usize],
+            decode[color.b as usize],
+        );
+        *pixel = Color::from_linear(matrix * linear).to_hex();
+    }
+}
+
+// Vistas de depuración, en el orden en que las recorre la tecla
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DebugView {
+    Off,
+    // Profundidad en grises: cerca blanco, lejos negro
+    Depth,
+    // Normales del G-buffer como color (x, y, z) -> (r, g, b)
+    Normals,
+    // Mallas de alambre de todos los cuerpos sobre fondo negro
+    Wireframe,
+}
+
+impl DebugView {
+    pub fn next(self) -> Self {
+        match self {
+            DebugView::Off => DebugView::Depth,
+            DebugView::Depth => DebugView::Normals,
+            DebugView::Normals => DebugView::Wireframe,
+            DebugView::Wireframe => DebugView::Off,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DebugView::Off => "Normal",
+            DebugView::Depth => "Profundidad",
+            DebugView::Normals => "Normales",
+            DebugView::Wireframe => "Alambre",
+        }
+    }
+}
+
+// Reemplaza la imagen por el z-buffer en grises. La profundidad de NDC se pasa a
+// distancia lineal (proyección en perspectiva con planos `near`/`far`) y se estira
+// entre el píxel más cercano y el más lejano del cuadro para que haya contraste.
+// Lo que no tiene geometría, o solo estrellas detrás de `far_limit`, queda negro.
+pub fn show_depth(framebuffer: &mut Framebuffer, near: f32, far: f32, far_limit: f32) {
+    let linear: Vec<Option<f32>> = framebuffer
+        .zbuffer
+        .iter()
+        .map(|&depth| {
+            (depth < far_limit).then(|| 2.0 * near * far / (far + near - depth * (far - near)))
+        })
+        .collect();
+
+    let (closest, farthest) = linear.iter().flatten().fold(
+        (f32::INFINITY, f32::NEG_INFINITY),
+        |(min, max), &distance| (min.min(distance), max.max(distance)),
+    );
+    let range = (farthest - closest).max(1e-6);
+
+    for (pixel, distance) in framebuffer.buffer.iter_mut().zip(&linear) {
+        *pixel = match distance {
+            Some(distance) => {
+                let gray = (1.0 - (distance - closest) / range).clamp(0.0, 1.0);
+                Color::from_float(gray, gray, gray).to_hex()
+            }
+            None => 0x000000,
+        };
+    }
+}
+
+// Reemplaza la imagen por las normales en espacio mundo; necesita el G-buffer
+pub fn show_normals(framebuffer: &mut Framebuffer) {
+    for y in 0..framebuffer.height {
+        for x in 0..framebuffer.width {
+            let color = match framebuffer.normal_at(x, y) {
+                Some(normal) => {
+                    let normal = normal.normalize() * 0.5 + Vec3::repeat(0.5);
+                    Color::from_float(normal.x, normal.y, normal.z).to_hex()
+                }
+                None => 0x000000,
+            };
+            framebuffer.buffer[y * framebuffer.width + x] = color;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn auto_exposure_dims_bright_scenes_and_brightens_dark_ones() {
+        let mut bright = Framebuffer::new(16, 16);
+        bright.buffer.fill(0xffffff);
+        let mut dark = Framebuffer::new(16, 16);
+        dark.buffer.fill(0x020202);
+
+        let mut auto = AutoExposure::default();
+        let before = auto.exposure();
+        let dimmed = auto.update(&bright, 0.1);
+        assert!(dimmed < before);
+        // Se adapta de a poco y no baja del mínimo
+        for _ in 0..200 {
+            auto.update(&bright, 0.1);
+        }
+        assert!((auto.exposure() - DEFAULT_EXPOSURE_TARGET.max(auto.min)).abs() < 1e-3);
+
+        for _ in 0..200 {
+            auto.update(&dark, 0.1);
+        }
+        assert!((auto.exposure() - auto.max).abs() < 1e-3);
+
+        // La exposición escala los valores antes de la curva
+        let mut framebuffer = Framebuffer::new(1, 1);
+        framebuffer.buffer[0] = 0x404040;
+        apply_tone_mapping(&mut framebuffer, false, 1.0, 2.0);
+        assert_eq!(framebuffer.buffer[0], 0x808080);
+    }
+
+    #[test]
+    fn color_blindness_keeps_grays_and_merges_red_with_green() {
+        let grays = [0x000000, 0x808080, 0xbcbcbc, 0xffffff];
+        let mut kind = ColorBlindness::Protanopia;
+        while kind != ColorBlindness::Off {
+            let mut framebuffer = Framebuffer::new(grays.len(), 1);
+            framebuffer.buffer.copy_from_slice(&grays);
+            simulate_color_blindness(&mut framebuffer, kind);
+            for (&original, &simulated) in grays.iter().zip(&framebuffer.buffer) {
+                let (original, simulated) = (Color::from_hex(original), Color::from_hex(simulated));
+                assert!(
+                    (original.r as i32 - simulated.r as i32).abs() <= 1,
+                    "{:?}",
+                    kind
+                );
+                assert!(
+                    (original.g as i32 - simulated.g as i32).abs() <= 1,
+                    "{:?}",
+                    kind
+                );
+                assert!(
+                    (original.b as i32 - simulated.b as i32).abs() <= 1,
+                    "{:?}",
+                    kind
+                );
+            }
+            kind = kind.next();
+        }
+
+        // Un protanope ve el rojo puro como un tono oliva: rojo y verde casi iguales
+        let mut framebuffer = Framebuffer::new(1, 1);
+        framebuffer.buffer[0] = 0xff0000;
+        simulate_color_blindness(&mut framebuffer, ColorBlindness::Protanopia);
+        let red = Color::from_hex(framebuffer.buffer[0]);
+        assert!((red.r as i32 - red.g as i32).abs() <= 2);
+
+        framebuffer.buffer[0] = 0xff0000;
+        simulate_color_blindness(&mut framebuffer, ColorBlindness::Off);
+        assert_eq!(framebuffer.buffer[0], 0xff0000);
+    }
+}
@@ -0,0 +1,195 @@
+// quality.rs
+// Calidad adaptativa: mide el tiempo de cuadro y sube o baja la resolución de
+// render (submuestreo o SSAA) y el detalle de las esferas para acercarse a un FPS objetivo
+
+// Un escalón de calidad. `render_scale / render_divisor` multiplica la resolución
+// de la ventana: menor que 1 se dibuja a menos resolución y se estira, mayor que 1 es SSAA
+// (se dibuja más grande y se promedia al presentar). `lod_bias` resta divisiones
+// de polo a polo a la esfera configurada.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QualityLevel {
+    pub name: &'static str,
+    pub render_scale: usize,
+    pub render_divisor: usize,
+    pub lod_bias: u32,
+}
+
+impl QualityLevel {
+    // Tamaño del framebuffer para una ventana de `width` x `height`
+    pub fn framebuffer_size(&self, width: usize, height: usize) -> (usize, usize) {
+        (
+            (width * self.render_scale / self.render_divisor).max(1),
+            (height * self.render_scale / self.render_divisor).max(1),
+        )
+    }
+
+    pub fn sphere_stacks(&self, base_stacks: u32) -> u32 {
+        base_stacks.saturating_sub(self.lod_bias).max(4)
+    }
+
+    pub fn ssaa_factor(&self) -> usize {
+        (self.render_scale / self.render_divisor).max(1)
+    }
+
+    // Costo aproximado relativo a "Alta": proporcional a los píxeles dibujados
+    fn relative_cost(&self) -> f32 {
+        let scale = self.render_scale as f32 / self.render_divisor as f32;
+        scale * scale
+    }
+}
+
+// De menor a mayor calidad
+pub const QUALITY_LEVELS: [QualityLevel; 4] = [
+    QualityLevel {
+        name: "Baja",
+        render_scale: 1,
+        render_divisor: 2,
+        lod_bias: 8,
+    },
+    QualityLevel {
+        name: "Media",
+        render_scale: 3,
+        render_divisor: 4,
+        lod_bias: 4,
+    },
+    QualityLevel {
+        name: "Alta",
+        render_scale: 1,
+        render_divisor: 1,
+        lod_bias: 0,
+    },
+    QualityLevel {
+        name: "SSAA 2x",
+        render_scale: 2,
+        render_divisor: 1,
+        lod_bias: 0,
+    },
+];
+
+// Nivel con el que arranca la aplicación y al que se vuelve al desactivar el modo
+pub const DEFAULT_QUALITY_LEVEL: usize = 2;
+
+// Banda de histéresis: se baja por debajo del 90% del objetivo y se sube solo si
+// el FPS estimado en el nivel siguiente (escalado por su costo) supera el 130%,
+// para que un nivel no quede saltando entre dos
+const DOWNGRADE_RATIO: f32 = 0.9;
+const UPGRADE_RATIO: f32 = 1.3;
+// Cuadros a esperar tras un cambio antes de volver a decidir
+const SETTLE_FRAMES: u32 = 45;
+// Peso de cada cuadro nuevo en el promedio móvil del tiempo de cuadro
+const SMOOTHING: f32 = 0.1;
+
+pub struct AutoQuality {
+    pub target_fps: f32,
+    pub level: usize,
+    average_frame_time: Option<f32>,
+    frames_since_change: u32,
+}
+
+impl AutoQuality {
+    pub fn new(target_fps: f32) -> Self {
+        AutoQuality {
+            target_fps,
+            level: DEFAULT_QUALITY_LEVEL,
+            average_frame_time: None,
+            frames_since_change: 0,
+        }
+    }
+
+    pub fn current(&self) -> QualityLevel {
+        QUALITY_LEVELS[self.level]
+    }
+
+    // FPS según el promedio móvil, si ya hay medidas
+    pub fn average_fps(&self) -> Option<f32> {
+        self.average_frame_time.map(|frame_time| 1.0 / frame_time)
+    }
+
+    pub fn set_level(&mut self, level: usize) {
+        self.level = level.min(QUALITY_LEVELS.len() - 1);
+        self.average_frame_time = None;
+        self.frames_since_change = 0;
+    }
+
+    // Registra la duración del último cuadro; devuelve true si cambió el nivel
+    pub fn update(&mut self, frame_time: f32) -> bool {
+        if frame_time <= 0.0 {
+            return false;
+        }
+        let average = match self.average_frame_time {
+            Some(average) => average + (frame_time - average) * SMOOTHING,
+            None => frame_time,
+        };
+        self.average_frame_time = Some(average);
+        self.frames_since_change += 1;
+        if self.frames_since_change < SETTLE_FRAMES {
+            return false;
+        }
+
+        let fps = 1.0 / average;
+        if fps < self.target_fps * DOWNGRADE_RATIO && self.level > 0 {
+            self.set_level(self.level - 1);
+            true
+        } else if self.level + 1 < QUALITY_LEVELS.len() {
+            let next = QUALITY_LEVELS[self.level + 1];
+            let predicted_fps = fps * self.current().relative_cost() / next.relative_cost();
+            if predicted_fps > self.target_fps * UPGRADE_RATIO {
+                self.set_level(self.level + 1);
+                true
+            } else {
+                false
+            }
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Alimenta `frames` cuadros de `fps` constantes y cuenta los cambios de nivel
+    fn feed(quality: &mut AutoQuality, fps: f32, frames: u32) -> usize {
+        (0..frames).filter(|_| quality.update(1.0 / fps)).count()
+    }
+
+    #[test]
+    fn slow_frames_downgrade_once_and_then_settle() {
+        let mut quality = AutoQuality::new(60.0);
+        assert_eq!(feed(&mut quality, 40.0, SETTLE_FRAMES - 1), 0);
+        assert!(quality.update(1.0 / 40.0));
+        assert_eq!(quality.level, DEFAULT_QUALITY_LEVEL - 1);
+        // Tras el cambio espera de nuevo antes de volver a decidir
+        assert_eq!(feed(&mut quality, 40.0, SETTLE_FRAMES - 1), 0);
+        assert_eq!(quality.level, DEFAULT_QUALITY_LEVEL - 1);
+    }
+
+    #[test]
+    fn fps_inside_the_band_keeps_the_level() {
+        let mut quality = AutoQuality::new(60.0);
+        assert_eq!(feed(&mut quality, 60.0, SETTLE_FRAMES * 10), 0);
+        assert_eq!(quality.level, DEFAULT_QUALITY_LEVEL);
+
+        // En "Media", subir a "Alta" casi duplica el costo: 100 FPS no alcanzan
+        quality.set_level(1);
+        assert_eq!(feed(&mut quality, 100.0, SETTLE_FRAMES * 10), 0);
+        assert_eq!(quality.level, 1);
+    }
+
+    #[test]
+    fn upgrade_needs_the_predicted_fps_above_the_upgrade_ratio() {
+        let medium = QUALITY_LEVELS[1].relative_cost();
+        let high = QUALITY_LEVELS[2].relative_cost();
+        let threshold = 60.0 * UPGRADE_RATIO * high / medium;
+
+        let mut quality = AutoQuality::new(60.0);
+        quality.set_level(1);
+        assert_eq!(feed(&mut quality, threshold * 0.97, SETTLE_FRAMES * 4), 0);
+        assert_eq!(quality.level, 1);
+
+        quality.set_level(1);
+        assert_eq!(feed(&mut quality, threshold * 1.03, SETTLE_FRAMES), 1);
+        assert_eq!(quality.level, 2);
+    }
+}
@@ -0,0 +1,1940 @@
+// render.rs
+// Pipeline de dibujo reutilizable: uniforms, matrices, rasterizado de mallas,
+// estelas y proyección a pantalla, más una API mínima `Renderer`/`Scene`
+
+use crate::camera::Camera;
+use crate::color::Color;
+use crate::events::{Events, FrameCallback, SelectCallback};
+use crate::fragment::Fragment;
+use crate::framebuffer::{Framebuffer, Rasterizer};
+use crate::log_debug;
+use crate::obj::IndexedMesh;
+use crate::planet_trail::PlanetTrail;
+use crate::shaders::{
+    camera_position, is_clipped, shader_noise_channel, vertex_shader, LightingSettings,
+};
+use crate::skybox::Skybox;
+use crate::texture::Texture;
+use crate::triangle::{triangle_in_rect, FillRule, FULL_RECT};
+use crate::vertex::Vertex;
+use fastnoise_lite::FastNoiseLite;
+use nalgebra_glm::{look_at, ortho, perspective, Mat4, Vec2, Vec3, Vec4};
+use std::f32::consts::PI;
+
+#[derive(Clone)]
+pub struct Uniforms<'a> {
+    pub model_matrix: Mat4,
+    pub view_matrix: Mat4,
+    pub projection_matrix: Mat4,
+    pub viewport_matrix: Mat4,
+    pub time: f32,
+    // Tiempo de la animación de las superficies (lava, nubes, deriva del terreno);
+    // puede quedar congelado mientras las órbitas siguen con `time`
+    pub surface_time: f32,
+    pub noises: Vec<&'a FastNoiseLite>,
+    // Plano (a, b, c, d) en espacio mundo: se descartan los fragmentos con
+    // a*x + b*y + c*z + d < 0. None dibuja todo.
+    pub clip_plane: Option<Vec4>,
+    // Autoiluminación: se suma al color del shader después de la iluminación,
+    // así el lado nocturno conserva un brillo base. Negro para cuerpos normales.
+    pub emissive: Color,
+    // Luz ambiental global (0..1) que ilumina el lado nocturno de todos los
+    // cuerpos por igual, y un tinte opcional por cuerpo (blanco por defecto)
+    pub ambient: f32,
+    pub ambient_tint: Color,
+    // Color propio del cuerpo, sin luz ni ruido, para `shader_flat`
+    pub albedo: Color,
+    // Parámetros numéricos de los shaders por nombre (umbrales, frecuencias...);
+    // el que falta toma el valor de por defecto del shader (ver `shaders::param`).
+    // Prestados: la tabla se arma una vez y se actualiza en su lugar, no por cuadro.
+    pub params: &'a [(&'static str, f32)],
+    // Posiciones de las fuentes de luz en espacio mundo; la primera es la
+    // principal (el Sol). Vacío ilumina desde +Z como antes.
+    pub lights: Vec<Light>,
+    // Mapa de normales para `shaders::mapped_normal`; None usa la normal de la malla
+    pub normal_map: Option<&'a Texture>,
+    // Esfera que puede tapar la luz (p. ej. Saturno sobre sus anillos)
+    pub shadow_caster: Option<ShadowCaster>,
+    // Preajuste de iluminación (caída de la luz, brillos, tono)
+    pub lighting: LightingSettings,
+    // Cuántas capas de ruido calculan los shaders (ver `shaders::layer_noise`);
+    // None las calcula todas
+    pub noise_layers: Option<usize>,
+}
+
+impl<'a> Uniforms<'a> {
+    // Base de un cuadro: sin cuerpo (modelo identidad, sin ruidos) y con los valores
+    // por defecto de la luz y de cada campo. Cada cuerpo cambia solo lo que lo
+    // distingue: `Uniforms { model_matrix, noises, ..base.clone() }`.
+    pub fn new(
+        view_matrix: Mat4,
+        projection_matrix: Mat4,
+        viewport_matrix: Mat4,
+        time: f32,
+    ) -> Self {
+        Uniforms {
+            model_matrix: Mat4::identity(),
+            view_matrix,
+            projection_matrix,
+            viewport_matrix,
+            time,
+            surface_time: time,
+            noises: vec![],
+            clip_plane: None,
+            emissive: Color::black(),
+            ambient: DEFAULT_AMBIENT,
+            ambient_tint: Color::white(),
+            albedo: Color::white(),
+            params: &[],
+            lights: vec![],
+            normal_map: None,
+            shadow_caster: None,
+            lighting: LightingSettings::default(),
+            noise_layers: None,
+        }
+    }
+}
+
+// Fuente de luz puntual en espacio mundo
+#[derive(Clone, Copy, Debug)]
+pub struct Light {
+    pub position: Vec3,
+    pub color: Color,
+}
+
+// Esfera opaca y fuente de luz puntual, en espacio mundo
+#[derive(Clone, Copy, Debug)]
+pub struct ShadowCaster {
+    pub light_position: Vec3,
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+// Nivel ambiental inicial, ajustable con ',' y '.'
+pub const DEFAULT_AMBIENT: f32 = 0.2;
+
+pub fn create_model_matrix(translation: Vec3, scale: f32, rotation: Vec3) -> Mat4 {
+    let (sin_x, cos_x) = rotation.x.sin_cos();
+    let (sin_y, cos_y) = rotation.y.sin_cos();
+    let (sin_z, cos_z) = rotation.z.sin_cos();
+
+    let rotation_matrix_x = Mat4::new(
+        1.0, 0.0, 0.0, 0.0, 0.0, cos_x, -sin_x, 0.0, 0.0, sin_x, cos_x, 0.0, 0.0, 0.0, 0.0, 1.0,
+    );
+
+    let rotation_matrix_y = Mat4::new(
+        cos_y, 0.0, sin_y, 0.0, 0.0, 1.0, 0.0, 0.0, -sin_y, 0.0, cos_y, 0.0, 0.0, 0.0, 0.0, 1.0,
+    );
+
+    let rotation_matrix_z = Mat4::new(
+        cos_z, -sin_z, 0.0, 0.0, sin_z, cos_z, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0,
+    );
+
+    let rotation_matrix = rotation_matrix_z * rotation_matrix_y * rotation_matrix_x;
+
+    let transform_matrix = Mat4::new(
+        scale,
+        0.0,
+        0.0,
+        translation.x,
+        0.0,
+        scale,
+        0.0,
+        translation.y,
+        0.0,
+        0.0,
+        scale,
+        translation.z,
+        0.0,
+        0.0,
+        0.0,
+        1.0,
+    );
+
+    transform_matrix * rotation_matrix
+}
+
+pub fn create_view_matrix(eye: Vec3, center: Vec3, up: Vec3) -> Mat4 {
+    look_at(&eye, &center, &up)
+}
+
+pub type FragmentShader = fn(&Fragment, &Uniforms) -> Option<Color>;
+
+// Tras la proyección, lo que está entre los planos cercano y lejano cae en z de
+// NDC dentro de [-1, 1] y eso es lo que guarda el zbuffer. Los fragmentos fuera de
+// ese rango se descartan uno a uno (un cuerpo que cruza el plano lejano se corta
+// limpiamente en vez de desaparecer de golpe), y el skybox se dibuja en
+// `skybox::SKYBOX_DEPTH`, justo antes del plano lejano, detrás de todo lo demás.
+pub const NEAR_PLANE: f32 = 0.1;
+pub const FAR_PLANE: f32 = 1000.0;
+// Campo de visión vertical de la perspectiva
+pub const FIELD_OF_VIEW: f32 = 45.0 * PI / 180.0;
+
+// El aspecto debe salir de las dimensiones del framebuffer (las mismas que usa
+// el viewport), no de la ventana; si no, las esferas se ven como elipses.
+pub fn create_perspective_matrix(framebuffer_width: f32, framebuffer_height: f32) -> Mat4 {
+    let aspect_ratio = framebuffer_width / framebuffer_height;
+
+    // nalgebra_glm::perspective recibe (aspect, fovy, near, far), en ese orden
+    perspective(aspect_ratio, FIELD_OF_VIEW, NEAR_PLANE, FAR_PLANE)
+}
+
+// Proyección ortográfica con el mismo encuadre que la perspectiva en el plano a
+// `distance` de la cámara, para poder cambiar entre ambas sin que salte la imagen
+pub fn create_orthographic_matrix(
+    framebuffer_width: f32,
+    framebuffer_height: f32,
+    distance: f32,
+) -> Mat4 {
+    let half_height = distance * (FIELD_OF_VIEW / 2.0).tan();
+    let half_width = half_height * framebuffer_width / framebuffer_height;
+    ortho(
+        -half_width,
+        half_width,
+        -half_height,
+        half_height,
+        NEAR_PLANE,
+        FAR_PLANE,
+    )
+}
+
+// Distancia a la cámara (a lo largo de la vista) del punto con profundidad NDC
+// `ndc_depth`, la que guarda el zbuffer; sirve para la perspectiva y la ortográfica
+pub fn view_distance(projection_matrix: &Mat4, ndc_depth: f32) -> f32 {
+    let p = projection_matrix;
+    let view_z = (p[(2, 3)] - ndc_depth * p[(3, 3)]) / (ndc_depth * p[(3, 2)] - p[(2, 2)]);
+    -view_z
+}
+
+pub fn create_viewport_matrix(width: f32, height: f32) -> Mat4 {
+    Mat4::new(
+        width / 2.0,
+        0.0,
+        0.0,
+        width / 2.0,
+        0.0,
+        -height / 2.0,
+        0.0,
+        height / 2.0,
+        0.0,
+        0.0,
+        1.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        1.0,
+    )
+}
+
+// Lado en píxeles de los bloques del rasterizador por bloques: lo bastante chicos
+// para que el color y la profundidad de un bloque quepan en caché, lo bastante
+// grandes para que un triángulo no se reparta en demasiados
+const TILE_SIZE: usize = 32;
+// Con menos bloques tocados que esto (un cuerpo pequeño, una estela) repartir no
+// compensa copiar los bloques y lanzar los hilos: se dibuja como en el modo simple
+const MIN_PARALLEL_TILES: usize = 8;
+
+// Shader de fragmentos que se puede llamar desde varios hilos a la vez
+pub type SharedShader<'s> = &'s (dyn Fn(&Fragment, &Uniforms) -> Option<Color> + Sync);
+
+pub fn render(
+    framebuffer: &mut Framebuffer,
+    uniforms: &Uniforms,
+    vertex_array: &[Vertex],
+    shader_fn: fn(&Fragment, &Uniforms) -> Option<Color>,
+) {
+    // Vertex Shader Stage
+    let mut transformed_vertices = Vec::with_capacity(vertex_array.len());
+    for vertex in vertex_array {
+        let transformed = vertex_shader(vertex, uniforms);
+        transformed_vertices.push(transformed);
+    }
+
+    // Primitive Assembly Stage
+    let triangles: Vec<[&Vertex; 3]> = transformed_vertices
+        .chunks_exact(3)
+        .map(|tri| [&tri[0], &tri[1], &tri[2]])
+        .collect();
+    draw_triangles(framebuffer, uniforms, &triangles, &shader_fn);
+}
+
+pub fn render_indexed(
+    framebuffer: &mut Framebuffer,
+    uniforms: &Uniforms,
+    mesh: &IndexedMesh,
+    shader_fn: fn(&Fragment, &Uniforms) -> Option<Color>,
+) {
+    render_indexed_with(framebuffer, uniforms, mesh, &shader_fn);
+}
+
+// Como `render_indexed`, aceptando también closures como shader
+pub fn render_indexed_with(
+    framebuffer: &mut Framebuffer,
+    uniforms: &Uniforms,
+    mesh: &IndexedMesh,
+    shader_fn: SharedShader,
+) {
+    // Vertex Shader Stage: cada vértice compartido se transforma una sola vez
+    let transformed_vertices: Vec<Vertex> = mesh
+        .vertices
+        .iter()
+        .map(|vertex| vertex_shader(vertex, uniforms))
+        .collect();
+
+    // Primitive Assembly Stage
+    let triangles: Vec<[&Vertex; 3]> = mesh
+        .indices
+        .chunks_exact(3)
+        .map(|tri| {
+            [
+                &transformed_vertices[tri[0] as usize],
+                &transformed_vertices[tri[1] as usize],
+                &transformed_vertices[tri[2] as usize],
+            ]
+        })
+        .collect();
+    draw_triangles(framebuffer, uniforms, &triangles, shader_fn);
+}
+
+// Rasterización con el modo elegido en el framebuffer
+fn draw_triangles(
+    framebuffer: &mut Framebuffer,
+    uniforms: &Uniforms,
+    triangles: &[[&Vertex; 3]],
+    shader_fn: SharedShader,
+) {
+    match framebuffer.rasterizer() {
+        Rasterizer::Simple => {
+            for &tri in triangles {
+                draw_triangle(framebuffer, uniforms, tri, shader_fn);
+            }
+        }
+        Rasterizer::Tiled => draw_triangles_tiled(framebuffer, uniforms, triangles, shader_fn),
+    }
+}
+
+// Cada triángulo se anota en los bloques que toca su caja envolvente; luego cada
+// bloque se dibuja por separado, en una copia propia, con sus triángulos en el
+// orden original. Como la prueba de profundidad de un píxel ve las mismas
+// escrituras en el mismo orden, la imagen es idéntica a la del modo simple.
+fn draw_triangles_tiled(
+    framebuffer: &mut Framebuffer,
+    uniforms: &Uniforms,
+    triangles: &[[&Vertex; 3]],
+    shader_fn: SharedShader,
+) {
+    let (width, height) = (framebuffer.width, framebuffer.height);
+    if width == 0 || height == 0 {
+        return;
+    }
+    let columns = width.div_ceil(TILE_SIZE);
+    let rows = height.div_ceil(TILE_SIZE);
+
+    let mut bins: Vec<Vec<usize>> = vec![Vec::new(); columns * rows];
+    for (index, tri) in triangles.iter().enumerate() {
+        if let Some((min_x, min_y, max_x, max_y)) = screen_bounds(tri, width, height) {
+            for row in min_y / TILE_SIZE..=max_y / TILE_SIZE {
+                for column in min_x / TILE_SIZE..=max_x / TILE_SIZE {
+                    bins[row * columns + column].push(index);
+                }
+            }
+        }
+    }
+
+    let tiles: Vec<usize> = (0..bins.len())
+        .filter(|&tile| !bins[tile].is_empty())
+        .collect();
+    if tiles.is_empty() {
+        return;
+    }
+    if tiles.len() < MIN_PARALLEL_TILES {
+        for &tri in triangles {
+            draw_triangle(framebuffer, uniforms, tri, shader_fn);
+        }
+        return;
+    }
+    let tile_rect = |tile: usize| {
+        let (x, y) = ((tile % columns) * TILE_SIZE, (tile / columns) * TILE_SIZE);
+        (x, y, TILE_SIZE.min(width - x), TILE_SIZE.min(height - y))
+    };
+
+    let fill_rule = framebuffer.fill_rule();
+    let shared: &Framebuffer = framebuffer;
+    let draw_tile = |tile: usize| {
+        let (x, y, tile_width, tile_height) = tile_rect(tile);
+        let mut region = shared.region(x, y, tile_width, tile_height);
+        let rect = (
+            x as i32,
+            y as i32,
+            (x + tile_width - 1) as i32,
+            (y + tile_height - 1) as i32,
+        );
+        for &index in &bins[tile] {
+            let [v1, v2, v3] = triangles[index];
+            let fragments = triangle_in_rect(v1, v2, v3, rect, fill_rule);
+            shade_fragments(&mut region, (x, y), uniforms, fragments, shader_fn);
+        }
+        (tile, region)
+    };
+
+    // Los bloques se reparten intercalados, así un cuerpo grande en el centro
+    // de la pantalla no le toca entero a un solo hilo
+    let workers = std::thread::available_parallelism()
+        .map_or(1, |count| count.get())
+        .min(tiles.len());
+    let rendered: Vec<(usize, Framebuffer)> = if workers <= 1 {
+        tiles.iter().map(|&tile| draw_tile(tile)).collect()
+    } else {
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..workers)
+                .map(|worker| {
+                    let tiles = &tiles;
+                    let draw_tile = &draw_tile;
+                    scope.spawn(move || {
+                        tiles
+                            .iter()
+                            .skip(worker)
+                            .step_by(workers)
+                            .map(|&tile| draw_tile(tile))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().expect("tile worker panicked"))
+                .collect()
+        })
+    };
+
+    for (tile, region) in rendered {
+        let (x, y, _, _) = tile_rect(tile);
+        framebuffer.write_region(&region, x, y);
+    }
+}
+
+// Caja envolvente en píxeles del triángulo ya transformado, recortada a la
+// pantalla; None si queda fuera o sus vértices no son finitos
+fn screen_bounds(
+    tri: &[&Vertex; 3],
+    width: usize,
+    height: usize,
+) -> Option<(usize, usize, usize, usize)> {
+    let positions = tri.map(|vertex| vertex.transformed_position);
+    if positions
+        .iter()
+        .any(|position| !(position.x.is_finite() && position.y.is_finite()))
+    {
+        return None;
+    }
+    let min_x = positions
+        .iter()
+        .map(|p| p.x)
+        .fold(f32::INFINITY, f32::min)
+        .floor();
+    let min_y = positions
+        .iter()
+        .map(|p| p.y)
+        .fold(f32::INFINITY, f32::min)
+        .floor();
+    let max_x = positions
+        .iter()
+        .map(|p| p.x)
+        .fold(f32::NEG_INFINITY, f32::max)
+        .ceil();
+    let max_y = positions
+        .iter()
+        .map(|p| p.y)
+        .fold(f32::NEG_INFINITY, f32::max)
+        .ceil();
+    if max_x < 0.0 || max_y < 0.0 || min_x >= width as f32 || min_y >= height as f32 {
+        return None;
+    }
+    Some((
+        min_x.max(0.0) as usize,
+        min_y.max(0.0) as usize,
+        (max_x as usize).min(width - 1),
+        (max_y as usize).min(height - 1),
+    ))
+}
+
+// Capa interior mostrada en el corte transversal
+pub struct CutawayLayer {
+    radius: f32, // Relativo al radio del planeta
+    color: Color,
+}
+
+impl CutawayLayer {
+    pub fn new(radius: f32, color: Color) -> Self {
+        CutawayLayer { radius, color }
+    }
+}
+
+// Corte transversal: se recorta la mitad del planeta que mira a la cámara con un
+// plano por su centro y se dibujan las capas como esferas concéntricas recortadas
+// por el mismo plano. La capa más interna se dibuja entera para que sobresalga.
+pub fn render_cutaway(
+    framebuffer: &mut Framebuffer,
+    uniforms: &Uniforms,
+    mesh: &IndexedMesh,
+    shader_fn: FragmentShader,
+    layers: &[CutawayLayer],
+    center: Vec3,
+    camera_position: Vec3,
+) {
+    let normal = (center - camera_position).normalize();
+    let clip_plane = Vec4::new(normal.x, normal.y, normal.z, -normal.dot(&center));
+
+    let uniforms_clipped = Uniforms {
+        clip_plane: Some(clip_plane),
+        ..uniforms.clone()
+    };
+    render_indexed(framebuffer, &uniforms_clipped, mesh, shader_fn);
+
+    let to_camera = -normal;
+    for (i, layer) in layers.iter().enumerate() {
+        let is_innermost = i + 1 == layers.len();
+        let layer_uniforms = Uniforms {
+            model_matrix: uniforms.model_matrix
+                * nalgebra_glm::scaling(&Vec3::repeat(layer.radius)),
+            surface_time: uniforms.surface_time,
+            clip_plane: (!is_innermost).then_some(clip_plane),
+            ..Uniforms::new(
+                uniforms.view_matrix,
+                uniforms.projection_matrix,
+                uniforms.viewport_matrix,
+                uniforms.time,
+            )
+        };
+        // Color plano con un sombreado leve para distinguir la curvatura
+        let layer_shader = |fragment: &Fragment, _: &Uniforms| {
+            Some(layer.color * (0.6 + 0.4 * fragment.normal.normalize().dot(&to_camera).abs()))
+        };
+        render_indexed_with(framebuffer, &layer_uniforms, mesh, &layer_shader);
+    }
+}
+
+// Dibuja las aristas de las caras frontales de la malla sobre la pasada sólida.
+// Cada arista usa la menor profundidad de sus extremos menos un sesgo mínimo:
+// queda por delante de su propia superficie sin atravesar geometría más cercana.
+pub fn render_wireframe(
+    framebuffer: &mut Framebuffer,
+    uniforms: &Uniforms,
+    mesh: &IndexedMesh,
+    color: u32,
+    camera_position: Vec3,
+) {
+    let depth_bias = 1e-6;
+    let transformed_vertices: Vec<Vertex> = mesh
+        .vertices
+        .iter()
+        .map(|vertex| vertex_shader(vertex, uniforms))
+        .collect();
+
+    let on_screen = |vertex: &Vertex| {
+        let p = vertex.transformed_position;
+        p.x >= 0.0
+            && p.y >= 0.0
+            && p.x < framebuffer.width as f32
+            && p.y < framebuffer.height as f32
+            && (-1.0..=1.0).contains(&p.z)
+    };
+
+    let mut edges = Vec::new();
+    for tri in mesh.indices.chunks_exact(3) {
+        let vertices = [tri[0], tri[1], tri[2]].map(|index| &transformed_vertices[index as usize]);
+        if !vertices.iter().all(|vertex| on_screen(vertex)) {
+            continue;
+        }
+
+        // Descartar caras traseras según la normal en espacio mundo
+        let centroid = (vertices[0].position + vertices[1].position + vertices[2].position) / 3.0;
+        let world_centroid =
+            (uniforms.model_matrix * Vec4::new(centroid.x, centroid.y, centroid.z, 1.0)).xyz();
+        let face_normal = vertices[0].transformed_normal
+            + vertices[1].transformed_normal
+            + vertices[2].transformed_normal;
+        if face_normal.dot(&(camera_position - world_centroid)) <= 0.0 {
+            continue;
+        }
+
+        for (a, b) in [(0, 1), (1, 2), (2, 0)] {
+            edges.push((
+                vertices[a].transformed_position,
+                vertices[b].transformed_position,
+            ));
+        }
+    }
+
+    framebuffer.set_current_color(color);
+    for (start, end) in edges {
+        framebuffer.draw_line(
+            start.x as usize,
+            start.y as usize,
+            end.x as usize,
+            end.y as usize,
+            start.z.min(end.z) - depth_bias,
+            1,
+        );
+    }
+}
+
+fn draw_triangle(
+    framebuffer: &mut Framebuffer,
+    uniforms: &Uniforms,
+    tri: [&Vertex; 3],
+    shader_fn: SharedShader,
+) {
+    // Rasterization Stage
+    let fragments = triangle_in_rect(tri[0], tri[1], tri[2], FULL_RECT, framebuffer.fill_rule());
+
+    shade_fragments(framebuffer, (0, 0), uniforms, fragments, shader_fn);
+}
+
+// Fragment Processing Stage. `framebuffer` cubre la parte de la pantalla que
+// empieza en `origin` (toda la pantalla, o un bloque del rasterizador por bloques).
+fn shade_fragments(
+    framebuffer: &mut Framebuffer,
+    origin: (usize, usize),
+    uniforms: &Uniforms,
+    fragments: Vec<Fragment>,
+    shader_fn: SharedShader,
+) {
+    for fragment in fragments {
+        // A la izquierda o arriba de la pantalla
+        if fragment.position.x < 0.0 || fragment.position.y < 0.0 {
+            continue;
+        }
+        let x = (fragment.position.x as usize).wrapping_sub(origin.0);
+        let y = (fragment.position.y as usize).wrapping_sub(origin.1);
+        // Fuera del volumen de visión en profundidad (más cerca que el plano cercano
+        // o más lejos que el lejano)
+        if !(-1.0..=1.0).contains(&fragment.depth) {
+            continue;
+        }
+        if x < framebuffer.width && y < framebuffer.height {
+            let world_position = (uniforms.model_matrix
+                * Vec4::new(
+                    fragment.vertex_position.x,
+                    fragment.vertex_position.y,
+                    fragment.vertex_position.z,
+                    1.0,
+                ))
+            .xyz();
+            if is_clipped(world_position, uniforms) {
+                continue;
+            }
+
+            // Aplicar el shader específico
+            let Some(shaded_color) = shader_fn(&fragment, uniforms) else {
+                continue; // Descartado por el shader
+            };
+            let color = (shaded_color + uniforms.emissive).to_hex();
+            framebuffer.set_current_color(color);
+            if framebuffer.has_gbuffer() {
+                framebuffer.point_with_attributes(
+                    x,
+                    y,
+                    fragment.depth,
+                    fragment.normal,
+                    world_position,
+                );
+            } else {
+                framebuffer.point(x, y, fragment.depth);
+            }
+        }
+    }
+}
+
+// Apariencia común a todas las estelas
+pub struct TrailStyle {
+    pub color_start: Color,
+    pub color_end: Color,
+    // Grosor en píxeles a `reference_distance` de la cámara; se adelgaza con la distancia
+    pub thickness: f32,
+    pub reference_distance: f32,
+    pub antialiased: bool,
+    // Cinta 3D orientada a la cámara (con prueba de profundidad) o, si es false,
+    // líneas en pantalla, más baratas
+    pub ribbon: bool,
+}
+
+// Todos los tramos (líneas o cinta) pasan la prueba de profundidad y escriben la
+// suya, así que donde la estela se cruza consigo misma se ve el tramo más cercano
+// y no el último dibujado
+pub fn render_trail(
+    framebuffer: &mut Framebuffer,
+    uniforms: &Uniforms,
+    trail: &PlanetTrail,
+    style: &TrailStyle,
+) {
+    let num_positions = trail.positions.len();
+    if num_positions < 2 {
+        return; // No hay suficientes puntos para dibujar
+    }
+    if style.ribbon {
+        render_trail_ribbon(framebuffer, uniforms, trail, style);
+        return;
+    }
+
+    // Proyectar las posiciones al espacio de pantalla con la misma proyección que
+    // los vértices de las mallas
+    let screen_positions: Vec<Option<(Vec3, f32)>> = trail
+        .positions
+        .iter()
+        .map(|&position| project_to_screen(position, uniforms))
+        .collect();
+
+    // Dibujar líneas entre las posiciones con efecto de desvanecimiento
+    let mut skipped = 0;
+    for i in 0..(screen_positions.len() - 1) {
+        // Segmentos detrás de la cámara no tienen proyección válida
+        let (Some((start_pos, start_w)), Some((end_pos, end_w))) =
+            (screen_positions[i], screen_positions[i + 1])
+        else {
+            skipped += 1;
+            continue;
+        };
+
+        // Interpolar el color para el efecto de desvanecimiento
+        let t = i as f32 / (screen_positions.len() - 1) as f32;
+        let color = style.color_start.lerp(&style.color_end, t);
+
+        framebuffer.set_current_color(color.to_hex());
+
+        // Perspectiva: el grosor es inversamente proporcional a la distancia, mínimo 1 píxel
+        let segment_distance = (start_w + end_w) * 0.5;
+        let thickness = (style.thickness * style.reference_distance / segment_distance)
+            .round()
+            .max(1.0) as usize;
+
+        // Un segmento largo puede pasar por delante y por detrás de un planeta: se
+        // parte en tramos cortos en pantalla, cada uno con su propia profundidad,
+        // para que la prueba de profundidad lo corte donde corresponde
+        let screen_length = (end_pos.xy() - start_pos.xy()).norm();
+        let pieces =
+            ((screen_length / TRAIL_PIECE_LENGTH).ceil() as usize).clamp(1, MAX_TRAIL_PIECES);
+        let (start_world, end_world) = (trail.positions[i], trail.positions[i + 1]);
+        let mut piece_start = start_pos;
+        for piece in 1..=pieces {
+            // Se interpola en mundo (no en pantalla) para respetar la perspectiva;
+            // entre dos puntos delante de la cámara todos lo están
+            let piece_end = if piece == pieces {
+                end_pos
+            } else {
+                let world = start_world.lerp(&end_world, piece as f32 / pieces as f32);
+                project_to_screen(world, uniforms).map_or(end_pos, |(screen, _)| screen)
+            };
+            let depth = (piece_start.z + piece_end.z) * 0.5;
+            draw_trail_piece(framebuffer, style, piece_start, piece_end, depth, thickness);
+            piece_start = piece_end;
+        }
+    }
+    // Pasa en cada cuadro mientras la cámara esté entre los puntos, así que no es
+    // una advertencia
+    if skipped > 0 {
+        log_debug!(
+            "trail: skipped {} of {} segments behind the camera",
+            skipped,
+            screen_positions.len() - 1
+        );
+    }
+}
+
+// Largo máximo en píxeles de cada tramo con profundidad propia, y tope de tramos
+// por segmento para que un segmento enorme cerca de la cámara no sea carísimo
+const TRAIL_PIECE_LENGTH: f32 = 8.0;
+const MAX_TRAIL_PIECES: usize = 64;
+
+fn draw_trail_piece(
+    framebuffer: &mut Framebuffer,
+    style: &TrailStyle,
+    start_pos: Vec3,
+    end_pos: Vec3,
+    depth: f32,
+    thickness: usize,
+) {
+    // Las estelas finas se ven mejor antialiasadas; las gruesas usan el camino rápido
+    if style.antialiased && thickness <= 1 {
+        framebuffer.draw_line_antialiased(start_pos.x, start_pos.y, end_pos.x, end_pos.y, depth);
+        return;
+    }
+
+    let x0 = start_pos.x.round() as usize;
+    let y0 = start_pos.y.round() as usize;
+    let x1 = end_pos.x.round() as usize;
+    let y1 = end_pos.y.round() as usize;
+
+    framebuffer.draw_line(x0, y0, x1, y1, depth, thickness);
+}
+
+// Segmento en espacio mundo con prueba de profundidad, p. ej. los ejes de
+// coordenadas. Se recorta contra el plano cercano y contra los bordes de la
+// pantalla (puede ser mucho más largo que lo visible) y se parte en tramos
+// cortos, cada uno con su propia profundidad, como las estelas.
+pub fn render_line(
+    framebuffer: &mut Framebuffer,
+    uniforms: &Uniforms,
+    start: Vec3,
+    end: Vec3,
+    color: Color,
+    thickness: usize,
+) {
+    let view_projection = uniforms.projection_matrix * uniforms.view_matrix;
+    let mut clip_start = view_projection * Vec4::new(start.x, start.y, start.z, 1.0);
+    let mut clip_end = view_projection * Vec4::new(end.x, end.y, end.z, 1.0);
+    if clip_start.w < NEAR_PLANE && clip_end.w < NEAR_PLANE {
+        return;
+    }
+    // La parte detrás del plano cercano no tiene proyección válida
+    if clip_start.w < NEAR_PLANE {
+        clip_start = clip_start.lerp(
+            &clip_end,
+            (NEAR_PLANE - clip_start.w) / (clip_end.w - clip_start.w),
+        );
+    } else if clip_end.w < NEAR_PLANE {
+        clip_end = clip_end.lerp(
+            &clip_start,
+            (NEAR_PLANE - clip_end.w) / (clip_start.w - clip_end.w),
+        );
+    }
+    let screen_start = clip_to_screen(clip_start, uniforms);
+    let screen_end = clip_to_screen(clip_end, uniforms);
+
+    // Recorte de Liang-Barsky contra la pantalla; la profundidad (z de NDC) varía
+    // linealmente en pantalla, así que se interpola con el mismo parámetro
+    let delta = screen_end - screen_start;
+    let (mut t_min, mut t_max) = (0.0f32, 1.0f32);
+    let max_x = framebuffer.width.saturating_sub(1) as f32;
+    let max_y = framebuffer.height.saturating_sub(1) as f32;
+    for (p, q) in [
+        (-delta.x, screen_start.x),
+        (delta.x, max_x - screen_start.x),
+        (-delta.y, screen_start.y),
+        (delta.y, max_y - screen_start.y),
+    ] {
+        if p == 0.0 {
+            if q < 0.0 {
+                return;
+            }
+        } else if p < 0.0 {
+            t_min = t_min.max(q / p);
+        } else {
+            t_max = t_max.min(q / p);
+        }
+    }
+    if t_min > t_max {
+        return;
+    }
+    let from = screen_start + delta * t_min;
+    let to = screen_start + delta * t_max;
+
+    framebuffer.set_current_color(color.to_hex());
+    let pieces =
+        (((to - from).xy().norm() / TRAIL_PIECE_LENGTH).ceil() as usize).clamp(1, MAX_TRAIL_PIECES);
+    let mut piece_start = from;
+    for piece in 1..=pieces {
+        let piece_end = from.lerp(&to, piece as f32 / pieces as f32);
+        framebuffer.draw_line(
+            piece_start.x.round() as usize,
+            piece_start.y.round() as usize,
+            piece_end.x.round() as usize,
+            piece_end.y.round() as usize,
+            (piece_start.z + piece_end.z) * 0.5,
+            thickness,
+        );
+        piece_start = piece_end;
+    }
+}
+
+// Cada punto de la estela aporta dos vértices separados a lo ancho, sobre la
+// perpendicular a la estela y a la dirección hacia la cámara; cada segmento son dos
+// triángulos que pasan por el pipeline normal. El ancho sigue la misma regla que las
+// líneas (grosor a `reference_distance`, mínimo 1 píxel), convertido a unidades de
+// mundo con la escala de la proyección a la distancia de cada punto.
+fn render_trail_ribbon(
+    framebuffer: &mut Framebuffer,
+    uniforms: &Uniforms,
+    trail: &PlanetTrail,
+    style: &TrailStyle,
+) {
+    let positions = &trail.positions;
+    let num_positions = positions.len();
+    let eye = camera_position(&uniforms.view_matrix);
+    let view_projection = uniforms.projection_matrix * uniforms.view_matrix;
+    // Alto del viewport en píxeles y escala vertical de la proyección
+    let viewport_height = (-2.0 * uniforms.viewport_matrix[(1, 1)]).abs();
+    let projection_scale = uniforms.projection_matrix[(1, 1)].abs();
+    if viewport_height <= 0.0 || projection_scale <= 0.0 {
+        return;
+    }
+
+    let mut mesh = IndexedMesh {
+        vertices: Vec::with_capacity(num_positions * 2),
+        indices: Vec::with_capacity((num_positions - 1) * 6),
+    };
+    let mut in_front = Vec::with_capacity(num_positions);
+    for (i, &position) in positions.iter().enumerate() {
+        let w = (view_projection * Vec4::new(position.x, position.y, position.z, 1.0)).w;
+        in_front.push(w > 0.0);
+
+        // Dirección de la estela en el punto (diferencia central en los interiores)
+        let previous = positions[i.saturating_sub(1)];
+        let next = positions[(i + 1).min(num_positions - 1)];
+        let to_eye = eye - position;
+        let side = (next - previous).cross(&to_eye);
+        let side = if side.norm() > f32::EPSILON {
+            side.normalize()
+        } else {
+            Vec3::zeros()
+        };
+
+        // Un píxel a esta distancia mide 2 w / (escala * alto) unidades de mundo
+        let distance = w.max(NEAR_PLANE);
+        let thickness = (style.thickness * style.reference_distance / distance).max(1.0);
+        let half_width = thickness * distance / (projection_scale * viewport_height);
+
+        // u recorre la estela de 0 (inicio) a 1 (final) para el degradado
+        let t = i as f32 / (num_positions - 1) as f32;
+        for (offset, v) in [(-half_width, 0.0), (half_width, 1.0)] {
+            mesh.vertices.push(Vertex::new(
+                position + side * offset,
+                to_eye.normalize(),
+                Vec2::new(t, v),
+            ));
+        }
+    }
+
+    for i in 0..(num_positions - 1) {
+        // Segmentos detrás de la cámara no tienen proyección válida
+        if !in_front[i] || !in_front[i + 1] {
+            continue;
+        }
+        let (a, b, c, d) = (
+            2 * i as u32,
+            2 * i as u32 + 1,
+            2 * i as u32 + 2,
+            2 * i as u32 + 3,
+        );
+        mesh.indices.extend_from_slice(&[a, b, c, b, d, c]);
+    }
+
+    let ribbon_uniforms = Uniforms {
+        model_matrix: Mat4::identity(),
+        ..uniforms.clone()
+    };
+    let shader = |fragment: &Fragment, _: &Uniforms| {
+        Some(
+            style
+                .color_start
+                .lerp(&style.color_end, fragment.tex_coords.x),
+        )
+    };
+    render_indexed_with(framebuffer, &ribbon_uniforms, &mesh, &shader);
+}
+
+// Proyección de un punto en espacio mundo: x, y en píxeles y la profundidad (z de
+// NDC) en z, con w (distancia en vista) aparte. None si queda detrás de la cámara.
+pub fn project_to_screen(point: Vec3, uniforms: &Uniforms) -> Option<(Vec3, f32)> {
+    let clip = uniforms.projection_matrix
+        * uniforms.view_matrix
+        * Vec4::new(point.x, point.y, point.z, 1.0);
+    if clip.w <= 0.0 {
+        return None;
+    }
+    Some((clip_to_screen(clip, uniforms), clip.w))
+}
+
+// Paso común a los vértices y a las estelas: división de perspectiva y viewport
+// de una posición en espacio de recorte
+pub fn clip_to_screen(clip: Vec4, uniforms: &Uniforms) -> Vec3 {
+    (uniforms.viewport_matrix * (clip / clip.w)).xyz()
+}
+
+// Radio en píxeles con el que se ve una esfera de radio `radius` centrada en
+// `center`; None si el centro queda detrás de la cámara
+pub fn projected_radius(center: Vec3, radius: f32, uniforms: &Uniforms) -> Option<f32> {
+    let view_center = uniforms.view_matrix * Vec4::new(center.x, center.y, center.z, 1.0);
+    if view_center.z >= 0.0 {
+        return None;
+    }
+    // Se desplaza en x del espacio de vista, paralelo al plano de la imagen
+    let to_screen = |view_position: Vec4| {
+        clip_to_screen(uniforms.projection_matrix * view_position, uniforms).xy()
+    };
+    let edge = view_center + Vec4::new(radius, 0.0, 0.0, 0.0);
+    Some((to_screen(edge) - to_screen(view_center)).magnitude())
+}
+
+// Posición del Sol en pantalla si su centro está visible: dentro del encuadre y
+// sin otro cuerpo delante (se compara con la profundidad de su cara frontal)
+pub fn visible_sun_position(
+    framebuffer: &Framebuffer,
+    uniforms: &Uniforms,
+    sun_position: Vec3,
+    sun_radius: f32,
+    camera_position: Vec3,
+) -> Option<Vec2> {
+    let center = project_to_screen(sun_position, uniforms)?.0;
+    if center.x < 0.0
+        || center.y < 0.0
+        || center.x >= framebuffer.width as f32
+        || center.y >= framebuffer.height as f32
+    {
+        return None;
+    }
+    let front = sun_position + (camera_position - sun_position).normalize() * sun_radius;
+    let front_depth = project_to_screen(front, uniforms)?.0.z;
+    let index = center.y as usize * framebuffer.width + center.x as usize;
+    if framebuffer.zbuffer[index] < front_depth - 1e-6 {
+        return None;
+    }
+    Some(center.xy())
+}
+
+// Posición del Sol en pantalla y fracción (0..1) de su disco que se ve: se
+// muestrea una rejilla de puntos del disco contra el zbuffer, así un planeta que
+// lo tapa a medias o el borde de la pantalla lo apagan de a poco. None si está
+// detrás de la cámara.
+pub fn sun_visibility(
+    framebuffer: &Framebuffer,
+    uniforms: &Uniforms,
+    sun_position: Vec3,
+    sun_radius: f32,
+    camera_position: Vec3,
+) -> Option<(Vec2, f32)> {
+    const GRID: usize = 5;
+    let center = project_to_screen(sun_position, uniforms)?.0;
+    let radius = projected_radius(sun_position, sun_radius, uniforms)?;
+    let front = sun_position + (camera_position - sun_position).normalize() * sun_radius;
+    let front_depth = project_to_screen(front, uniforms)?.0.z;
+
+    let (mut inside, mut visible) = (0, 0);
+    for row in 0..GRID {
+        for column in 0..GRID {
+            // Rejilla en [-1, 1]², solo los puntos dentro del disco (un poco adentro del borde)
+            let offset = Vec2::new(column as f32, row as f32) / (GRID - 1) as f32 * 2.0
+                - Vec2::new(1.0, 1.0);
+            if offset.magnitude() > 1.0 {
+                continue;
+            }
+            inside += 1;
+            let point = center.xy() + offset * radius * 0.8;
+            if point.x < 0.0
+                || point.y < 0.0
+                || point.x >= framebuffer.width as f32
+                || point.y >= framebuffer.height as f32
+            {
+                continue;
+            }
+            let index = point.y as usize * framebuffer.width + point.x as usize;
+            if framebuffer.zbuffer[index] >= front_depth - 1e-6 {
+                visible += 1;
+            }
+        }
+    }
+    Some((center.xy(), visible as f32 / inside as f32))
+}
+
+// Un cuerpo a dibujar: malla, transformación, shader y los ruidos que usa, más
+// lo propio del cuerpo en sus uniforms. `SceneBody::new` deja el resto como en un
+// cuerpo sin nada especial; cada cuerpo cambia solo lo que lo distingue.
+#[derive(Clone)]
+pub struct SceneBody<'a> {
+    pub mesh: &'a IndexedMesh,
+    pub model_matrix: Mat4,
+    pub shader: FragmentShader,
+    pub noises: Vec<&'a FastNoiseLite>,
+    pub emissive: Color,
+    pub ambient_tint: Color,
+    pub albedo: Color,
+    pub params: &'a [(&'static str, f32)],
+    pub normal_map: Option<&'a Texture>,
+    pub shadow_caster: Option<ShadowCaster>,
+    pub noise_layers: Option<usize>,
+    pub style: BodyStyle<'a>,
+    // Rectángulo de recorte y regla de relleno propios; None usa los del framebuffer
+    pub scissor: Option<(usize, usize, usize, usize)>,
+    pub fill_rule: Option<FillRule>,
+}
+
+// Cómo se dibuja un cuerpo de la escena
+#[derive(Clone, Copy, Default)]
+pub enum BodyStyle<'a> {
+    #[default]
+    Shaded,
+    // Un canal de sus ruidos en gris, sin luz propia (ver `shader_noise_channel`)
+    NoiseChannel(usize),
+    // Abierto por la mitad que mira a la cámara, con sus capas (ver `render_cutaway`)
+    Cutaway(&'a [CutawayLayer]),
+}
+
+impl<'a> SceneBody<'a> {
+    pub fn new(
+        mesh: &'a IndexedMesh,
+        model_matrix: Mat4,
+        shader: FragmentShader,
+        noises: Vec<&'a FastNoiseLite>,
+    ) -> Self {
+        SceneBody {
+            mesh,
+            model_matrix,
+            shader,
+            noises,
+            emissive: Color::black(),
+            ambient_tint: Color::white(),
+            albedo: Color::white(),
+            params: &[],
+            normal_map: None,
+            shadow_caster: None,
+            noise_layers: None,
+            style: BodyStyle::Shaded,
+            scissor: None,
+            fill_rule: None,
+        }
+    }
+
+    // Uniforms con los que se dibuja el cuerpo sobre los del cuadro (`frame`)
+    pub fn uniforms(&self, frame: &Uniforms<'a>) -> Uniforms<'a> {
+        Uniforms {
+            model_matrix: self.model_matrix,
+            noises: self.noises.clone(),
+            emissive: self.emissive,
+            ambient_tint: self.ambient_tint,
+            albedo: self.albedo,
+            params: self.params,
+            normal_map: self.normal_map,
+            shadow_caster: self.shadow_caster,
+            noise_layers: self.noise_layers,
+            ..frame.clone()
+        }
+    }
+
+    // Centro del cuerpo en espacio mundo
+    pub fn center(&self) -> Vec3 {
+        (self.model_matrix * Vec4::new(0.0, 0.0, 0.0, 1.0)).xyz()
+    }
+}
+
+// Lo que se dibuja en un cuadro, en orden; el skybox va primero si existe.
+// Los cuerpos son opacos y pasan por el zbuffer, así que su orden no cambia la
+// imagen (ver `order_mismatch`). Las pasadas que mezclan con lo ya dibujado sí
+// dependen del orden y no forman parte de una escena: estelas y líneas suavizadas
+// y partículas (`blend_point`), halos (`add_glow`), el HUD (`blend_pixel`) y los
+// efectos de pantalla completa como los rayos de luz o el destello del Sol.
+pub struct Scene<'a> {
+    pub skybox: Option<&'a Skybox>,
+    pub bodies: Vec<SceneBody<'a>>,
+    pub lights: Vec<Light>,
+    // Lo común a todos los cuerpos del cuadro (ver `Uniforms`); `surface_time`
+    // None sigue al tiempo del cuadro
+    pub ambient: f32,
+    pub lighting: LightingSettings,
+    pub surface_time: Option<f32>,
+}
+
+impl Default for Scene<'_> {
+    fn default() -> Self {
+        Scene {
+            skybox: None,
+            bodies: vec![],
+            lights: vec![],
+            ambient: DEFAULT_AMBIENT,
+            lighting: LightingSettings::default(),
+            surface_time: None,
+        }
+    }
+}
+
+// Framebuffer con las matrices de proyección y viewport que le corresponden.
+// Sirve para incrustar el renderizador o dibujar sin ventana (pruebas, capturas).
+// Los eventos que avisa a la aplicación están descritos en `events.rs`.
+pub struct Renderer {
+    pub framebuffer: Framebuffer,
+    pub projection_matrix: Mat4,
+    pub viewport_matrix: Mat4,
+    // Cuerpo de la escena seleccionado por la aplicación, si hay
+    selected: Option<usize>,
+    events: Events,
+}
+
+impl Renderer {
+    pub fn new(width: usize, height: usize) -> Self {
+        Renderer {
+            framebuffer: Framebuffer::new(width, height),
+            projection_matrix: create_perspective_matrix(width as f32, height as f32),
+            viewport_matrix: create_viewport_matrix(width as f32, height as f32),
+            selected: None,
+            events: Events::default(),
+        }
+    }
+
+    // Cambia el tamaño de dibujo: framebuffer nuevo (con su configuración por defecto)
+    // y las matrices de su tamaño; se conservan la selección y los eventos
+    pub fn resize(&mut self, width: usize, height: usize) {
+        self.framebuffer = Framebuffer::new(width, height);
+        self.projection_matrix = create_perspective_matrix(width as f32, height as f32);
+        self.viewport_matrix = create_viewport_matrix(width as f32, height as f32);
+    }
+
+    pub fn on_select(&mut self, callback: SelectCallback) {
+        self.events.on_select(callback);
+    }
+
+    pub fn on_frame(&mut self, callback: FrameCallback) {
+        self.events.on_frame(callback);
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    // Selecciona un cuerpo de la escena (o ninguno) y avisa solo si cambió
+    pub fn select(&mut self, index: Option<usize>) {
+        if self.selected != index {
+            self.selected = index;
+            self.events.selected(index);
+        }
+    }
+
+    pub fn uniforms<'a>(
+        &self,
+        camera: &Camera,
+        model_matrix: Mat4,
+        time: f32,
+        noises: Vec<&'a FastNoiseLite>,
+    ) -> Uniforms<'a> {
+        Uniforms {
+            model_matrix,
+            noises,
+            ..Uniforms::new(
+                camera.view_matrix(),
+                self.projection_matrix,
+                self.viewport_matrix,
+                time,
+            )
+        }
+    }
+
+    // Limpia el framebuffer y dibuja la escena completa desde la cámara, sin avisar:
+    // la aplicación puede dibujar encima antes de `finish_frame`
+    pub fn render_scene(&mut self, scene: &Scene, camera: &Camera, time: f32) {
+        draw_scene(
+            &mut self.framebuffer,
+            self.projection_matrix,
+            self.viewport_matrix,
+            scene,
+            camera,
+            time,
+        );
+    }
+
+    // El cuadro está listo para presentarse: avisa con el framebuffer final
+    pub fn finish_frame(&mut self, time: f32) {
+        self.events.frame_rendered(&self.framebuffer, time);
+    }
+
+    pub fn render_frame(&mut self, scene: &Scene, camera: &Camera, time: f32) {
+        self.render_scene(scene, camera, time);
+        self.finish_frame(time);
+    }
+}
+
+// Dibuja la escena en otro framebuffer (p. ej. uno pequeño fuera de pantalla para
+// el minimapa), con la proyección y el viewport de su propio tamaño. El resultado
+// se puede componer sobre el cuadro principal con `Framebuffer::blit`.
+pub fn render_scene_into(framebuffer: &mut Framebuffer, scene: &Scene, camera: &Camera, time: f32) {
+    let (width, height) = (framebuffer.width as f32, framebuffer.height as f32);
+    draw_scene(
+        framebuffer,
+        create_perspective_matrix(width, height),
+        create_viewport_matrix(width, height),
+        scene,
+        camera,
+        time,
+    );
+}
+
+// Píxeles que cambian al dibujar los cuerpos de la escena en orden inverso, con
+// algún canal a más de `tolerance` del original. Con un zbuffer correcto es 0:
+// sirve para detectar regresiones de profundidad.
+pub fn order_mismatch(
+    width: usize,
+    height: usize,
+    scene: &Scene,
+    camera: &Camera,
+    time: f32,
+    tolerance: u8,
+) -> usize {
+    let reversed = Scene {
+        skybox: scene.skybox,
+        bodies: scene.bodies.iter().rev().cloned().collect(),
+        lights: scene.lights.clone(),
+        ambient: scene.ambient,
+        lighting: scene.lighting,
+        surface_time: scene.surface_time,
+    };
+    let mut forward_buffer = Framebuffer::new(width, height);
+    let mut reversed_buffer = Framebuffer::new(width, height);
+    render_scene_into(&mut forward_buffer, scene, camera, time);
+    render_scene_into(&mut reversed_buffer, &reversed, camera, time);
+    forward_buffer.count_differences(&reversed_buffer, tolerance)
+}
+
+fn draw_scene(
+    framebuffer: &mut Framebuffer,
+    projection_matrix: Mat4,
+    viewport_matrix: Mat4,
+    scene: &Scene,
+    camera: &Camera,
+    time: f32,
+) {
+    framebuffer.clear();
+    let frame = Uniforms {
+        surface_time: scene.surface_time.unwrap_or(time),
+        ambient: scene.ambient,
+        lights: scene.lights.clone(),
+        lighting: scene.lighting,
+        ..Uniforms::new(
+            camera.view_matrix(),
+            projection_matrix,
+            viewport_matrix,
+            time,
+        )
+    };
+
+    if let Some(skybox) = scene.skybox {
+        skybox.render(framebuffer, &frame, camera.eye);
+    }
+
+    let (scissor, fill_rule) = (framebuffer.scissor(), framebuffer.fill_rule());
+    for body in &scene.bodies {
+        let uniforms = body.uniforms(&frame);
+        framebuffer.set_scissor(body.scissor.or(scissor));
+        framebuffer.set_fill_rule(body.fill_rule.unwrap_or(fill_rule));
+        match body.style {
+            BodyStyle::Shaded => render_indexed(framebuffer, &uniforms, body.mesh, body.shader),
+            BodyStyle::NoiseChannel(channel) => {
+                let uniforms = Uniforms {
+                    emissive: Color::black(),
+                    ..uniforms
+                };
+                let shader = |fragment: &Fragment, uniforms: &Uniforms| {
+                    Some(shader_noise_channel(fragment, uniforms, channel))
+                };
+                render_indexed_with(framebuffer, &uniforms, body.mesh, &shader);
+            }
+            BodyStyle::Cutaway(layers) => render_cutaway(
+                framebuffer,
+                &uniforms,
+                body.mesh,
+                body.shader,
+                layers,
+                body.center(),
+                camera.eye,
+            ),
+        }
+    }
+    framebuffer.set_scissor(scissor);
+    framebuffer.set_fill_rule(fill_rule);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shaders::shader_flat;
+    use crate::{mesh, skybox};
+
+    fn white_shader(_fragment: &Fragment, _uniforms: &Uniforms) -> Option<Color> {
+        Some(Color::new(255, 255, 255))
+    }
+
+    fn transform_point(matrix: &Mat4, point: Vec3) -> Vec3 {
+        let transformed = matrix * Vec4::new(point.x, point.y, point.z, 1.0);
+        assert_eq!(transformed.w, 1.0);
+        transformed.xyz()
+    }
+
+    fn assert_close(actual: Vec3, expected: Vec3) {
+        assert!(
+            (actual - expected).magnitude() < 1e-5,
+            "expected {:?}, got {:?}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn model_matrix_translates_and_scales() {
+        let matrix = create_model_matrix(Vec3::new(3.0, -2.0, 5.0), 2.0, Vec3::zeros());
+
+        assert_close(
+            transform_point(&matrix, Vec3::zeros()),
+            Vec3::new(3.0, -2.0, 5.0),
+        );
+        assert_close(
+            transform_point(&matrix, Vec3::new(1.0, 1.0, -1.0)),
+            Vec3::new(5.0, 0.0, 3.0),
+        );
+        // La traslación vive en la cuarta columna, no en la cuarta fila
+        assert_eq!(matrix.column(3).xyz(), Vec3::new(3.0, -2.0, 5.0));
+        assert_eq!(
+            matrix.row(3),
+            nalgebra_glm::vec4(0.0, 0.0, 0.0, 1.0).transpose()
+        );
+    }
+
+    #[test]
+    fn view_distance_inverts_the_projected_depth() {
+        let projections = [
+            create_perspective_matrix(800.0, 600.0),
+            create_orthographic_matrix(800.0, 600.0, 30.0),
+        ];
+        for projection in projections {
+            for distance in [0.5, 4.0, 37.0, 250.0] {
+                let clip = projection * Vec4::new(0.3, -0.2, -distance, 1.0);
+                let ndc_depth = clip.z / clip.w;
+                let recovered = view_distance(&projection, ndc_depth);
+                assert!((recovered - distance).abs() < distance * 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn projected_radius_shrinks_with_distance() {
+        let renderer = Renderer::new(800, 600);
+        let camera = Camera::new(
+            Vec3::zeros(),
+            Vec3::new(0.0, 0.0, -1.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        );
+        let uniforms = renderer.uniforms(&camera, Mat4::identity(), 0.0, vec![]);
+
+        let near = projected_radius(Vec3::new(0.0, 0.0, -10.0), 1.0, &uniforms).unwrap();
+        let far = projected_radius(Vec3::new(0.0, 0.0, -100.0), 1.0, &uniforms).unwrap();
+        assert!((near / far - 10.0).abs() < 1e-3);
+        assert!(projected_radius(Vec3::new(0.0, 0.0, 10.0), 1.0, &uniforms).is_none());
+    }
+
+    #[test]
+    fn model_matrix_rotates_about_each_axis() {
+        let quarter = PI / 2.0;
+        let cases = [
+            // (rotación, punto, resultado esperado), regla de la mano derecha
+            (
+                Vec3::new(quarter, 0.0, 0.0),
+                Vec3::new(0.0, 1.0, 0.0),
+                Vec3::new(0.0, 0.0, 1.0),
+            ),
+            (
+                Vec3::new(0.0, quarter, 0.0),
+                Vec3::new(0.0, 0.0, 1.0),
+                Vec3::new(1.0, 0.0, 0.0),
+            ),
+            (
+                Vec3::new(0.0, 0.0, quarter),
+                Vec3::new(1.0, 0.0, 0.0),
+                Vec3::new(0.0, 1.0, 0.0),
+            ),
+        ];
+
+        for (rotation, point, expected) in cases {
+            let matrix = create_model_matrix(Vec3::zeros(), 1.0, rotation);
+            assert_close(transform_point(&matrix, point), expected);
+        }
+    }
+
+    #[test]
+    fn model_matrix_is_translate_rotate_scale() {
+        let cases = [
+            (Vec3::new(1.0, 2.0, 3.0), 0.5, Vec3::new(0.3, 0.0, 0.0)),
+            (Vec3::new(-4.0, 0.0, 10.0), 3.0, Vec3::new(0.0, -1.2, 0.0)),
+            (Vec3::new(0.0, 7.5, -2.0), 1.5, Vec3::new(0.0, 0.0, 2.0)),
+            (Vec3::new(20.0, -3.0, 0.5), 0.8, Vec3::new(0.4, 1.1, -0.7)),
+        ];
+        let points = [
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, -1.0, 0.5),
+            Vec3::new(0.3, 0.7, -0.2),
+        ];
+
+        for (translation, scale, rotation) in cases {
+            let matrix = create_model_matrix(translation, scale, rotation);
+            // Orden de las rotaciones: primero X, luego Y y al final Z
+            let expected = nalgebra_glm::translation(&translation)
+                * nalgebra_glm::rotation(rotation.z, &Vec3::z())
+                * nalgebra_glm::rotation(rotation.y, &Vec3::y())
+                * nalgebra_glm::rotation(rotation.x, &Vec3::x())
+                * nalgebra_glm::scaling(&Vec3::repeat(scale));
+
+            for point in points {
+                assert_close(
+                    transform_point(&matrix, point),
+                    transform_point(&expected, point),
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn sphere_stays_round_on_wide_framebuffer() {
+        let (width, height) = (1280, 720);
+        let mut framebuffer = Framebuffer::new(width, height);
+        framebuffer.clear();
+
+        let sphere = mesh::uv_sphere(16, 32);
+        let camera = Camera::new(
+            Vec3::new(0.0, 0.0, 5.0),
+            Vec3::zeros(),
+            Vec3::new(0.0, 1.0, 0.0),
+        );
+        let uniforms = Renderer::new(width, height).uniforms(
+            &camera,
+            create_model_matrix(Vec3::zeros(), 1.0, Vec3::zeros()),
+            0.0,
+            vec![],
+        );
+        render_indexed(&mut framebuffer, &uniforms, &sphere, white_shader);
+
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (usize::MAX, usize::MAX, 0, 0);
+        for y in 0..height {
+            for x in 0..width {
+                if framebuffer.buffer[y * width + x] != 0 {
+                    min_x = min_x.min(x);
+                    min_y = min_y.min(y);
+                    max_x = max_x.max(x);
+                    max_y = max_y.max(y);
+                }
+            }
+        }
+
+        let silhouette_width = (max_x - min_x + 1) as f32;
+        let silhouette_height = (max_y - min_y + 1) as f32;
+        assert!(silhouette_width > 50.0, "sphere should be visible");
+        assert!(
+            (silhouette_width / silhouette_height - 1.0).abs() < 0.03,
+            "silhouette is {}x{} pixels",
+            silhouette_width,
+            silhouette_height
+        );
+    }
+
+    fn normal_shader(fragment: &Fragment, _uniforms: &Uniforms) -> Option<Color> {
+        let normal = fragment.normal.normalize() * 0.5 + Vec3::repeat(0.5);
+        Some(Color::from_float(normal.x, normal.y, normal.z))
+    }
+
+    // Esferas que se tapan entre sí y se salen por los bordes, sobre un tamaño que
+    // no es múltiplo del bloque, con G-buffer y recorte
+    fn render_overlapping_spheres(rasterizer: Rasterizer) -> Framebuffer {
+        let mut renderer = Renderer::new(150, 110);
+        renderer.framebuffer.set_rasterizer(rasterizer);
+        renderer.framebuffer.set_gbuffer_enabled(true);
+        renderer.framebuffer.set_scissor(Some((5, 3, 130, 100)));
+        renderer.framebuffer.clear();
+
+        let camera = Camera::new(
+            Vec3::new(0.0, 0.0, 6.0),
+            Vec3::zeros(),
+            Vec3::new(0.0, 1.0, 0.0),
+        );
+        let sphere = mesh::uv_sphere(12, 24);
+        let bodies = [
+            (Vec3::new(0.0, 0.0, 0.0), 1.5),
+            (Vec3::new(1.2, 0.4, 1.0), 0.8),
+            (Vec3::new(-3.2, -1.5, 0.5), 1.2),
+        ];
+        for (translation, scale) in bodies {
+            let uniforms = renderer.uniforms(
+                &camera,
+                create_model_matrix(translation, scale, Vec3::new(0.3, 0.5, 0.0)),
+                0.0,
+                vec![],
+            );
+            render_indexed(&mut renderer.framebuffer, &uniforms, &sphere, normal_shader);
+        }
+        renderer.framebuffer
+    }
+
+    #[test]
+    fn tiled_rasterizer_matches_simple_rasterizer() {
+        let simple = render_overlapping_spheres(Rasterizer::Simple);
+        let tiled = render_overlapping_spheres(Rasterizer::Tiled);
+
+        assert!(simple.buffer.iter().any(|&pixel| pixel != 0));
+        assert_eq!(simple.buffer, tiled.buffer);
+        assert_eq!(simple.zbuffer, tiled.zbuffer);
+        for y in 0..simple.height {
+            for x in 0..simple.width {
+                assert_eq!(simple.normal_at(x, y), tiled.normal_at(x, y));
+                assert_eq!(
+                    simple.world_position_at(x, y),
+                    tiled.world_position_at(x, y)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn scissor_discards_fragments_outside_rectangle() {
+        let (width, height) = (100, 100);
+        let mut renderer = Renderer::new(width, height);
+        renderer.framebuffer.clear();
+        renderer.framebuffer.set_scissor(Some((20, 30, 40, 25)));
+
+        // Una esfera que cubre toda la vista
+        let camera = Camera::new(
+            Vec3::new(0.0, 0.0, 2.0),
+            Vec3::zeros(),
+            Vec3::new(0.0, 1.0, 0.0),
+        );
+        let sphere = mesh::uv_sphere(16, 32);
+        let uniforms = renderer.uniforms(
+            &camera,
+            create_model_matrix(Vec3::zeros(), 1.5, Vec3::zeros()),
+            0.0,
+            vec![],
+        );
+        render_indexed(&mut renderer.framebuffer, &uniforms, &sphere, white_shader);
+
+        for y in 0..height {
+            for x in 0..width {
+                let inside = (20..60).contains(&x) && (30..55).contains(&y);
+                let written = renderer.framebuffer.buffer[y * width + x] != 0;
+                assert_eq!(written, inside, "pixel ({}, {})", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn flat_shader_ignores_realistic_highlights() {
+        let (width, height) = (100, 100);
+        let mut renderer = Renderer::new(width, height);
+        renderer.framebuffer.clear();
+
+        // Luz junto a la cámara: de frente habría brillo especular y borde de Fresnel
+        let camera = Camera::new(
+            Vec3::new(0.0, 0.0, 4.0),
+            Vec3::zeros(),
+            Vec3::new(0.0, 1.0, 0.0),
+        );
+        let albedo = Color::new(40, 120, 200);
+        let uniforms = Uniforms {
+            lighting: LightingSettings::REALISTIC,
+            lights: vec![Light {
+                position: Vec3::new(0.0, 0.0, 4.0),
+                color: Color::white(),
+            }],
+            albedo,
+            ..renderer.uniforms(
+                &camera,
+                create_model_matrix(Vec3::zeros(), 1.0, Vec3::zeros()),
+                0.0,
+                vec![],
+            )
+        };
+        let sphere = mesh::uv_sphere(16, 32);
+        render_indexed(&mut renderer.framebuffer, &uniforms, &sphere, shader_flat);
+
+        let written: Vec<u32> = renderer
+            .framebuffer
+            .buffer
+            .iter()
+            .copied()
+            .filter(|&pixel| pixel != 0)
+            .collect();
+        assert!(!written.is_empty());
+        assert!(written.iter().all(|&pixel| pixel == albedo.to_hex()));
+    }
+
+    // Cuenta los píxeles blancos de un cuerpo de radio `radius` a `distance`
+    // unidades delante de la cámara, con el fondo ya ocupado por el skybox
+    fn far_body_pixels(distance: f32, radius: f32) -> usize {
+        let (width, height) = (400, 400);
+        let mut framebuffer = Framebuffer::new(width, height);
+        framebuffer.clear();
+        framebuffer.set_current_color(0x202020);
+        for y in 0..height {
+            for x in 0..width {
+                framebuffer.point(x, y, skybox::SKYBOX_DEPTH);
+            }
+        }
+
+        let sphere = mesh::uv_sphere(16, 32);
+        let camera = Camera::new(
+            Vec3::zeros(),
+            Vec3::new(0.0, 0.0, -1.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        );
+        let uniforms = Renderer::new(width, height).uniforms(
+            &camera,
+            create_model_matrix(Vec3::new(0.0, 0.0, -distance), radius, Vec3::zeros()),
+            0.0,
+            vec![],
+        );
+        render_indexed(&mut framebuffer, &uniforms, &sphere, white_shader);
+
+        assert!(framebuffer
+            .zbuffer
+            .iter()
+            .all(|&depth| depth <= skybox::SKYBOX_DEPTH));
+        framebuffer
+            .buffer
+            .iter()
+            .filter(|&&pixel| pixel == 0xFFFFFF)
+            .count()
+    }
+
+    #[test]
+    fn body_near_far_plane_renders_in_front_of_skybox() {
+        let radius = 20.0;
+        let inside = far_body_pixels(FAR_PLANE - 2.0 * radius, radius);
+        assert!(inside > 100, "only {} pixels visible", inside);
+
+        // Cruzando el plano lejano se ve el casquete frontal, no desaparece entero
+        let straddling = far_body_pixels(FAR_PLANE, radius);
+        assert!(
+            straddling > 0 && straddling < inside,
+            "{} pixels straddling vs {} inside",
+            straddling,
+            inside
+        );
+
+        let beyond = far_body_pixels(FAR_PLANE + 2.0 * radius, radius);
+        assert_eq!(beyond, 0);
+    }
+
+    #[test]
+    fn trail_segment_is_hidden_where_it_passes_behind_a_body() {
+        let (width, height) = (400, 400);
+        let mut framebuffer = Framebuffer::new(width, height);
+        framebuffer.clear();
+        let camera = Camera::new(
+            Vec3::zeros(),
+            Vec3::new(0.0, 0.0, -1.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        );
+        let uniforms = Renderer::new(width, height).uniforms(
+            &camera,
+            create_model_matrix(Vec3::new(0.0, 0.0, -10.0), 1.0, Vec3::zeros()),
+            0.0,
+            vec![],
+        );
+        render_indexed(
+            &mut framebuffer,
+            &uniforms,
+            &mesh::uv_sphere(16, 32),
+            white_shader,
+        );
+
+        // Un solo segmento que entra por delante de la esfera (a la izquierda) y
+        // sale por detrás (a la derecha)
+        let mut trail = PlanetTrail::new(2);
+        trail.add_position(Vec3::new(-3.0, 0.0, -7.0));
+        trail.add_position(Vec3::new(3.0, 0.0, -13.0));
+        let red = Color::new(255, 0, 0);
+        let style = TrailStyle {
+            color_start: red,
+            color_end: red,
+            thickness: 1.0,
+            reference_distance: 1.0,
+            antialiased: false,
+            ribbon: false,
+        };
+        render_trail(&mut framebuffer, &uniforms, &trail, &style);
+
+        let center = project_to_screen(Vec3::new(0.0, 0.0, -10.0), &uniforms)
+            .unwrap()
+            .0
+            .xy();
+        let edge = project_to_screen(Vec3::new(1.0, 0.0, -10.0), &uniforms)
+            .unwrap()
+            .0
+            .xy();
+        let radius = (edge.x - center.x) as usize;
+        let (center_x, center_y) = (center.x as usize, center.y as usize);
+        let red_pixels = |columns: std::ops::Range<usize>| {
+            columns
+                .flat_map(|x| (center_y - 1..=center_y + 1).map(move |y| (x, y)))
+                .filter(|&(x, y)| framebuffer.buffer[y * width + x] == red.to_hex())
+                .count()
+        };
+        assert!(red_pixels(center_x - radius..center_x) > 0);
+        assert_eq!(red_pixels(center_x + 1..center_x + radius), 0);
+    }
+
+    #[test]
+    fn self_overlapping_trail_resolves_by_depth_not_draw_order() {
+        let (width, height) = (100, 100);
+        let renderer = Renderer::new(width, height);
+        let camera = Camera::new(
+            Vec3::zeros(),
+            Vec3::new(0.0, 0.0, -1.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        );
+        let uniforms = renderer.uniforms(&camera, Mat4::identity(), 0.0, vec![]);
+        let (red, blue) = (Color::new(255, 0, 0), Color::new(0, 0, 255));
+        // El degradado va de rojo a azul: el primer segmento es rojizo y el
+        // último, azulado
+        let style = |ribbon| TrailStyle {
+            color_start: red,
+            color_end: blue,
+            thickness: 3.0,
+            reference_distance: 16.0,
+            antialiased: false,
+            ribbon,
+        };
+        let center = project_to_screen(Vec3::new(0.0, 0.0, -10.0), &uniforms)
+            .unwrap()
+            .0
+            .xy();
+        let center = center.y.round() as usize * width + center.x.round() as usize;
+
+        // La estela vuelve sobre sí misma: el primer segmento es horizontal a
+        // profundidad `first` y el último lo cruza en vertical a `last`
+        let crossing_color = |first: f32, last: f32, ribbon: bool| {
+            let mut trail = PlanetTrail::new(4);
+            for position in [
+                Vec3::new(-0.2 * first, 0.0, -first),
+                Vec3::new(0.2 * first, 0.0, -first),
+                Vec3::new(0.0, 0.2 * last, -last),
+                Vec3::new(0.0, -0.2 * last, -last),
+            ] {
+                trail.add_position(position);
+            }
+            let mut framebuffer = Framebuffer::new(width, height);
+            framebuffer.clear();
+            render_trail(&mut framebuffer, &uniforms, &trail, &style(ribbon));
+            Color::from_hex(framebuffer.buffer[center])
+        };
+
+        for ribbon in [false, true] {
+            // El segmento dibujado al final queda detrás: se ve el primero
+            let color = crossing_color(8.0, 16.0, ribbon);
+            assert!(color.r > color.b, "{} {:06x}", ribbon, color.to_hex());
+            // Y si queda delante, se ve el último
+            let color = crossing_color(16.0, 8.0, ribbon);
+            assert!(color.b > color.r, "{} {:06x}", ribbon, color.to_hex());
+        }
+    }
+
+    #[test]
+    fn lines_are_clipped_to_the_screen_and_hidden_behind_bodies() {
+        let (width, height) = (100, 100);
+        let mut framebuffer = Framebuffer::new(width, height);
+        framebuffer.clear();
+        let renderer = Renderer::new(width, height);
+        let camera = Camera::new(
+            Vec3::zeros(),
+            Vec3::new(0.0, 0.0, -1.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        );
+        let uniforms = renderer.uniforms(
+            &camera,
+            create_model_matrix(Vec3::new(0.0, 0.0, -10.0), 1.0, Vec3::zeros()),
+            0.0,
+            vec![],
+        );
+        render_indexed(
+            &mut framebuffer,
+            &uniforms,
+            &mesh::uv_sphere(16, 32),
+            white_shader,
+        );
+
+        // Una línea mucho más larga que la pantalla, detrás de la esfera
+        let red = Color::new(255, 0, 0);
+        render_line(
+            &mut framebuffer,
+            &uniforms,
+            Vec3::new(-1000.0, 0.0, -12.0),
+            Vec3::new(1000.0, 0.0, -12.0),
+            red,
+            1,
+        );
+        let center = project_to_screen(Vec3::new(0.0, 0.0, -12.0), &uniforms)
+            .unwrap()
+            .0
+            .xy();
+        let row = center.y.round() as usize * width;
+        let red_in_row = |columns: std::ops::Range<usize>| {
+            columns
+                .filter(|&x| framebuffer.buffer[row + x] == red.to_hex())
+                .count()
+        };
+        // Cruza de borde a borde sin doblarse, salvo donde la tapa la esfera
+        assert_eq!(red_in_row(0..10), 10);
+        assert_eq!(red_in_row(width - 10..width), 10);
+        let sphere_edge = project_to_screen(Vec3::new(0.9, 0.0, -10.0), &uniforms)
+            .unwrap()
+            .0
+            .xy();
+        let center_x = center.x as usize;
+        let covered = center_x - (sphere_edge.x - center.x) as usize..center_x + 1;
+        assert_eq!(red_in_row(covered), 0);
+
+        // La parte detrás de la cámara se recorta
+        framebuffer.clear();
+        render_line(
+            &mut framebuffer,
+            &uniforms,
+            Vec3::new(0.0, -1.0, 5.0),
+            Vec3::new(0.0, -1.0, -50.0),
+            red,
+            1,
+        );
+        let drawn = framebuffer
+            .buffer
+            .iter()
+            .filter(|&&pixel| pixel == red.to_hex())
+            .count();
+        assert!(drawn > 0 && drawn < width * height / 10);
+    }
+
+    #[test]
+    fn trails_and_vertices_project_to_the_same_pixel() {
+        let (width, height) = (64, 48);
+        let renderer = Renderer::new(width, height);
+        let camera = Camera::new(
+            Vec3::new(2.0, 3.0, 10.0),
+            Vec3::zeros(),
+            Vec3::new(0.0, 1.0, 0.0),
+        );
+        let point = Vec3::new(1.5, -0.5, 2.0);
+        // El vértice está en el origen del modelo, trasladado al punto
+        let uniforms = renderer.uniforms(&camera, nalgebra_glm::translation(&point), 0.0, vec![]);
+
+        let (screen, w) = project_to_screen(point, &uniforms).unwrap();
+        assert!(w > 0.0);
+        let vertex = vertex_shader(
+            &Vertex::new(Vec3::zeros(), Vec3::z(), Vec2::zeros()),
+            &uniforms,
+        );
+        assert!((vertex.transformed_position - screen).magnitude() < 1e-3);
+
+        // Una estela de un solo píxel cae donde cae el vértice
+        let mut framebuffer = Framebuffer::new(width, height);
+        let mut trail = PlanetTrail::new(2);
+        trail.add_position(point);
+        trail.add_position(point);
+        let red = Color::new(255, 0, 0);
+        let style = TrailStyle {
+            color_start: red,
+            color_end: red,
+            thickness: 1.0,
+            reference_distance: 1.0,
+            antialiased: false,
+            ribbon: false,
+        };
+        render_trail(&mut framebuffer, &uniforms, &trail, &style);
+        let (x, y) = (
+            vertex.transformed_position.x.round() as usize,
+            vertex.transformed_position.y.round() as usize,
+        );
+        assert_eq!(framebuffer.buffer[y * width + x], red.to_hex());
+
+        // Detrás de la cámara no hay proyección
+        assert!(project_to_screen(Vec3::new(2.0, 3.0, 20.0), &uniforms).is_none());
+    }
+}
@@ -0,0 +1,187 @@
+// scene_dump.rs
+// Guarda el estado de la escena en ejecución (cámara, ajustes globales y cada
+// cuerpo con sus ruidos) como TOML, para conservar o compartir lo ajustado en vivo.
+// Se escribe a mano porque el formato es pequeño y plano.
+
+use crate::camera::Camera;
+use crate::color::Color;
+use crate::planet::Planet;
+use fastnoise_lite::FastNoiseLite;
+use nalgebra_glm::Vec3;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Carpeta donde se guardan las escenas
+pub const SCENE_DIR: &str = "scenes";
+
+// Un cuerpo con sus ruidos y, si tiene, el parámetro de su shader ajustado en vivo
+pub struct BodyState<'a> {
+    pub planet: &'a Planet,
+    pub noises: &'a [FastNoiseLite],
+    pub tweak: Option<(&'a str, f32)>,
+}
+
+pub struct SceneState<'a> {
+    pub camera: &'a Camera,
+    pub time: f32,
+    // 1 hacia adelante, -1 en reversa
+    pub direction: f32,
+    pub ambient: f32,
+    // Nombre del preset de escena y semilla maestra (None = ajustadas a mano)
+    pub preset: &'a str,
+    pub seed: Option<u64>,
+    // Otros ajustes globales, como pares nombre = valor ya formateados en TOML
+    pub settings: Vec<(&'a str, String)>,
+    pub bodies: Vec<BodyState<'a>>,
+}
+
+impl SceneState<'_> {
+    pub fn to_toml(&self) -> String {
+        let mut out = String::new();
+        // Escribir en un String no falla
+        let _ = self.write_toml(&mut out);
+        out
+    }
+
+    fn write_toml(&self, out: &mut String) -> std::fmt::Result {
+        writeln!(out, "# Escena guardada en ejecución")?;
+        writeln!(out, "preset = {}", quote(self.preset))?;
+        if let Some(seed) = self.seed {
+            writeln!(out, "seed = {}", seed)?;
+        }
+        writeln!(out, "time = {}", self.time)?;
+        writeln!(out, "direction = {}", self.direction)?;
+        writeln!(out, "ambient = {}", self.ambient)?;
+        for (name, value) in &self.settings {
+            writeln!(out, "{} = {}", name, value)?;
+        }
+
+        writeln!(out, "\n[camera]")?;
+        writeln!(out, "eye = {}", vector(self.camera.eye))?;
+        writeln!(out, "center = {}", vector(self.camera.center))?;
+        writeln!(out, "up = {}", vector(self.camera.up))?;
+        if let Some(distance) = self.camera.track_distance {
+            writeln!(out, "track_distance = {}", distance)?;
+        }
+
+        for body in &self.bodies {
+            let planet = body.planet;
+            writeln!(out, "\n[[bodies]]")?;
+            writeln!(out, "name = {}", quote(&planet.name))?;
+            writeln!(out, "scale = {}", planet.scale)?;
+            writeln!(out, "orbit_radius = {}", planet.orbit_radius)?;
+            writeln!(out, "orbit_speed = {}", planet.orbit_speed)?;
+            writeln!(out, "phase_offset = {}", planet.phase_offset)?;
+            writeln!(out, "spin_speed = {}", planet.spin_speed)?;
+            writeln!(out, "retrograde_orbit = {}", planet.retrograde_orbit)?;
+            writeln!(out, "retrograde_spin = {}", planet.retrograde_spin)?;
+            writeln!(out, "albedo = {}", color(planet.albedo))?;
+            if planet.emissive_strength > 0.0 {
+                writeln!(out, "emissive = {}", color(planet.emissive))?;
+                writeln!(out, "emissive_strength = {}", planet.emissive_strength)?;
+            }
+            if let Some(model) = &planet.model {
+                writeln!(out, "model = {}", quote(model))?;
+            }
+            if let Some((name, value)) = body.tweak {
+                writeln!(
+                    out,
+                    "tweak = {{ name = {}, value = {} }}",
+                    quote(name),
+                    value
+                )?;
+            }
+
+            for noise in body.noises {
+                writeln!(out, "\n[[bodies.noises]]")?;
+                writeln!(out, "seed = {}", noise.seed)?;
+                writeln!(out, "frequency = {}", noise.frequency)?;
+                writeln!(
+                    out,
+                    "noise_type = {}",
+                    quote(&format!("{:?}", noise.noise_type))
+                )?;
+                writeln!(
+                    out,
+                    "fractal_type = {}",
+                    quote(&format!("{:?}", noise.fractal_type))
+                )?;
+                writeln!(out, "octaves = {}", noise.octaves)?;
+                writeln!(out, "lacunarity = {}", noise.lacunarity)?;
+                writeln!(out, "gain = {}", noise.gain)?;
+            }
+        }
+        Ok(())
+    }
+
+    // Escribe la escena en un archivo nuevo de `SCENE_DIR` y devuelve su ruta
+    pub fn save(&self) -> std::io::Result<PathBuf> {
+        let path = next_scene_path()?;
+        fs::write(&path, self.to_toml())?;
+        Ok(path)
+    }
+}
+
+// Ruta nueva dentro de `SCENE_DIR` (que se crea si falta), con la hora en
+// milisegundos para no pisar escenas anteriores
+pub fn next_scene_path() -> std::io::Result<PathBuf> {
+    fs::create_dir_all(SCENE_DIR)?;
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis())
+        .unwrap_or(0);
+    Ok(Path::new(SCENE_DIR).join(format!("escena_{}.toml", millis)))
+}
+
+// Cadena TOML básica, escapando comillas y barras
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn vector(v: Vec3) -> String {
+    format!("[{}, {}, {}]", v.x, v.y, v.z)
+}
+
+fn color(color: Color) -> String {
+    format!("[{}, {}, {}]", color.r, color.g, color.b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scene_state_is_written_as_toml_tables() {
+        let camera = Camera::new(
+            Vec3::new(0.0, 10.0, 100.0),
+            Vec3::zeros(),
+            Vec3::new(0.0, 1.0, 0.0),
+        );
+        let planet = Planet::new("Tierra \"azul\"", 1.2, 12.0, 0.01, 100);
+        let noises = [FastNoiseLite::with_seed(42)];
+        let state = SceneState {
+            camera: &camera,
+            time: 1500.0,
+            direction: -1.0,
+            ambient: 0.1,
+            preset: "solar",
+            seed: Some(7),
+            settings: vec![("glow", "true".to_string())],
+            bodies: vec![BodyState {
+                planet: &planet,
+                noises: &noises,
+                tweak: Some(("clouds", 0.5)),
+            }],
+        };
+
+        let toml = state.to_toml();
+        assert!(toml.contains("seed = 7\ntime = 1500\ndirection = -1\n"));
+        assert!(toml.contains("glow = true\n"));
+        assert!(toml.contains("[camera]\neye = [0, 10, 100]\n"));
+        assert!(toml.contains("[[bodies]]\nname = \"Tierra \\\"azul\\\"\"\nscale = 1.2\n"));
+        assert!(toml.contains("tweak = { name = \"clouds\", value = 0.5 }\n"));
+        assert!(toml.contains("[[bodies.noises]]\nseed = 42\n"));
+    }
+}
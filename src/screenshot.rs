@@ -0,0 +1,108 @@
+// screenshot.rs
+// Capturas en PNG del framebuffer. La captura en alta resolución se dibuja en un
+// framebuffer propio, así su tamaño no depende del de la ventana.
+
+use crate::Framebuffer;
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Carpeta donde se guardan las capturas
+pub const SCREENSHOT_DIR: &str = "screenshots";
+// Lado mayor de la captura; el otro sigue la proporción de la ventana para que
+// el encuadre sea el mismo que en pantalla
+pub const CAPTURE_LONG_SIDE: usize = 4000;
+
+// Tamaño de la captura para una ventana de `width` x `height`
+pub fn capture_size(width: usize, height: usize) -> (usize, usize) {
+    let long_side = width.max(height).max(1) as f32;
+    let scale = CAPTURE_LONG_SIDE as f32 / long_side;
+    (
+        ((width as f32 * scale).round() as usize).max(1),
+        ((height as f32 * scale).round() as usize).max(1),
+    )
+}
+
+// Guarda el color del framebuffer como PNG de 8 bits: RGB, o RGBA con `transparent`,
+// opaco donde hay geometría y transparente en el fondo (ver `Framebuffer::coverage`)
+// para componer los planetas sobre otras imágenes
+pub fn save_png(
+    framebuffer: &Framebuffer,
+    path: &Path,
+    transparent: bool,
+) -> Result<(), png::EncodingError> {
+    let file = File::create(path)?;
+    let mut encoder = png::Encoder::new(
+        BufWriter::new(file),
+        framebuffer.width as u32,
+        framebuffer.height as u32,
+    );
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let rgb = |pixel: u32| [(pixel >> 16) as u8, (pixel >> 8) as u8, pixel as u8];
+    let data: Vec<u8> = if transparent {
+        encoder.set_color(png::ColorType::Rgba);
+        framebuffer
+            .buffer
+            .iter()
+            .zip(framebuffer.coverage())
+            .flat_map(|(&pixel, covered)| {
+                let [r, g, b] = rgb(pixel);
+                [r, g, b, if covered { 255 } else { 0 }]
+            })
+            .collect()
+    } else {
+        encoder.set_color(png::ColorType::Rgb);
+        framebuffer
+            .buffer
+            .iter()
+            .flat_map(|&pixel| rgb(pixel))
+            .collect()
+    };
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&data)
+}
+
+// Ruta nueva dentro de `SCREENSHOT_DIR` (que se crea si falta), con la hora en
+// milisegundos para no pisar capturas anteriores
+pub fn next_screenshot_path() -> std::io::Result<PathBuf> {
+    fs::create_dir_all(SCREENSHOT_DIR)?;
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis())
+        .unwrap_or(0);
+    Ok(Path::new(SCREENSHOT_DIR).join(format!("captura_{}.png", millis)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capture_size_keeps_window_aspect_ratio() {
+        assert_eq!(capture_size(800, 800), (4000, 4000));
+        assert_eq!(capture_size(1280, 720), (4000, 2250));
+        assert_eq!(capture_size(600, 1200), (2000, 4000));
+    }
+
+    #[test]
+    fn transparent_png_keeps_only_drawn_pixels_opaque() {
+        let mut framebuffer = Framebuffer::new(2, 1);
+        framebuffer.set_background_color(0x102030);
+        framebuffer.clear();
+        framebuffer.set_current_color(0xff8000);
+        framebuffer.point(1, 0, 0.5);
+
+        let path = std::env::temp_dir().join(format!("captura_{}.png", std::process::id()));
+        save_png(&framebuffer, &path, true).unwrap();
+        let decoder = png::Decoder::new(File::open(&path).unwrap());
+        let mut reader = decoder.read_info().unwrap();
+        let mut data = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut data).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(info.color_type, png::ColorType::Rgba);
+        assert_eq!(&data[..8], &[0x10, 0x20, 0x30, 0, 0xff, 0x80, 0x00, 255]);
+    }
+}
@@ -0,0 +1,58 @@
+// Semilla maestra de la escena: deriva de forma determinista las semillas de
+// ruido de cada cuerpo, para generar sistemas distintos pero reproducibles.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum SceneSeed {
+    // Las semillas ajustadas a mano originales de cada planeta
+    #[default]
+    HandTuned,
+    Master(u64),
+}
+
+impl SceneSeed {
+    // Semilla para la capa `layer` del cuerpo `body`; con `HandTuned` devuelve `preset`
+    pub fn derive(&self, body: &str, layer: u32, preset: i32) -> i32 {
+        match self {
+            SceneSeed::HandTuned => preset,
+            SceneSeed::Master(master) => {
+                let hash = splitmix64(master ^ fnv1a(body) ^ (layer as u64).rotate_left(32));
+                (hash >> 33) as i32
+            }
+        }
+    }
+
+    // Semilla para generadores aleatorios (p. ej. las estrellas); None conserva el azar original
+    pub fn rng_seed(&self, name: &str) -> Option<u64> {
+        match self {
+            SceneSeed::HandTuned => None,
+            SceneSeed::Master(master) => Some(splitmix64(master ^ fnv1a(name))),
+        }
+    }
+
+    // Ángulo inicial de la órbita del cuerpo `body`, en [0, 2π). Sin semilla maestra
+    // sale solo del nombre, así la disposición inicial es siempre la misma.
+    pub fn phase(&self, body: &str) -> f32 {
+        let master = match self {
+            SceneSeed::HandTuned => 0,
+            SceneSeed::Master(master) => *master,
+        };
+        let hash = splitmix64(master ^ fnv1a(body) ^ fnv1a("phase"));
+        (hash >> 40) as f32 / (1u64 << 24) as f32 * std::f32::consts::TAU
+    }
+}
+
+// Hash estable entre versiones de Rust (a diferencia de DefaultHasher)
+fn fnv1a(text: &str) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in text.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+fn splitmix64(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
@@ -1,24 +1,175 @@
 use crate::color::Color;
 use crate::fragment::Fragment;
+use crate::log_warn;
+use crate::render::{clip_to_screen, FragmentShader};
 use crate::vertex::Vertex;
 use crate::Uniforms;
-use nalgebra_glm::{mat4_to_mat3, Mat3, Vec3, Vec4};
+use fastnoise_lite::FastNoiseLite;
+use nalgebra_glm::{mat4_to_mat3, Mat3, Mat4, Vec3, Vec4};
 use rand::rngs::StdRng;
 use rand::Rng;
 use rand::SeedableRng;
 
+// Parámetros compartidos del océano de la Tierra
+pub const EARTH_LAND_THRESHOLD: f32 = 0.5;
+// Ancho de la transición agua/tierra para que el brillo no se corte en la costa
+pub const EARTH_COASTLINE_BLEND: f32 = 0.02;
+pub const EARTH_SPECULAR_POWER: f32 = 48.0;
+pub const EARTH_SPECULAR_STRENGTH: f32 = 0.6;
+
+// Parámetro de un shader que se puede ajustar en vivo ('+' y '-' del teclado numérico con el
+// planeta enfocado). Se guarda en `uniforms.params` con su nombre; sin valor el
+// shader usa `default`, que coincide con la constante que tenía antes.
+#[derive(Clone, Copy, Debug)]
+pub struct ShaderTweak {
+    pub name: &'static str,
+    pub default: f32,
+    pub step: f32,
+    pub min: f32,
+    pub max: f32,
+}
+
+impl ShaderTweak {
+    const fn new(name: &'static str, default: f32, step: f32, min: f32, max: f32) -> Self {
+        ShaderTweak {
+            name,
+            default,
+            step,
+            min,
+            max,
+        }
+    }
+
+    pub fn value(&self, uniforms: &Uniforms) -> f32 {
+        param(uniforms, self.name, self.default)
+    }
+
+    // Un paso arriba (`steps` = 1) o abajo (-1), dentro de los límites
+    pub fn adjust(&self, value: f32, steps: f32) -> f32 {
+        (value + self.step * steps).clamp(self.min, self.max)
+    }
+}
+
+// Parámetro `name` de `uniforms.params`, o `default` si no está
+pub fn param(uniforms: &Uniforms, name: &str, default: f32) -> f32 {
+    uniforms
+        .params
+        .iter()
+        .find(|(key, _)| *key == name)
+        .map_or(default, |&(_, value)| value)
+}
+
+// Valor de la capa `layer` de los ruidos en un punto. Las capas a partir de
+// `uniforms.noise_layers` no se calculan y valen 0, el valor medio del ruido: los
+// cuerpos que se ven pequeños se sombrean solo con sus capas principales.
+pub fn layer_noise(uniforms: &Uniforms, layer: usize, x: f32, y: f32, z: f32) -> f32 {
+    if uniforms.noise_layers.is_some_and(|layers| layer >= layers) {
+        return 0.0;
+    }
+    uniforms.noises[layer].get_noise_3d(x, y, z)
+}
+
+pub const MERCURY_TWEAK: ShaderTweak = ShaderTweak::new("texture_scale", 10.0, 1.0, 1.0, 40.0);
+pub const VENUS_TWEAK: ShaderTweak = ShaderTweak::new("atmosphere_scale", 0.1, 0.05, 0.0, 2.0);
+pub const EARTH_TWEAK: ShaderTweak =
+    ShaderTweak::new("land_threshold", EARTH_LAND_THRESHOLD, 0.02, 0.0, 1.0);
+pub const MARS_TWEAK: ShaderTweak = ShaderTweak::new("detail_mix", 1.0, 0.1, 0.0, 1.0);
+pub const JUPITER_TWEAK: ShaderTweak = ShaderTweak::new("band_frequency", 1.0, 0.1, 0.1, 5.0);
+pub const SATURN_TWEAK: ShaderTweak = ShaderTweak::new("band_frequency", 1.0, 0.1, 0.1, 5.0);
+pub const URANUS_TWEAK: ShaderTweak = ShaderTweak::new("secondary_mix", 1.0, 0.1, 0.0, 1.0);
+pub const NEPTUNE_TWEAK: ShaderTweak = ShaderTweak::new("atmosphere_mix", 1.0, 0.1, 0.0, 1.0);
+pub const PLUTO_TWEAK: ShaderTweak = ShaderTweak::new("ice_mix", 1.0, 0.1, 0.0, 1.0);
+pub const ERIS_TWEAK: ShaderTweak = ShaderTweak::new("ice_mix", 1.0, 0.1, 0.0, 1.0);
+pub const SEDNA_TWEAK: ShaderTweak = ShaderTweak::new("ice_mix", 1.0, 0.1, 0.0, 1.0);
+
+// Ancho de la penumbra de `shadow_factor`, como fracción del radio de la esfera
+const SHADOW_PENUMBRA: f32 = 0.08;
+
+// Cómo se iluminan los cuerpos. Se elige entre dos preajustes con '0': el
+// estilizado (ambiente plano más difusa, el aspecto original) y el realista. Las
+// luces caen con `diffuse_light`, los brillos se suman en `apply_lighting` y el
+// tono y la gamma se aplican al cuadro en `postprocess::apply_tone_mapping`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LightingSettings {
+    pub name: &'static str,
+    // Distancia a la que una luz llega con intensidad 1; más lejos cae con el
+    // cuadrado de la distancia. None la deja constante.
+    pub falloff_distance: Option<f32>,
+    // Reflejo especular de Blinn-Phong (0 lo apaga) y su exponente
+    pub specular: f32,
+    pub shininess: f32,
+    // Borde atmosférico de Fresnel en el lado iluminado (0 lo apaga), color lineal
+    pub fresnel: f32,
+    pub fresnel_color: Vec3,
+    // Curva de tono fílmica y gamma de salida (1 = sin corrección)
+    pub tone_mapping: bool,
+    pub gamma: f32,
+}
+
+impl LightingSettings {
+    pub const STYLIZED: LightingSettings = LightingSettings {
+        name: "Estilizada",
+        falloff_distance: None,
+        specular: 0.0,
+        shininess: 1.0,
+        fresnel: 0.0,
+        fresnel_color: Vec3::new(0.0, 0.0, 0.0),
+        tone_mapping: false,
+        gamma: 1.0,
+    };
+
+    // La luz llega entera a la órbita de Mercurio y se apaga hacia afuera; la gamma
+    // devuelve detalle a los planetas lejanos
+    pub const REALISTIC: LightingSettings = LightingSettings {
+        name: "Realista",
+        falloff_distance: Some(8.0),
+        specular: 0.25,
+        shininess: 24.0,
+        fresnel: 0.6,
+        fresnel_color: Vec3::new(0.45, 0.65, 1.0),
+        tone_mapping: true,
+        gamma: 2.2,
+    };
+
+    pub fn next(self) -> Self {
+        if self == Self::STYLIZED {
+            Self::REALISTIC
+        } else {
+            Self::STYLIZED
+        }
+    }
+
+    // Atenuación de una luz a `distance` del punto iluminado
+    pub fn attenuation(&self, distance: f32) -> f32 {
+        match self.falloff_distance {
+            Some(reference) => (reference / distance.max(1e-3)).powi(2),
+            None => 1.0,
+        }
+    }
+}
+
+impl Default for LightingSettings {
+    fn default() -> Self {
+        Self::STYLIZED
+    }
+}
+
+// Descarte común a todos los shaders: true si la posición (espacio mundo) queda
+// del lado negativo del plano de recorte de los uniforms
+pub fn is_clipped(world_position: Vec3, uniforms: &Uniforms) -> bool {
+    uniforms
+        .clip_plane
+        .is_some_and(|plane| plane.xyz().dot(&world_position) + plane.w < 0.0)
+}
+
 pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
     // Transform position
     let position = Vec4::new(vertex.position.x, vertex.position.y, vertex.position.z, 1.0);
     let transformed =
         uniforms.projection_matrix * uniforms.view_matrix * uniforms.model_matrix * position;
 
-    // Perform perspective division
-    let w = transformed.w;
-    let ndc_position = Vec4::new(transformed.x / w, transformed.y / w, transformed.z / w, 1.0);
-
-    // apply viewport matrix
-    let screen_position = uniforms.viewport_matrix * ndc_position;
+    // Perspective division and viewport, shared with the trails
+    let screen_position = clip_to_screen(transformed, uniforms);
 
     // Transform normal
     let model_mat3 = mat4_to_mat3(&uniforms.model_matrix);
@@ -28,24 +179,41 @@ pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
         .unwrap_or(Mat3::identity());
 
     let transformed_normal = normal_matrix * vertex.normal;
+    // La tangente está sobre la superficie: se transforma como una dirección
+    let transformed_tangent = model_mat3 * vertex.tangent;
 
     // Create a new Vertex with transformed attributes
     Vertex {
         position: vertex.position,
         normal: vertex.normal,
         tex_coords: vertex.tex_coords,
+        tangent: vertex.tangent,
         color: vertex.color,
-        transformed_position: Vec3::new(screen_position.x, screen_position.y, screen_position.z),
+        transformed_position: screen_position,
         transformed_normal,
+        transformed_tangent,
     }
 }
 
-pub fn fragment_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+// Los shaders de fragmentos devuelven None para descartar el fragmento
+// (no se escribe color ni profundidad)
+pub fn fragment_shader(fragment: &Fragment, uniforms: &Uniforms) -> Option<Color> {
     lava_shader(fragment, uniforms)
 }
 
-fn random_color_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let seed = uniforms.time as u64;
+// Estrella compañera del modo binario: la misma superficie de lava, pero de una
+// estrella más caliente (azul blanquecina)
+pub fn shader_companion_star(fragment: &Fragment, uniforms: &Uniforms) -> Option<Color> {
+    let lava = lava_base_color().lerp(&lava_bright_color(), lava_noise(fragment, uniforms))
+        * fragment.intensity;
+    let brightness = (lava.r as f32 + lava.g as f32 + lava.b as f32) / (3.0 * 255.0);
+    let dark_color = Color::new(40, 70, 180);
+    let bright_color = Color::new(220, 235, 255);
+    Some(dark_color.lerp(&bright_color, (brightness * 2.0).min(1.0)))
+}
+
+fn random_color_shader(fragment: &Fragment, uniforms: &Uniforms) -> Option<Color> {
+    let seed = uniforms.surface_time as u64;
 
     let mut rng = StdRng::seed_from_u64(seed);
 
@@ -55,11 +223,11 @@ fn random_color_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
 
     let random_color = Color::new(r, g, b);
 
-    random_color * fragment.intensity
+    Some(random_color * fragment.intensity)
 }
 
-fn black_and_white(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let seed = uniforms.time as f32 * fragment.vertex_position.y * fragment.vertex_position.x;
+fn black_and_white(fragment: &Fragment, uniforms: &Uniforms) -> Option<Color> {
+    let seed = uniforms.surface_time * fragment.vertex_position.y * fragment.vertex_position.x;
 
     let mut rng = StdRng::seed_from_u64(seed.abs() as u64);
 
@@ -71,10 +239,10 @@ fn black_and_white(fragment: &Fragment, uniforms: &Uniforms) -> Color {
         Color::new(255, 255, 255)
     };
 
-    black_or_white * fragment.intensity
+    Some(black_or_white * fragment.intensity)
 }
 
-fn dalmata_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+fn dalmata_shader(fragment: &Fragment, uniforms: &Uniforms) -> Option<Color> {
     let zoom = 100.0;
     let ox = 0.0;
     let oy = 0.0;
@@ -93,16 +261,16 @@ fn dalmata_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
         base_color
     };
 
-    noise_color * fragment.intensity
+    Some(noise_color * fragment.intensity)
 }
 
-fn cloud_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+fn cloud_shader(fragment: &Fragment, uniforms: &Uniforms) -> Option<Color> {
     let zoom = 100.0; // to move our values
     let ox = 100.0; // offset x in the noise map
     let oy = 100.0;
     let x = fragment.vertex_position.x;
     let y = fragment.vertex_position.y;
-    let t = uniforms.time as f32 * 0.5;
+    let t = uniforms.surface_time * 0.5;
 
     let noise_value = uniforms.noises[0].get_noise_2d(x * zoom + ox + t, y * zoom + oy);
 
@@ -118,10 +286,10 @@ fn cloud_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
         sky_color
     };
 
-    noise_color * fragment.intensity
+    Some(noise_color * fragment.intensity)
 }
 
-fn cellular_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+fn cellular_shader(fragment: &Fragment, uniforms: &Uniforms) -> Option<Color> {
     let zoom = 30.0; // Zoom factor to adjust the scale of the cell pattern
     let ox = 50.0; // Offset x in the noise map
     let oy = 50.0; // Offset y in the noise map
@@ -151,14 +319,31 @@ fn cellular_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     };
 
     // Adjust intensity to simulate lighting effects (optional)
-    final_color * fragment.intensity
+    Some(final_color * fragment.intensity)
+}
+
+// Base rojiza de la lava: el Sol la emite en toda su superficie (ver `lava_shader`)
+pub fn lava_base_color() -> Color {
+    Color::new(130, 20, 0) // Darker red-orange
+}
+
+fn lava_bright_color() -> Color {
+    Color::new(255, 240, 0) // Bright orange (lava-like)
 }
 
-fn lava_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    // Base colors for the lava effect
-    let bright_color = Color::new(255, 240, 0); // Bright orange (lava-like)
-    let dark_color = Color::new(130, 20, 0); // Darker red-orange
+// Superficie del Sol: las manchas brillantes de la lava, oscurecidas hacia el borde
+// con `fragment.intensity`. La base no sale de aquí sino de la emisión del cuerpo
+// (`uniforms.emissive`, con `lava_base_color`), que se suma después sin depender
+// de la luz; juntas dan la misma mezcla de antes en el centro del disco.
+fn lava_shader(fragment: &Fragment, uniforms: &Uniforms) -> Option<Color> {
+    let base = lava_base_color();
+    let bright = lava_bright_color();
+    let spots = Color::new(bright.r - base.r, bright.g - base.g, bright.b - base.b);
+    Some(spots * (lava_noise(fragment, uniforms) * fragment.intensity))
+}
 
+// Patrón de la lava en [0, 1]
+fn lava_noise(fragment: &Fragment, uniforms: &Uniforms) -> f32 {
     // Get fragment position
     let position = Vec3::new(
         fragment.vertex_position.x,
@@ -169,47 +354,244 @@ fn lava_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     // Base frequency and amplitude for the pulsating effect
     let base_frequency = 0.2;
     let pulsate_amplitude = 0.5;
-    let t = uniforms.time as f32 * 0.001;
+    let t = uniforms.surface_time * 0.001;
 
     // Pulsate on the z-axis to change spot size
     let pulsate = (t * base_frequency).sin() * pulsate_amplitude;
 
     // Apply noise to coordinates with subtle pulsating on z-axis
     let zoom = 1000.0; // Constant zoom factor
-    let noise_value1 = uniforms.noises[0].get_noise_3d(
+    let noise_value1 = layer_noise(
+        uniforms,
+        0,
         position.x * zoom,
         position.y * zoom,
         (position.z + pulsate) * zoom,
     );
-    let noise_value2 = uniforms.noises[0].get_noise_3d(
+    let noise_value2 = layer_noise(
+        uniforms,
+        0,
         (position.x + 1000.0) * zoom,
         (position.y + 1000.0) * zoom,
         (position.z + 1000.0 + pulsate) * zoom,
     );
-    let noise_value = (noise_value1 + noise_value2) * 0.5; // Averaging noise for smoother transitions
+    (noise_value1 + noise_value2) * 0.5 // Averaging noise for smoother transitions
+}
+
+// Iluminación común de los cuerpos: la luz difusa (ya con el color de las
+// estrellas) más la luz ambiental global (`uniforms.ambient`), teñida por el
+// color ambiental del cuerpo, y los brillos del preajuste (`apply_highlights`).
+// Los shaders sin luz (el color plano, los canales de ruido) no pasan por aquí.
+pub fn apply_lighting(
+    base_color: Color,
+    diffuse: Color,
+    normal: Vec3,
+    world_position: Vec3,
+    uniforms: &Uniforms,
+) -> Color {
+    let lit = base_color.blend_multiply(&diffuse)
+        + base_color.blend_multiply(&uniforms.ambient_tint) * uniforms.ambient;
+    apply_highlights(lit, normal, world_position, uniforms)
+}
+
+// Igual que `apply_lighting`, para un albedo lineal (de `Texture::sample_linear`).
+// La luz se aplica en espacio lineal y el resultado se vuelve a codificar en sRGB
+// al final, así un texel gris medio bajo luz plena conserva su valor.
+pub fn apply_lighting_linear(albedo: Vec3, diffuse: Color, uniforms: &Uniforms) -> Color {
+    let to_vec = |color: Color| Vec3::new(color.r as f32, color.g as f32, color.b as f32) / 255.0;
+    let light = to_vec(diffuse) + to_vec(uniforms.ambient_tint) * uniforms.ambient;
+    Color::from_linear(albedo.component_mul(&light))
+}
+
+// Fracción (0..1) de la luz directa que llega a un punto del mundo según la
+// esfera de `uniforms.shadow_caster`, con un borde suave de penumbra
+pub fn shadow_factor(world_position: Vec3, uniforms: &Uniforms) -> f32 {
+    let Some(caster) = uniforms.shadow_caster else {
+        return 1.0;
+    };
+    let to_light = (caster.light_position - world_position).normalize();
+    let to_center = caster.center - world_position;
+    let along = to_center.dot(&to_light);
+    if along <= 0.0 {
+        return 1.0; // La esfera queda del lado opuesto a la luz
+    }
+    let distance = (to_center - to_light * along).magnitude();
+    smoothstep(
+        caster.radius * (1.0 - SHADOW_PENUMBRA),
+        caster.radius * (1.0 + SHADOW_PENUMBRA),
+        distance,
+    )
+}
 
-    // Use lerp for color blending based on noise value
-    let color = dark_color.lerp(&bright_color, noise_value);
+// Posición de un punto del modelo en espacio mundo
+pub fn to_world(position: Vec3, uniforms: &Uniforms) -> Vec3 {
+    (uniforms.model_matrix * Vec4::new(position.x, position.y, position.z, 1.0)).xyz()
+}
+
+// Dirección hacia la luz principal (el Sol); sin luces, hacia +Z
+pub fn light_direction(world_position: Vec3, uniforms: &Uniforms) -> Vec3 {
+    match uniforms.lights.first() {
+        Some(light) => (light.position - world_position).normalize(),
+        None => Vec3::new(0.0, 0.0, 1.0),
+    }
+}
+
+// Luz difusa de Lambert sumada sobre todas las luces, cada una con su color.
+// Donde dos luces iluminan el mismo hemisferio los colores se suman canal por
+// canal (y se saturan en blanco), así cada terminador conserva el tono de su estrella.
+pub fn diffuse_light(normal: Vec3, world_position: Vec3, uniforms: &Uniforms) -> Color {
+    if uniforms.lights.is_empty() {
+        let intensity = normal
+            .dot(&light_direction(world_position, uniforms))
+            .max(0.0);
+        return Color::white() * intensity;
+    }
+    let mut total = Vec3::zeros();
+    for light in &uniforms.lights {
+        let to_light = light.position - world_position;
+        let intensity = normal.dot(&to_light.normalize()).max(0.0)
+            * uniforms.lighting.attenuation(to_light.magnitude());
+        total += Vec3::new(
+            light.color.r as f32,
+            light.color.g as f32,
+            light.color.b as f32,
+        ) * (intensity / 255.0);
+    }
+    Color::from_float(total.x, total.y, total.z)
+}
+
+// Brillo especular y borde de Fresnel según `uniforms.lighting`, sumados al color
+// ya sombreado de un fragmento. Solo donde llega la luz, así el Sol (con la luz
+// en su centro) y los lados nocturnos no cambian. Con el preajuste estilizado
+// devuelve el color tal cual.
+pub fn apply_highlights(
+    color: Color,
+    normal: Vec3,
+    world_position: Vec3,
+    uniforms: &Uniforms,
+) -> Color {
+    let lighting = &uniforms.lighting;
+    if lighting.specular <= 0.0 && lighting.fresnel <= 0.0 {
+        return color;
+    }
+    let normal = normal.normalize();
+    let to_eye = (camera_position(&uniforms.view_matrix) - world_position).normalize();
+    let rim = (1.0 - normal.dot(&to_eye).max(0.0)).powi(5) * lighting.fresnel;
+
+    let mut added = Vec3::zeros();
+    let lights = uniforms
+        .lights
+        .iter()
+        .map(|light| (light.position, light.color));
+    let fallback = (world_position + Vec3::new(0.0, 0.0, 1.0), Color::white());
+    for (position, light_color) in lights.chain(uniforms.lights.is_empty().then_some(fallback)) {
+        let to_light = position - world_position;
+        let direction = to_light.normalize();
+        let facing = normal.dot(&direction);
+        if facing <= 0.0 {
+            continue;
+        }
+        let strength = lighting.attenuation(to_light.magnitude());
+        let light = Vec3::new(
+            light_color.r as f32,
+            light_color.g as f32,
+            light_color.b as f32,
+        ) / 255.0
+            * strength;
+        let half = (direction + to_eye).normalize();
+        let specular = normal.dot(&half).max(0.0).powf(lighting.shininess) * lighting.specular;
+        added += light * specular;
+        added += light.component_mul(&lighting.fresnel_color) * rim * facing.sqrt();
+    }
+    color + Color::from_float(added.x, added.y, added.z)
+}
+
+// Normal interpolada perturbada por `uniforms.normal_map`, en espacio mundo. Sin
+// mapa, o sin tangente (mallas sin UV), es la normal interpolada tal cual.
+pub fn mapped_normal(fragment: &Fragment, uniforms: &Uniforms) -> Vec3 {
+    let normal = fragment.normal.normalize();
+    let Some(normal_map) = uniforms.normal_map else {
+        return normal;
+    };
+    // Gram-Schmidt: la tangente interpolada deja de ser perpendicular a la normal
+    let tangent = fragment.tangent - normal * normal.dot(&fragment.tangent);
+    if tangent.magnitude() < 1e-6 {
+        return normal;
+    }
+    let tangent = tangent.normalize();
+    let bitangent = normal.cross(&tangent);
 
-    color * fragment.intensity
+    let sample = normal_map.sample_normal(fragment.tex_coords);
+    (tangent * sample.x + bitangent * sample.y + normal * sample.z).normalize()
 }
 
-pub fn shader_earth(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+// Roca gris iluminada con la normal del mapa: muestra solo el relieve
+pub fn shader_normal_mapped(fragment: &Fragment, uniforms: &Uniforms) -> Option<Color> {
+    let normal = mapped_normal(fragment, uniforms);
+    let world_position = to_world(fragment.vertex_position, uniforms);
+    let diffuse = diffuse_light(normal, world_position, uniforms);
+    Some(
+        apply_lighting(
+            Color::new(170, 165, 160),
+            diffuse,
+            normal,
+            world_position,
+            uniforms,
+        )
+        .clamp(),
+    )
+}
+
+// Color plano del cuerpo (`albedo`), sin iluminación ni ruido
+pub fn shader_flat(_fragment: &Fragment, uniforms: &Uniforms) -> Option<Color> {
+    Some(uniforms.albedo)
+}
+
+// Un canal de ruido crudo en escala de grises (-1 negro, 1 blanco), sin luz ni
+// mapa de color, muestreado donde lo muestrean los shaders de los planetas.
+// Un canal que el cuerpo no tiene se dibuja negro.
+pub fn shader_noise_channel(fragment: &Fragment, uniforms: &Uniforms, channel: usize) -> Color {
+    let Some(noise) = uniforms.noises.get(channel) else {
+        return Color::black();
+    };
+    let position = fragment.vertex_position;
+    let value = noise.get_noise_3d(position.x, position.y, position.z);
+    let gray = ((value * 0.5 + 0.5).clamp(0.0, 1.0) * 255.0).round() as u8;
+    Color::new(gray, gray, gray)
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+// Posición de la cámara en espacio mundo a partir de la matriz de vista (rotación + traslación)
+pub(crate) fn camera_position(view_matrix: &Mat4) -> Vec3 {
+    let rotation = mat4_to_mat3(view_matrix);
+    let translation = Vec3::new(
+        view_matrix[(0, 3)],
+        view_matrix[(1, 3)],
+        view_matrix[(2, 3)],
+    );
+    -(rotation.transpose() * translation)
+}
+
+pub fn shader_earth(fragment: &Fragment, uniforms: &Uniforms) -> Option<Color> {
     // Posición y normal del fragmento
     let position = fragment.vertex_position;
     let normal = fragment.normal.normalize();
 
     // Iluminación
-    let light_pos = Vec3::new(0.0, 0.0, 20.0);
-    let light_dir = (light_pos - position).normalize();
-    let diffuse_intensity = normal.dot(&light_dir).max(0.0);
+    let world_position = to_world(position, uniforms);
+    let light_dir = light_direction(world_position, uniforms);
+    let diffuse = diffuse_light(normal, world_position, uniforms);
 
     // Variable de tiempo para animación
-    let time = uniforms.time * 0.0001;
+    let time = uniforms.surface_time * 0.0001;
 
     // Parámetros de umbral
-    let land_threshold = 0.5;
-    let cloud_threshold = 0.7;
+    let land_threshold = EARTH_TWEAK.value(uniforms);
+    let cloud_threshold = param(uniforms, "cloud_threshold", 0.7);
 
     // Colores base
     let water_color = Color::from_float(0.0, 0.1, 0.4); // Color del agua
@@ -220,30 +602,29 @@ pub fn shader_earth(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     let atmosphere_color = Color::from_float(0.0, 0.4, 0.8); // Azul de la atmósfera
 
     // Velocidades de movimiento
-    let land_speed = 0.01;
-    let cloud_speed = 0.03;
-
-    // Obtener referencias a los ruidos
-    let mountain_noise = uniforms.noises[0];
-    let hill_noise = uniforms.noises[1];
-    let detail_noise = uniforms.noises[2];
-    let cloud_noise = uniforms.noises[3];
-    let atmosphere_noise = uniforms.noises[4];
+    let land_speed = param(uniforms, "land_speed", 0.01);
+    let cloud_speed = param(uniforms, "cloud_speed", 0.03);
 
     // Ruido combinado para el terreno
-    let mountain_value = mountain_noise.get_noise_3d(
+    let mountain_value = layer_noise(
+        uniforms,
+        0,
         position.x * 0.5 + time * land_speed,
         position.y * 0.5 + time * land_speed,
         position.z * 0.5 + time * land_speed,
     );
 
-    let hill_value = hill_noise.get_noise_3d(
+    let hill_value = layer_noise(
+        uniforms,
+        1,
         position.x + time * land_speed,
         position.y + time * land_speed,
         position.z + time * land_speed,
     );
 
-    let detail_value = detail_noise.get_noise_3d(
+    let detail_value = layer_noise(
+        uniforms,
+        2,
         position.x * 2.0 + time * land_speed,
         position.y * 2.0 + time * land_speed,
         position.z * 2.0 + time * land_speed,
@@ -274,7 +655,9 @@ pub fn shader_earth(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     };
 
     // Ruido para las nubes
-    let cloud_noise_value = cloud_noise.get_noise_3d(
+    let cloud_noise_value = layer_noise(
+        uniforms,
+        3,
         position.x + time * cloud_speed,
         position.y + time * cloud_speed,
         position.z + time * cloud_speed,
@@ -289,10 +672,32 @@ pub fn shader_earth(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     base_color = base_color.lerp(&cloud_color, cloud_opacity);
 
     // Aplicar iluminación al color base (antes de agregar la atmósfera)
-    let lit_color = base_color * diffuse_intensity;
-    let ambient_intensity = 0.3;
-    let ambient_color = base_color * ambient_intensity;
-    let mut final_color = ambient_color + lit_color;
+    let mut final_color = apply_lighting(base_color, diffuse, normal, world_position, uniforms);
+
+    // Reflejo especular solo sobre el agua (y no bajo las nubes). La máscara usa el
+    // mismo ruido de terreno, así que el brillo recorre los océanos al girar el planeta.
+    let water_mask = 1.0
+        - smoothstep(
+            land_threshold - EARTH_COASTLINE_BLEND,
+            land_threshold,
+            terrain_normalized,
+        );
+    let view_dir = (camera_position(&uniforms.view_matrix) - world_position).normalize();
+    let reflect_dir = (normal * 2.0 * normal.dot(&light_dir) - light_dir).normalize();
+    let specular = reflect_dir
+        .dot(&view_dir)
+        .max(0.0)
+        .powf(EARTH_SPECULAR_POWER)
+        * EARTH_SPECULAR_STRENGTH
+        * water_mask
+        * (1.0 - cloud_opacity);
+    if normal.dot(&light_dir) > 0.0 {
+        let light_color = uniforms
+            .lights
+            .first()
+            .map_or(Color::white(), |light| light.color);
+        final_color = final_color + light_color * specular;
+    }
 
     // Calcular el efecto de la atmósfera
     let atmosphere_radius = 1.05; // Radio de la atmósfera (un poco más grande que el radio de la Tierra)
@@ -301,7 +706,9 @@ pub fn shader_earth(fragment: &Fragment, uniforms: &Uniforms) -> Color {
         ((distance_from_center - 1.0) / (atmosphere_radius - 1.0)).clamp(0.0, 1.0);
 
     // Obtener el valor de ruido para la atmósfera
-    let atmosphere_noise_value = atmosphere_noise.get_noise_3d(
+    let atmosphere_noise_value = layer_noise(
+        uniforms,
+        4,
         position.x * 10.0 + time * 0.005,
         position.y * 10.0 + time * 0.005,
         position.z * 10.0 + time * 0.005,
@@ -315,20 +722,32 @@ pub fn shader_earth(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     final_color = final_color.lerp(&atmosphere_color, atmosphere_opacity);
 
     // Asegurar que los valores de color estén en el rango válido
-    final_color.clamp()
+    Some(final_color.clamp())
 }
 
-pub fn shader_jupiter(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+pub fn shader_jupiter(fragment: &Fragment, uniforms: &Uniforms) -> Option<Color> {
     let position = fragment.vertex_position;
     let normal = fragment.normal.normalize();
-    let light_pos = Vec3::new(0.0, 0.0, 20.0);
-    let light_dir = (light_pos - position).normalize();
-    let diffuse_intensity = normal.dot(&light_dir).max(0.0);
-
-    let band_noise_value = uniforms.noises[0].get_noise_3d(position.x, position.y, position.z);
-    let high_clouds_noise = uniforms.noises[1].get_noise_3d(position.x, position.y, position.z);
-    let deep_atmospheric_noise =
-        uniforms.noises[2].get_noise_3d(position.x, position.y, position.z);
+    let world_position = to_world(position, uniforms);
+    let diffuse = diffuse_light(normal, world_position, uniforms);
+
+    let band_frequency = JUPITER_TWEAK.value(uniforms);
+    let band_noise_value = layer_noise(
+        uniforms,
+        0,
+        position.x * band_frequency,
+        position.y * band_frequency,
+        position.z * band_frequency,
+    );
+    let cloud_frequency = param(uniforms, "cloud_frequency", 1.0);
+    let high_clouds_noise = layer_noise(
+        uniforms,
+        1,
+        position.x * cloud_frequency,
+        position.y * cloud_frequency,
+        position.z * cloud_frequency,
+    );
+    let deep_atmospheric_noise = layer_noise(uniforms, 2, position.x, position.y, position.z);
 
     let normalized_band_value = (band_noise_value + 1.0) * 0.5;
     let normalized_high_clouds = (high_clouds_noise + 1.0) * 0.5;
@@ -340,31 +759,25 @@ pub fn shader_jupiter(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     let deep_color = Color::from_float(0.5, 0.4, 0.3); // Deeper atmospheric color
 
     let base_color = color1.lerp(&color2, normalized_band_value);
-    let clouds_color = base_color.lerp(&high_clouds_color, normalized_high_clouds);
+    let clouds_color = base_color.lerp(
+        &high_clouds_color,
+        normalized_high_clouds * param(uniforms, "cloud_mix", 1.0),
+    );
     let mut final_color = clouds_color.lerp(&deep_color, normalized_deep_atmos);
 
-    let lit_color = final_color * diffuse_intensity;
-    let ambient_intensity = 0.1;
-    let ambient_color = final_color * ambient_intensity;
-    final_color = ambient_color + lit_color;
+    final_color = apply_lighting(final_color, diffuse, normal, world_position, uniforms);
 
-    final_color.clamp()
+    Some(final_color.clamp())
 }
 
-pub fn shader_moon(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+pub fn shader_moon(fragment: &Fragment, uniforms: &Uniforms) -> Option<Color> {
     // Posición y normal del fragmento
     let position = fragment.vertex_position;
     let normal = fragment.normal.normalize();
 
     // Iluminación
-    let light_pos = Vec3::new(0.0, 0.0, 20.0);
-    let light_dir = (light_pos - position).normalize();
-    let diffuse_intensity = normal.dot(&light_dir).max(0.0);
-
-    // Obtener referencias a los ruidos
-    let noise1 = uniforms.noises[0];
-    let noise2 = uniforms.noises[1];
-    let noise3 = uniforms.noises[2];
+    let world_position = to_world(position, uniforms);
+    let diffuse = diffuse_light(normal, world_position, uniforms);
 
     // Escalar las coordenadas para ajustar el tamaño de las manchas
     let scale_factor_large = 0.5; // Escala para manchas grandes
@@ -372,17 +785,23 @@ pub fn shader_moon(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     let scale_factor_small = 5.0; // Escala para detalles finos
 
     // Obtener los valores de ruido
-    let noise_value1 = noise1.get_noise_3d(
+    let noise_value1 = layer_noise(
+        uniforms,
+        0,
         position.x * scale_factor_large,
         position.y * scale_factor_large,
         position.z * scale_factor_large,
     );
-    let noise_value2 = noise2.get_noise_3d(
+    let noise_value2 = layer_noise(
+        uniforms,
+        1,
         position.x * scale_factor_medium,
         position.y * scale_factor_medium,
         position.z * scale_factor_medium,
     );
-    let noise_value3 = noise3.get_noise_3d(
+    let noise_value3 = layer_noise(
+        uniforms,
+        2,
         position.x * scale_factor_small,
         position.y * scale_factor_small,
         position.z * scale_factor_small,
@@ -401,29 +820,21 @@ pub fn shader_moon(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     // Interpolar entre los colores basado en el valor de ruido
     let base_color = dark_gray.lerp(&light_gray, normalized_value);
 
-    // Aplicar iluminación difusa
-    let lit_color = base_color * diffuse_intensity;
-
-    // Añadir un término ambiental
-    let ambient_intensity = 0.2;
-    let ambient_color = base_color * ambient_intensity;
-
-    // Combinar los componentes ambiental y difuso
-    let final_color = ambient_color + lit_color;
+    // Iluminación difusa más el término ambiental global
+    let final_color = apply_lighting(base_color, diffuse, normal, world_position, uniforms);
 
     // Asegurar que los valores de color estén en el rango válido
-    final_color.clamp()
+    Some(final_color.clamp())
 }
 
-pub fn shader_ring(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+pub fn shader_ring(fragment: &Fragment, uniforms: &Uniforms) -> Option<Color> {
     // Posición y normal del fragmento
     let position = fragment.vertex_position;
     let normal = fragment.normal.normalize();
 
     // Iluminación
-    let light_pos = Vec3::new(0.0, 0.0, 20.0);
-    let light_dir = (light_pos - position).normalize();
-    let diffuse_intensity = normal.dot(&light_dir).max(0.0);
+    let world_position = to_world(position, uniforms);
+    let diffuse = diffuse_light(normal, world_position, uniforms);
 
     // Generar un patrón para el anillo usando coordenadas polares
     let x = position.x;
@@ -442,30 +853,37 @@ pub fn shader_ring(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     // Interpolar entre los colores según el valor de la banda
     let base_color = color1.lerp(&color2, band_value);
 
-    // Aplicar iluminación difusa
-    let lit_color = base_color * diffuse_intensity;
+    // La parte del anillo a la sombra del planeta solo recibe luz ambiental
+    let shadow = shadow_factor(world_position, uniforms);
 
-    // Añadir un término ambiental
-    let ambient_intensity = 0.2;
-    let ambient_color = base_color * ambient_intensity;
-
-    // Combinar los componentes ambiental y difuso
-    let final_color = ambient_color + lit_color;
+    // Iluminación difusa más el término ambiental global
+    let final_color = apply_lighting(
+        base_color,
+        diffuse * shadow,
+        normal,
+        world_position,
+        uniforms,
+    );
 
     // Asegurar que los valores de color estén en el rango válido
-    final_color.clamp()
+    Some(final_color.clamp())
 }
 
-pub fn shader_venus(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+pub fn shader_venus(fragment: &Fragment, uniforms: &Uniforms) -> Option<Color> {
     let position = fragment.vertex_position;
     let normal = fragment.normal.normalize();
-    let light_pos = Vec3::new(0.0, 0.0, 20.0);
-    let light_dir = (light_pos - position).normalize();
-    let diffuse_intensity = normal.dot(&light_dir).max(0.0);
-
-    let surface_noise = uniforms.noises[0].get_noise_3d(position.x, position.y, position.z);
-    let atmosphere_noise =
-        uniforms.noises[1].get_noise_3d(position.x * 0.1, position.y * 0.1, position.z * 0.1);
+    let world_position = to_world(position, uniforms);
+    let diffuse = diffuse_light(normal, world_position, uniforms);
+
+    let atmosphere_scale = VENUS_TWEAK.value(uniforms);
+    let surface_noise = layer_noise(uniforms, 0, position.x, position.y, position.z);
+    let atmosphere_noise = layer_noise(
+        uniforms,
+        1,
+        position.x * atmosphere_scale,
+        position.y * atmosphere_scale,
+        position.z * atmosphere_scale,
+    );
 
     let surface_color = Color::from_float(0.8, 0.4, 0.1); // Deep volcanic orange
     let cloud_color = Color::from_float(0.9, 0.85, 0.7); // Sulphuric clouds
@@ -474,26 +892,33 @@ pub fn shader_venus(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     let mut base_color = surface_color.lerp(&cloud_color, surface_noise.abs());
     base_color = base_color.lerp(&glow_color, atmosphere_noise.abs());
 
-    let lit_color = base_color * diffuse_intensity;
-    let ambient_intensity = 0.2;
-    let ambient_color = base_color * ambient_intensity;
-    let final_color = ambient_color + lit_color;
+    let final_color = apply_lighting(base_color, diffuse, normal, world_position, uniforms);
 
-    final_color.clamp()
+    Some(final_color.clamp())
 }
 
-pub fn shader_mercury(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+pub fn shader_mercury(fragment: &Fragment, uniforms: &Uniforms) -> Option<Color> {
     let position = fragment.vertex_position;
-    let normal = fragment.normal.normalize();
-    let light_pos = Vec3::new(0.0, 0.0, 20.0);
-    let light_dir = (light_pos - position).normalize();
-    let diffuse_intensity = normal.dot(&light_dir).max(0.0);
-
-    let crater_noise = uniforms.noises[0].get_noise_3d(position.x, position.y, position.z);
-    let texture_noise =
-        uniforms.noises[1].get_noise_3d(position.x * 10.0, position.y * 10.0, position.z * 10.0);
-    let undulation_noise =
-        uniforms.noises[2].get_noise_3d(position.x * 0.1, position.y * 0.1, position.z * 0.1);
+    let normal = mapped_normal(fragment, uniforms);
+    let world_position = to_world(position, uniforms);
+    let diffuse = diffuse_light(normal, world_position, uniforms);
+
+    let texture_scale = MERCURY_TWEAK.value(uniforms);
+    let crater_noise = layer_noise(uniforms, 0, position.x, position.y, position.z);
+    let texture_noise = layer_noise(
+        uniforms,
+        1,
+        position.x * texture_scale,
+        position.y * texture_scale,
+        position.z * texture_scale,
+    );
+    let undulation_noise = layer_noise(
+        uniforms,
+        2,
+        position.x * 0.1,
+        position.y * 0.1,
+        position.z * 0.1,
+    );
 
     let base_color = Color::from_float(0.6, 0.5, 0.4); // Basaltic rock
     let dark_crater_color = Color::from_float(0.3, 0.3, 0.3); // Shadow in craters
@@ -503,47 +928,45 @@ pub fn shader_mercury(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     let textured_color = crater_base.lerp(&highlight_color, texture_noise.abs());
     let mut final_color = textured_color.lerp(&base_color, undulation_noise.abs());
 
-    let lit_color = final_color * diffuse_intensity;
-    let ambient_intensity = 0.2;
-    let ambient_color = final_color * ambient_intensity;
-    final_color = ambient_color + lit_color;
+    final_color = apply_lighting(final_color, diffuse, normal, world_position, uniforms);
 
-    final_color.clamp()
+    Some(final_color.clamp())
 }
 
-pub fn shader_mars(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+pub fn shader_mars(fragment: &Fragment, uniforms: &Uniforms) -> Option<Color> {
     let position = fragment.vertex_position;
     let normal = fragment.normal.normalize();
-    let light_pos = Vec3::new(0.0, 0.0, 20.0);
-    let light_dir = (light_pos - position).normalize();
-    let diffuse_intensity = normal.dot(&light_dir).max(0.0);
+    let world_position = to_world(position, uniforms);
+    let diffuse = diffuse_light(normal, world_position, uniforms);
 
-    let surface_value = uniforms.noises[0].get_noise_3d(position.x, position.y, position.z);
-    let detail_value = uniforms.noises[1].get_noise_3d(position.x, position.y, position.z);
-    let atmospheric_value = uniforms.noises[2].get_noise_3d(position.x, position.y, position.z);
+    let surface_value = layer_noise(uniforms, 0, position.x, position.y, position.z);
+    let detail_value = layer_noise(uniforms, 1, position.x, position.y, position.z);
+    let atmospheric_value = layer_noise(uniforms, 2, position.x, position.y, position.z);
 
     let base_color = Color::from_float(1.0, 0.7, 0.5); // Color base para Marte (#ff9966)
     let detail_color = Color::from_float(0.12, 0.09, 0.05); // Detalles más claros
     let atmospheric_color = Color::from_float(0.9, 0.4, 0.3); // Tono atmosférico
 
     let combined_color = base_color
-        .lerp(&detail_color, detail_value.abs())
+        .lerp(
+            &detail_color,
+            detail_value.abs() * MARS_TWEAK.value(uniforms),
+        )
         .lerp(&atmospheric_color, atmospheric_value.abs());
-    let final_color = combined_color * diffuse_intensity;
+    let final_color = apply_lighting(combined_color, diffuse, normal, world_position, uniforms);
 
-    final_color.clamp()
+    Some(final_color.clamp())
 }
 
-pub fn shader_phobos(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+pub fn shader_phobos(fragment: &Fragment, uniforms: &Uniforms) -> Option<Color> {
     let position = fragment.vertex_position;
-    let normal = fragment.normal.normalize();
-    let light_pos = Vec3::new(0.0, 0.0, 20.0);
-    let light_dir = (light_pos - position).normalize();
-    let diffuse_intensity = normal.dot(&light_dir).max(0.0);
+    let normal = mapped_normal(fragment, uniforms);
+    let world_position = to_world(position, uniforms);
+    let diffuse = diffuse_light(normal, world_position, uniforms);
 
-    let crater_noise = uniforms.noises[2].get_noise_3d(position.x, position.y, position.z);
-    let surface_noise = uniforms.noises[1].get_noise_3d(position.x, position.y, position.z);
-    let detail_noise = uniforms.noises[0].get_noise_3d(position.x, position.y, position.z);
+    let crater_noise = layer_noise(uniforms, 2, position.x, position.y, position.z);
+    let surface_noise = layer_noise(uniforms, 1, position.x, position.y, position.z);
+    let detail_noise = layer_noise(uniforms, 0, position.x, position.y, position.z);
 
     let base_color = Color::from_float(0.6, 0.5, 0.4); // Basaltic rock
     let dark_crater_color = Color::from_float(0.3, 0.3, 0.3); // Shadow in craters
@@ -553,20 +976,26 @@ pub fn shader_phobos(fragment: &Fragment, uniforms: &Uniforms) -> Color {
         .lerp(&base_color, crater_noise.abs())
         .lerp(&dark_crater_color, surface_noise.abs())
         .lerp(&highlight_color, detail_noise.abs());
-    let lit_color = final_color * diffuse_intensity;
+    let lit_color = apply_lighting(final_color, diffuse, normal, world_position, uniforms);
 
-    lit_color.clamp()
+    Some(lit_color.clamp())
 }
 
-pub fn shader_saturn(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+pub fn shader_saturn(fragment: &Fragment, uniforms: &Uniforms) -> Option<Color> {
     let position = fragment.vertex_position;
     let normal = fragment.normal.normalize();
-    let light_pos = Vec3::new(0.0, 0.0, 20.0);
-    let light_dir = (light_pos - position).normalize();
-    let diffuse_intensity = normal.dot(&light_dir).max(0.0);
-
-    let band_value = uniforms.noises[0].get_noise_3d(position.x, position.y, position.z);
-    let cloud_value = uniforms.noises[1].get_noise_3d(position.x, position.y, position.z);
+    let world_position = to_world(position, uniforms);
+    let diffuse = diffuse_light(normal, world_position, uniforms);
+
+    let band_frequency = SATURN_TWEAK.value(uniforms);
+    let band_value = layer_noise(
+        uniforms,
+        0,
+        position.x * band_frequency,
+        position.y * band_frequency,
+        position.z * band_frequency,
+    );
+    let cloud_value = layer_noise(uniforms, 1, position.x, position.y, position.z);
 
     let base_color = Color::from_float(0.5, 0.5, 0.5); // Neutral color for Saturn's base
     let band_color = Color::from_float(0.7, 0.7, 0.5); // Slightly yellow for bands
@@ -576,119 +1005,469 @@ pub fn shader_saturn(fragment: &Fragment, uniforms: &Uniforms) -> Color {
         .lerp(&band_color, (band_value + 1.0) * 0.5)
         .lerp(&cloud_color, cloud_value.abs());
 
-    let lit_color = color * diffuse_intensity;
+    let lit_color = apply_lighting(color, diffuse, normal, world_position, uniforms);
 
-    lit_color.clamp()
+    Some(lit_color.clamp())
 }
 
-pub fn shader_uranus(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+pub fn shader_uranus(fragment: &Fragment, uniforms: &Uniforms) -> Option<Color> {
     let position = fragment.vertex_position;
     let normal = fragment.normal.normalize();
-    let light_dir = (Vec3::new(0.0, 0.0, 20.0) - position).normalize();
-    let diffuse_intensity = normal.dot(&light_dir).max(0.0);
+    let world_position = to_world(position, uniforms);
+    let diffuse = diffuse_light(normal, world_position, uniforms);
 
-    let primary_value = uniforms.noises[0].get_noise_3d(position.x, position.y, position.z);
-    let secondary_value = uniforms.noises[1].get_noise_3d(position.x, position.y, position.z);
+    let primary_value = layer_noise(uniforms, 0, position.x, position.y, position.z);
+    let secondary_value = layer_noise(uniforms, 1, position.x, position.y, position.z);
 
     let base_color = Color::from_float(0.4, 0.5, 0.6); // Color base para Urano
     let secondary_color = Color::from_float(0.3, 0.4, 0.5); // Color secundario para dar más profundidad
 
-    let combined_color = base_color.lerp(&secondary_color, secondary_value.abs());
-    let final_color = combined_color * diffuse_intensity;
+    let combined_color = base_color.lerp(
+        &secondary_color,
+        secondary_value.abs() * URANUS_TWEAK.value(uniforms),
+    );
+    let final_color = apply_lighting(combined_color, diffuse, normal, world_position, uniforms);
 
-    final_color.clamp()
+    Some(final_color.clamp())
 }
 
-pub fn shader_uranus_ring(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+pub fn shader_uranus_ring(fragment: &Fragment, uniforms: &Uniforms) -> Option<Color> {
     let position = fragment.vertex_position;
     let normal = fragment.normal.normalize();
-    let light_dir = (Vec3::new(0.0, 0.0, 20.0) - position).normalize();
-    let diffuse_intensity = normal.dot(&light_dir).max(0.0);
+    let world_position = to_world(position, uniforms);
+    let diffuse = diffuse_light(normal, world_position, uniforms);
 
-    let noise1 = uniforms.noises[0].get_noise_3d(position.x, position.y, position.z);
-    let noise2 = uniforms.noises[1].get_noise_3d(position.x, position.y, position.z);
+    let noise1 = layer_noise(uniforms, 0, position.x, position.y, position.z);
+    let noise2 = layer_noise(uniforms, 1, position.x, position.y, position.z);
 
     let base_color = Color::from_float(0.15, 0.15, 0.15); // Muy oscuro para el anillo
     let detail_color = Color::from_float(0.2, 0.2, 0.2); // Ligeramente más claro para detalles
 
     let color_blend = base_color.lerp(&detail_color, (noise1.abs() + noise2.abs()) / 2.0);
-    let final_color = color_blend * diffuse_intensity;
+    let shadow = shadow_factor(world_position, uniforms);
+    let final_color = apply_lighting(
+        color_blend,
+        diffuse * shadow,
+        normal,
+        world_position,
+        uniforms,
+    );
 
-    final_color.clamp()
+    Some(final_color.clamp())
 }
 
-pub fn shader_neptune(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+pub fn shader_neptune(fragment: &Fragment, uniforms: &Uniforms) -> Option<Color> {
     let position = fragment.vertex_position;
     let normal = fragment.normal.normalize();
-    let light_pos = Vec3::new(0.0, 0.0, 20.0);
-    let light_dir = (light_pos - position).normalize();
-    let diffuse_intensity = normal.dot(&light_dir).max(0.0);
+    let world_position = to_world(position, uniforms);
+    let diffuse = diffuse_light(normal, world_position, uniforms);
 
-    let surface_noise = uniforms.noises[0].get_noise_3d(position.x, position.y, position.z);
-    let atmosphere_noise = uniforms.noises[1].get_noise_3d(position.x, position.y, position.z);
+    let surface_noise = layer_noise(uniforms, 0, position.x, position.y, position.z);
+    let atmosphere_noise = layer_noise(uniforms, 1, position.x, position.y, position.z);
 
     let base_color = Color::from_float(0.2, 0.2, 0.6);
     let atmosphere_color = Color::from_float(0.1, 0.1, 0.7);
 
-    let combined_color = base_color.lerp(&atmosphere_color, atmosphere_noise.abs());
-    let final_color = combined_color * diffuse_intensity;
+    let combined_color = base_color.lerp(
+        &atmosphere_color,
+        atmosphere_noise.abs() * NEPTUNE_TWEAK.value(uniforms),
+    );
+    let final_color = apply_lighting(combined_color, diffuse, normal, world_position, uniforms);
 
-    final_color.clamp()
+    Some(final_color.clamp())
 }
 
-pub fn shader_pluto(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+pub fn shader_pluto(fragment: &Fragment, uniforms: &Uniforms) -> Option<Color> {
     let position = fragment.vertex_position;
     let normal = fragment.normal.normalize();
-    let light_pos = Vec3::new(0.0, 0.0, 20.0);
-    let light_dir = (light_pos - position).normalize();
-    let diffuse_intensity = normal.dot(&light_dir).max(0.0);
+    let world_position = to_world(position, uniforms);
+    let diffuse = diffuse_light(normal, world_position, uniforms);
 
-    let surface_noise = uniforms.noises[0].get_noise_3d(position.x, position.y, position.z);
-    let ice_noise = uniforms.noises[1].get_noise_3d(position.x, position.y, position.z);
+    let surface_noise = layer_noise(uniforms, 0, position.x, position.y, position.z);
+    let ice_noise = layer_noise(uniforms, 1, position.x, position.y, position.z);
 
     let base_color = Color::from_float(0.5, 0.5, 0.5);
     let ice_color = Color::from_float(0.8, 0.8, 0.9);
 
-    let combined_color = base_color.lerp(&ice_color, ice_noise.abs());
-    let final_color = combined_color * diffuse_intensity;
+    let combined_color = base_color.lerp(&ice_color, ice_noise.abs() * PLUTO_TWEAK.value(uniforms));
+    let final_color = apply_lighting(combined_color, diffuse, normal, world_position, uniforms);
 
-    final_color.clamp()
+    Some(final_color.clamp())
 }
 
-pub fn shader_eris(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+pub fn shader_eris(fragment: &Fragment, uniforms: &Uniforms) -> Option<Color> {
     let position = fragment.vertex_position;
     let normal = fragment.normal.normalize();
-    let light_pos = Vec3::new(0.0, 0.0, 20.0);
-    let light_dir = (light_pos - position).normalize();
-    let diffuse_intensity = normal.dot(&light_dir).max(0.0);
+    let world_position = to_world(position, uniforms);
+    let diffuse = diffuse_light(normal, world_position, uniforms);
 
-    let surface_noise = uniforms.noises[0].get_noise_3d(position.x, position.y, position.z);
-    let ice_noise = uniforms.noises[1].get_noise_3d(position.x, position.y, position.z);
+    let surface_noise = layer_noise(uniforms, 0, position.x, position.y, position.z);
+    let ice_noise = layer_noise(uniforms, 1, position.x, position.y, position.z);
 
     let base_color = Color::from_float(0.6, 0.5, 0.4);
     let ice_color = Color::from_float(0.7, 0.7, 0.8);
 
-    let combined_color = base_color.lerp(&ice_color, ice_noise.abs());
-    let final_color = combined_color * diffuse_intensity;
+    let combined_color = base_color.lerp(&ice_color, ice_noise.abs() * ERIS_TWEAK.value(uniforms));
+    let final_color = apply_lighting(combined_color, diffuse, normal, world_position, uniforms);
 
-    final_color.clamp()
+    Some(final_color.clamp())
 }
 
-pub fn shader_sedna(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+pub fn shader_sedna(fragment: &Fragment, uniforms: &Uniforms) -> Option<Color> {
     let position = fragment.vertex_position;
     let normal = fragment.normal.normalize();
-    let light_pos = Vec3::new(0.0, 0.0, 20.0);
-    let light_dir = (light_pos - position).normalize();
-    let diffuse_intensity = normal.dot(&light_dir).max(0.0);
+    let world_position = to_world(position, uniforms);
+    let diffuse = diffuse_light(normal, world_position, uniforms);
 
-    let surface_noise = uniforms.noises[0].get_noise_3d(position.x, position.y, position.z);
-    let ice_noise = uniforms.noises[1].get_noise_3d(position.x, position.y, position.z);
+    let surface_noise = layer_noise(uniforms, 0, position.x, position.y, position.z);
+    let ice_noise = layer_noise(uniforms, 1, position.x, position.y, position.z);
 
     let base_color = Color::from_float(0.4, 0.3, 0.3);
     let ice_color = Color::from_float(0.5, 0.5, 0.6);
 
-    let combined_color = base_color.lerp(&ice_color, ice_noise.abs());
-    let final_color = combined_color * diffuse_intensity;
+    let combined_color = base_color.lerp(&ice_color, ice_noise.abs() * SEDNA_TWEAK.value(uniforms));
+    let final_color = apply_lighting(combined_color, diffuse, normal, world_position, uniforms);
+
+    Some(final_color.clamp())
+}
+
+// Shaders de los cuerpos con la cantidad de ruidos que leen de `uniforms.noises`
+// (los índices 0..n). Con menos, el shader entra en pánico al indexar.
+pub const SHADER_NOISES: [(&str, FragmentShader, usize); 17] = [
+    ("fragment_shader", fragment_shader, 1),
+    ("shader_companion_star", shader_companion_star, 1),
+    ("shader_mercury", shader_mercury, 3),
+    ("shader_venus", shader_venus, 2),
+    ("shader_earth", shader_earth, 5),
+    ("shader_moon", shader_moon, 3),
+    ("shader_mars", shader_mars, 3),
+    ("shader_phobos", shader_phobos, 3),
+    ("shader_jupiter", shader_jupiter, 3),
+    ("shader_saturn", shader_saturn, 2),
+    ("shader_uranus", shader_uranus, 2),
+    ("shader_neptune", shader_neptune, 2),
+    ("shader_pluto", shader_pluto, 2),
+    ("shader_eris", shader_eris, 2),
+    ("shader_sedna", shader_sedna, 2),
+    ("shader_ring", shader_ring, 0),
+    ("shader_uranus_ring", shader_uranus_ring, 2),
+];
+
+// Entrada de `SHADER_NOISES` del shader, buscada por la función misma (no por un
+// nombre aparte que podría no corresponder al shader que se dibuja)
+fn registered_shader(shader: FragmentShader) -> Option<(&'static str, usize)> {
+    SHADER_NOISES
+        .iter()
+        .find(|&&(_, registered, _)| std::ptr::fn_addr_eq(registered, shader))
+        .map(|&(name, _, count)| (name, count))
+}
+
+// Ruidos que lee el shader. Uno sin registrar avisa y cuenta como 0: no se puede
+// comprobar, así que hay que agregarlo a `SHADER_NOISES`.
+pub fn required_noises(shader: FragmentShader) -> usize {
+    match registered_shader(shader) {
+        Some((_, count)) => count,
+        None => {
+            log_warn!("Shader is not registered in SHADER_NOISES; its noises are not checked");
+            0
+        }
+    }
+}
+
+// Comprueba que `noises` alcance para el shader del cuerpo; si faltan, avisa con
+// el nombre de ambos y completa con ruidos por defecto (la superficie se verá
+// distinta, pero no falla)
+pub fn with_required_noises(
+    body: &str,
+    shader: FragmentShader,
+    mut noises: Vec<FastNoiseLite>,
+) -> Vec<FastNoiseLite> {
+    let Some((shader_name, required)) = registered_shader(shader) else {
+        log_warn!(
+            "{} uses a shader that is not registered in SHADER_NOISES; its noises are not checked",
+            body
+        );
+        return noises;
+    };
+    if noises.len() < required {
+        log_warn!(
+            "{} has {} noises but {} needs {}; filling the rest with default noise",
+            body,
+            noises.len(),
+            shader_name,
+            required
+        );
+        noises.resize_with(required, FastNoiseLite::new);
+    }
+    noises
+}
 
-    final_color.clamp()
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh;
+    use crate::texture::Texture;
+    use crate::Light;
+    use nalgebra_glm::Vec2;
+
+    fn uniforms<'a>(
+        noises: Vec<&'a FastNoiseLite>,
+        normal_map: Option<&'a Texture>,
+    ) -> Uniforms<'a> {
+        Uniforms {
+            noises,
+            lights: vec![Light {
+                position: Vec3::new(3.0, 2.0, 20.0),
+                color: Color::white(),
+            }],
+            normal_map,
+            ..Uniforms::new(Mat4::identity(), Mat4::identity(), Mat4::identity(), 0.0)
+        }
+    }
+
+    #[test]
+    fn sun_spots_plus_emission_match_the_lava_mix_and_keep_a_glowing_limb() {
+        let noise = FastNoiseLite::with_seed(3);
+        let uniforms = uniforms(vec![&noise], None);
+        let position = Vec3::new(0.3, -0.2, 0.9);
+        let fragment = |intensity| {
+            Fragment::new(
+                Vec2::zeros(),
+                Color::black(),
+                0.5,
+                position,
+                intensity,
+                position,
+            )
+        };
+
+        // De frente, las manchas más la emisión dan la mezcla de la lava
+        let mixed =
+            lava_base_color().lerp(&lava_bright_color(), lava_noise(&fragment(1.0), &uniforms));
+        let lit = fragment_shader(&fragment(1.0), &uniforms).unwrap() + lava_base_color();
+        for (a, b) in [(lit.r, mixed.r), (lit.g, mixed.g), (lit.b, mixed.b)] {
+            assert!(a.abs_diff(b) <= 1);
+        }
+        // En el borde solo queda la emisión, que no depende de la luz
+        let limb = fragment_shader(&fragment(0.0), &uniforms).unwrap();
+        assert_eq!(limb.to_hex(), 0x000000);
+    }
+
+    #[test]
+    fn flat_normal_map_leaves_lighting_unchanged() {
+        let noises = [
+            FastNoiseLite::new(),
+            FastNoiseLite::new(),
+            FastNoiseLite::new(),
+        ];
+        let flat_map = Texture::flat_normal_map(16, 8);
+        let plain = uniforms(noises.iter().collect(), None);
+        let mapped = uniforms(noises.iter().collect(), Some(&flat_map));
+
+        for vertex in mesh::uv_sphere(8, 16).vertices {
+            let fragment = Fragment {
+                tex_coords: vertex.tex_coords,
+                tangent: vertex.tangent,
+                ..Fragment::new(
+                    Vec2::zeros(),
+                    Color::black(),
+                    0.0,
+                    vertex.normal,
+                    1.0,
+                    vertex.position,
+                )
+            };
+
+            let normal = mapped_normal(&fragment, &mapped);
+            assert!((normal - vertex.normal).magnitude() < 1e-6);
+            for shader in [shader_mercury, shader_phobos, shader_normal_mapped] {
+                assert_eq!(
+                    shader(&fragment, &plain).map(|color| color.to_hex()),
+                    shader(&fragment, &mapped).map(|color| color.to_hex())
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn skipped_noise_layers_do_not_affect_the_shader() {
+        let mut noises = [
+            FastNoiseLite::with_seed(1),
+            FastNoiseLite::with_seed(2),
+            FastNoiseLite::with_seed(3),
+        ];
+        let shade = |noises: &[FastNoiseLite], noise_layers: Option<usize>| {
+            let uniforms = Uniforms {
+                noise_layers,
+                ..uniforms(noises.iter().collect(), None)
+            };
+            mesh::uv_sphere(8, 16)
+                .vertices
+                .iter()
+                .map(|vertex| {
+                    let fragment = Fragment::new(
+                        Vec2::zeros(),
+                        Color::black(),
+                        0.0,
+                        vertex.normal,
+                        1.0,
+                        vertex.position,
+                    );
+                    shader_jupiter(&fragment, &uniforms).map(|color| color.to_hex())
+                })
+                .collect::<Vec<_>>()
+        };
+        let full = shade(&noises, None);
+        let reduced = shade(&noises, Some(1));
+        assert_eq!(full, shade(&noises, Some(3)));
+        assert_ne!(full, reduced);
+
+        // Con una sola capa, cambiar las demás no cambia nada
+        noises[1].set_seed(Some(20));
+        noises[2].set_seed(Some(30));
+        assert_eq!(reduced, shade(&noises, Some(1)));
+        assert_ne!(full, shade(&noises, None));
+    }
+
+    #[test]
+    fn realistic_lighting_falls_off_and_adds_highlights_on_the_lit_side() {
+        let stylized = uniforms(vec![], None);
+        let realistic = Uniforms {
+            lighting: LightingSettings::REALISTIC,
+            ..uniforms(vec![], None)
+        };
+        let base = Color::new(40, 40, 40);
+        // Frente a la luz (y a la cámara, en el origen) y de espaldas a ella
+        let lit = (Vec3::new(0.0, 0.0, 1.0), Vec3::new(0.0, 0.0, 10.0));
+        let dark = (Vec3::new(0.0, 0.0, -1.0), Vec3::new(0.0, 0.0, 10.0));
+
+        assert_eq!(
+            apply_highlights(base, lit.0, lit.1, &stylized).to_hex(),
+            base.to_hex()
+        );
+        assert!(apply_highlights(base, lit.0, lit.1, &realistic).r > base.r);
+        assert_eq!(
+            apply_highlights(base, dark.0, dark.1, &realistic).to_hex(),
+            base.to_hex()
+        );
+
+        // Al doble de la distancia de referencia llega un cuarto de la luz
+        let reference = LightingSettings::REALISTIC.falloff_distance.unwrap();
+        assert!((LightingSettings::REALISTIC.attenuation(reference * 2.0) - 0.25).abs() < 1e-6);
+        assert_eq!(stylized.lighting.attenuation(1000.0), 1.0);
+        assert_eq!(
+            LightingSettings::STYLIZED.next(),
+            LightingSettings::REALISTIC
+        );
+        assert_eq!(
+            LightingSettings::REALISTIC.next(),
+            LightingSettings::STYLIZED
+        );
+    }
+
+    #[test]
+    fn textured_albedo_is_lit_in_linear_space() {
+        let gray = Texture::from_fn(4, 4, |_, _| Color::new(128, 128, 128));
+        let albedo = gray.sample_linear(Vec2::new(0.3, 0.6));
+        assert!((albedo.x - 0.2158).abs() < 1e-3);
+
+        let mut uniforms = uniforms(vec![], None);
+        uniforms.ambient = 0.0;
+        // Luz plena: el gris medio sale igual que entró
+        let lit = apply_lighting_linear(albedo, Color::white(), &uniforms);
+        assert_eq!(lit.to_hex(), 0x808080);
+        // Media luz es la mitad de la energía, no la mitad del valor sRGB (64)
+        let half = apply_lighting_linear(albedo, Color::white() * 0.5, &uniforms);
+        assert!((half.r as i32 - 92).abs() <= 1, "{:?}", half);
+
+        // Un mapa de normales es lineal: se muestrea sin decodificar
+        let flat = Texture::flat_normal_map(4, 4);
+        assert_eq!(flat.sample(Vec2::new(0.5, 0.5)).to_hex(), 0x8080ff);
+    }
+
+    #[test]
+    fn registered_shaders_run_with_their_required_noises() {
+        for (name, shader, _) in SHADER_NOISES {
+            // Completa desde cero: también comprueba que la cantidad declarada alcanza
+            let noises = with_required_noises("Prueba", shader, vec![]);
+            assert_eq!(noises.len(), required_noises(shader), "{}", name);
+            let uniforms = uniforms(noises.iter().collect(), None);
+            for vertex in mesh::uv_sphere(4, 8).vertices {
+                let fragment = Fragment::new(
+                    Vec2::zeros(),
+                    Color::black(),
+                    0.0,
+                    vertex.normal,
+                    1.0,
+                    vertex.position,
+                );
+                shader(&fragment, &uniforms);
+            }
+        }
+
+        let enough: Vec<FastNoiseLite> = (0..6).map(|_| FastNoiseLite::new()).collect();
+        assert_eq!(
+            with_required_noises("Prueba", shader_earth, enough).len(),
+            6
+        );
+
+        // Un shader sin registrar no se puede comprobar: se deja tal cual
+        fn unregistered(_: &Fragment, _: &Uniforms) -> Option<Color> {
+            None
+        }
+        assert_eq!(required_noises(unregistered), 0);
+        assert!(with_required_noises("Prueba", unregistered, vec![]).is_empty());
+    }
+
+    #[test]
+    fn default_params_match_the_hardcoded_look() {
+        let noises: Vec<FastNoiseLite> = (0..5).map(|_| FastNoiseLite::new()).collect();
+        let cases: [(FragmentShader, &[(&str, f32)]); 2] = [
+            (
+                shader_earth,
+                &[
+                    ("land_threshold", EARTH_LAND_THRESHOLD),
+                    ("cloud_threshold", 0.7),
+                    ("land_speed", 0.01),
+                    ("cloud_speed", 0.03),
+                ],
+            ),
+            (
+                shader_jupiter,
+                &[
+                    ("band_frequency", 1.0),
+                    ("cloud_frequency", 1.0),
+                    ("cloud_mix", 1.0),
+                ],
+            ),
+        ];
+        for (shader, params) in cases {
+            let empty = uniforms(noises.iter().collect(), None);
+            let explicit = Uniforms {
+                params,
+                ..empty.clone()
+            };
+            let halved = [(params[0].0, params[0].1 * 0.5)];
+            let changed = Uniforms {
+                params: &halved,
+                ..empty.clone()
+            };
+
+            let mut differs = false;
+            for vertex in mesh::uv_sphere(8, 16).vertices {
+                let fragment = Fragment::new(
+                    Vec2::zeros(),
+                    Color::black(),
+                    0.0,
+                    vertex.normal,
+                    1.0,
+                    vertex.position,
+                );
+                let color = |uniforms: &Uniforms| shader(&fragment, uniforms).map(|c| c.to_hex());
+                assert_eq!(color(&empty), color(&explicit));
+                differs |= color(&empty) != color(&changed);
+            }
+            assert!(differs);
+        }
+    }
 }
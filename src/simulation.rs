@@ -0,0 +1,155 @@
+// simulation.rs
+// Avance del tiempo simulado en pasos fijos a partir del tiempo real de un
+// `Clock`, con el muestreo de las estelas. No sabe nada de la ventana, así que
+// se puede probar con un `ManualClock`.
+
+use crate::clock::Clock;
+
+// Paso fijo de la simulación: se avanza en incrementos de FIXED_DT segundos
+// reales, independientemente de la tasa de cuadros, para que sea determinista.
+pub const FIXED_DT: f32 = 1.0 / 60.0;
+// Unidades de `time` que avanza la simulación en cada paso fijo
+pub const SIM_TIME_PER_STEP: f32 = 100.0;
+// Unidades de `time` entre dos puntos de una estela. Las estelas se muestrean por
+// tiempo simulado, así su densidad y su largo (puntos * intervalo) no dependen de
+// los FPS ni del tamaño del paso.
+pub const TRAIL_SAMPLE_INTERVAL: f32 = 100.0;
+// Evita la "espiral de la muerte" si un cuadro tarda demasiado
+pub const MAX_FRAME_TIME: f32 = 0.25;
+
+pub struct Simulation {
+    time: f32,
+    previous_time: f32,
+    accumulator: f32,
+    // Tiempo simulado acumulado desde el último punto de las estelas
+    trail_sample_time: f32,
+    // 1 hacia adelante, -1 en reversa
+    pub direction: f32,
+    // Sin avanzar (`--hold-time`): el tiempo real se sigue midiendo, pero no se acumula
+    pub held: bool,
+}
+
+impl Simulation {
+    pub fn new(start_time: f32) -> Self {
+        Simulation {
+            time: start_time,
+            previous_time: start_time,
+            accumulator: 0.0,
+            trail_sample_time: 0.0,
+            direction: 1.0,
+            held: false,
+        }
+    }
+
+    // Lee el tiempo real del cuadro y lo acumula para `step`. Devuelve ese tiempo,
+    // recortado a MAX_FRAME_TIME, para lo que se anima en tiempo real.
+    pub fn tick(&mut self, clock: &mut dyn Clock) -> f32 {
+        let frame_time = clock.delta_time().min(MAX_FRAME_TIME);
+        if !self.held {
+            self.accumulator += frame_time;
+        }
+        frame_time
+    }
+
+    // Consume lo acumulado en pasos fijos. `sample_trail` recibe el instante exacto
+    // de cada punto de las estelas, cada TRAIL_SAMPLE_INTERVAL de tiempo simulado.
+    pub fn step<F: FnMut(f32)>(&mut self, mut sample_trail: F) {
+        while self.accumulator >= FIXED_DT {
+            self.previous_time = self.time;
+            self.time += SIM_TIME_PER_STEP * self.direction;
+            self.accumulator -= FIXED_DT;
+
+            self.trail_sample_time += SIM_TIME_PER_STEP;
+            while self.trail_sample_time >= TRAIL_SAMPLE_INTERVAL {
+                self.trail_sample_time -= TRAIL_SAMPLE_INTERVAL;
+                sample_trail(self.time - self.trail_sample_time * self.direction);
+            }
+        }
+    }
+
+    // Tiempo a renderizar, interpolado entre los dos últimos pasos. Como las
+    // posiciones son funciones puras del tiempo, interpolar el tiempo mantiene
+    // cada cuerpo sobre su órbita (en lugar de cortar por la cuerda).
+    pub fn time(&self) -> f32 {
+        let alpha = self.accumulator / FIXED_DT;
+        self.previous_time + (self.time - self.previous_time) * alpha
+    }
+}
+
+// Tiempo simulado equivalente a `seconds` segundos reales de simulación
+pub fn sim_time_from_seconds(seconds: f32) -> f32 {
+    seconds / FIXED_DT * SIM_TIME_PER_STEP
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::planet::{orbit_translation, Planet};
+    use crate::planet_trail::PlanetTrail;
+    use nalgebra_glm::Vec3;
+
+    #[test]
+    fn manual_clock_drives_orbits_and_trails() {
+        let planet = Planet::new("Prueba", 1.0, 12.0, 0.02, 100);
+        let mut trail = PlanetTrail::new(planet.max_trail_length);
+        let mut clock = ManualClock::new();
+        let mut simulation = Simulation::new(0.0);
+
+        // Diez cuadros de 0.1 s: 60 pasos fijos, un punto de estela por paso. Medio
+        // paso extra al inicio aleja el total del borde de un paso (redondeo).
+        let mut samples = 0;
+        clock.advance(FIXED_DT * 0.5);
+        for _ in 0..10 {
+            clock.advance(0.1);
+            simulation.tick(&mut clock);
+            simulation.step(|sample_time| {
+                samples += 1;
+                trail.advance(orbit_translation(
+                    Vec3::zeros(),
+                    planet.orbit_radius,
+                    planet.signed_orbit_speed(),
+                    planet.phase_offset,
+                    sample_time,
+                ));
+            });
+        }
+        assert_eq!(samples, 60);
+        // Se interpola entre los dos últimos pasos con el medio paso pendiente
+        let expected = sim_time_from_seconds(1.0) - SIM_TIME_PER_STEP * 0.5;
+        assert!((simulation.time() - expected).abs() < SIM_TIME_PER_STEP * 0.01);
+
+        let position = orbit_translation(
+            Vec3::zeros(),
+            planet.orbit_radius,
+            planet.signed_orbit_speed(),
+            planet.phase_offset,
+            simulation.time(),
+        );
+        let direct = orbit_translation(
+            Vec3::zeros(),
+            planet.orbit_radius,
+            planet.signed_orbit_speed(),
+            planet.phase_offset,
+            expected,
+        );
+        assert!((position - direct).magnitude() < 1e-3);
+
+        // Sin lecturas nuevas del reloj el tiempo no avanza
+        assert_eq!(simulation.tick(&mut clock), 0.0);
+        let before = simulation.time();
+        simulation.step(|_| panic!("no hay pasos pendientes"));
+        assert_eq!(simulation.time(), before);
+    }
+
+    #[test]
+    fn held_simulation_measures_but_does_not_advance() {
+        let mut clock = ManualClock::new();
+        let mut simulation = Simulation::new(500.0);
+        simulation.held = true;
+        clock.advance(2.0);
+        assert_eq!(simulation.tick(&mut clock), MAX_FRAME_TIME);
+        simulation.step(|_| panic!("la simulación está detenida"));
+        assert_eq!(simulation.time(), 500.0);
+    }
+}
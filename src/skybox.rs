@@ -1,24 +1,121 @@
 use crate::{Framebuffer, Uniforms};
 use nalgebra_glm::{Vec3, Vec4};
 use rand::prelude::*;
+use rand::rngs::StdRng;
 use std::f32::consts::PI;
 
+// Profundidad (z de NDC) de las estrellas: justo dentro del plano lejano (z = 1).
+// Toda la geometría visible queda delante, y lo que pasa del plano lejano se
+// recorta en `draw_triangle`, así que las estrellas nunca atraviesan un cuerpo.
+pub const SKYBOX_DEPTH: f32 = 1.0 - 1e-7;
+
+// Radio en píxeles de cada estrella: base + escala * tamaño * brillo
+const STAR_BASE_RADIUS: f32 = 0.4;
+const STAR_RADIUS_SCALE: f32 = 0.4;
+const STAR_SOFTNESS: f32 = 0.7;
+
+// Capas de paralaje: la 0 es la más lejana y no se desplaza; cada capa más
+// cercana se mueve una fracción más del ancho de pantalla al girar la cámara
+const STAR_LAYERS: usize = 3;
+const PARALLAX_SCREEN_FRACTION: f32 = 0.006;
+
+// Banda galáctica: inclinación respecto del plano de las órbitas y semiancho
+// angular (radianes) en el que se agrupan sus estrellas
+const GALACTIC_BAND_TILT: f32 = PI / 3.0;
+const GALACTIC_BAND_HALF_WIDTH: f32 = 0.18;
+
+// Fondo degradado: color del cielo lejos de la banda galáctica y el que se suma
+// sobre ella, con una caída gaussiana de semiancho angular SKY_GLOW_WIDTH
+const SKY_BASE_COLOR: Vec3 = Vec3::new(2.0, 2.0, 6.0);
+const SKY_GLOW_COLOR: Vec3 = Vec3::new(14.0, 12.0, 22.0);
+const SKY_GLOW_WIDTH: f32 = 0.35;
+
+// Parámetros del cielo estrellado
+#[derive(Clone, Copy, Debug)]
+pub struct SkyboxConfig {
+    pub star_count: usize,
+    // Variación relativa máxima del brillo de cada estrella (0 apaga el titileo)
+    // y su rapidez media en radianes por unidad de `uniforms.time`
+    pub twinkle_amplitude: f32,
+    pub twinkle_speed: f32,
+    // Fracción de las estrellas (0..1) agrupadas en una banda galáctica;
+    // con 0 se reparten por todo el cielo
+    pub galactic_band: f32,
+}
+
+impl Default for SkyboxConfig {
+    fn default() -> Self {
+        SkyboxConfig {
+            star_count: 5000,
+            twinkle_amplitude: 0.15,
+            twinkle_speed: 0.0005,
+            galactic_band: 0.0,
+        }
+    }
+}
+
 pub struct Star {
     position: Vec3,
     brightness: f32,
     size: u8,
+    layer: usize,
+    // Fase y rapidez relativa del titileo, fijas para cada estrella
+    twinkle_phase: f32,
+    twinkle_rate: f32,
+}
+
+impl Star {
+    // Brillo en el instante `time`: oscila alrededor del brillo base
+    fn brightness_at(&self, time: f32, amplitude: f32, speed: f32) -> f32 {
+        let wave = (self.twinkle_phase + time * speed * self.twinkle_rate).sin();
+        (self.brightness * (1.0 + amplitude * wave)).clamp(0.0, 1.0)
+    }
 }
 
 pub struct Skybox {
     stars: Vec<Star>,
+    // Desplaza las capas cercanas según la orientación de la cámara
+    pub parallax_enabled: bool,
+    pub twinkle_amplitude: f32,
+    pub twinkle_speed: f32,
+    // Degradado analítico detrás de las estrellas, más claro hacia la banda galáctica
+    pub gradient_enabled: bool,
 }
 
 impl Skybox {
     pub fn new(star_count: usize) -> Self {
-        let mut rng = rand::thread_rng();
-        let mut stars = Vec::with_capacity(star_count);
+        Self::with_config(
+            &SkyboxConfig {
+                star_count,
+                ..SkyboxConfig::default()
+            },
+            None,
+        )
+    }
 
-        for _ in 0..star_count {
+    // Misma distribución de estrellas en cada ejecución para una semilla dada
+    pub fn with_seed(star_count: usize, seed: u64) -> Self {
+        Self::with_config(
+            &SkyboxConfig {
+                star_count,
+                ..SkyboxConfig::default()
+            },
+            Some(seed),
+        )
+    }
+
+    // Sin semilla cada ejecución tiene un cielo distinto
+    pub fn with_config(config: &SkyboxConfig, seed: Option<u64>) -> Self {
+        match seed {
+            Some(seed) => Self::generate(config, &mut StdRng::seed_from_u64(seed)),
+            None => Self::generate(config, &mut rand::thread_rng()),
+        }
+    }
+
+    fn generate<R: Rng>(config: &SkyboxConfig, rng: &mut R) -> Self {
+        let mut stars = Vec::with_capacity(config.star_count);
+
+        for index in 0..config.star_count {
             // Generate random spherical coordinates
             let theta = rng.gen::<f32>() * 2.0 * PI; // Azimuth angle
             let phi = rng.gen::<f32>() * PI; // Polar angle
@@ -28,19 +125,36 @@ impl Skybox {
             let x = radius * phi.sin() * theta.cos();
             let y = radius * phi.cos();
             let z = radius * phi.sin() * theta.sin();
+            let mut position = Vec3::new(x, y, z);
+
+            // Sin banda no se piden más números al generador: el cielo de cada
+            // semilla queda igual que antes
+            if config.galactic_band > 0.0 && rng.gen::<f32>() < config.galactic_band {
+                position = galactic_band_position(theta, rng) * radius;
+            }
 
             // Random brightness between 0.0 and 1.0
             let brightness = rng.gen::<f32>();
             let size: u8 = rng.gen_range(1..=3);
 
             stars.push(Star {
-                position: Vec3::new(x, y, z),
+                position,
                 brightness,
                 size,
+                // Por índice y no con el generador, para no cambiar el cielo de cada semilla
+                layer: index % STAR_LAYERS,
+                twinkle_phase: star_hash(index, 0) * 2.0 * PI,
+                twinkle_rate: 0.5 + star_hash(index, 1),
             });
         }
 
-        Skybox { stars }
+        Skybox {
+            stars,
+            parallax_enabled: true,
+            twinkle_amplitude: config.twinkle_amplitude,
+            twinkle_speed: config.twinkle_speed,
+            gradient_enabled: false,
+        }
     }
 
     pub fn render(
@@ -51,6 +165,24 @@ impl Skybox {
     ) {
         // let mut rng = rand::thread_rng();
 
+        if self.gradient_enabled {
+            render_gradient(framebuffer, uniforms);
+        }
+
+        // Desplazamiento en pantalla por capa a partir del rumbo y la inclinación de
+        // la cámara (la tercera fila de la matriz de vista es -adelante). Se usa el
+        // seno de los ángulos para que no salte al dar la vuelta completa.
+        let parallax_step = if self.parallax_enabled {
+            let view = uniforms.view_matrix;
+            let forward = -Vec3::new(view[(2, 0)], view[(2, 1)], view[(2, 2)]);
+            let yaw = forward.x.atan2(-forward.z);
+            let pitch = forward.y.clamp(-1.0, 1.0).asin();
+            let pixels = PARALLAX_SCREEN_FRACTION * framebuffer.width as f32;
+            (-yaw.sin() * pixels, pitch.sin() * pixels)
+        } else {
+            (0.0, 0.0)
+        };
+
         for star in &self.stars {
             // Calculate star position relative to camera
             let position = star.position + camera_position;
@@ -73,44 +205,182 @@ impl Skybox {
                 continue;
             }
 
-            let x = screen_pos.x as usize;
-            let y = screen_pos.y as usize;
-
-            if x < framebuffer.width && y < framebuffer.height {
-                // Random chance for star to blink
-                /*
-                let blink_chance = rng.gen::<f32>();
-                let blink_amount = if blink_chance < 0.3 {
-                    (rng.gen::<f32>() - 0.5) * 0.2 // This gives us -0.1 to 0.1 variation
-                } else {
-                    0.0
-                };
-                let adjusted_brightness = (star.brightness + blink_amount).clamp(0.0, 1.0);
-                */
-                let intensity = (star.brightness * 255.0) as u8;
-                let color = (intensity as u32) << 16 | (intensity as u32) << 8 | intensity as u32;
-
-                framebuffer.set_current_color(color);
-                // framebuffer.point(x, y, 1000.0);  // depth is high so things render in front
-
-                match star.size {
-                    1 => framebuffer.point(x, y, 1000.0),
-                    2 => {
-                        framebuffer.point(x, y, 1000.0);
-                        framebuffer.point(x + 1, y, 1000.0);
-                        framebuffer.point(x, y + 1, 1000.0);
-                        framebuffer.point(x + 1, y + 1, 1000.0);
-                    }
-                    3 => {
-                        framebuffer.point(x, y, 1000.0);
-                        framebuffer.point(x - 1, y, 1000.0);
-                        framebuffer.point(x + 1, y, 1000.0);
-                        framebuffer.point(x, y - 1, 1000.0);
-                        framebuffer.point(x, y + 1, 1000.0);
-                    }
-                    _ => {}
-                }
+            let (x, y) = (
+                screen_pos.x + parallax_step.0 * star.layer as f32,
+                screen_pos.y + parallax_step.1 * star.layer as f32,
+            );
+            if x < 0.0 || y < 0.0 || x >= framebuffer.width as f32 || y >= framebuffer.height as f32
+            {
+                continue;
+            }
+
+            // Titileo suave y determinista: cada estrella con su fase y su ritmo
+            let brightness =
+                star.brightness_at(uniforms.time, self.twinkle_amplitude, self.twinkle_speed);
+            let intensity = (brightness * 255.0) as u8;
+            let color = (intensity as u32) << 16 | (intensity as u32) << 8 | intensity as u32;
+
+            // Las estrellas grandes y brillantes se ven como discos suaves; las
+            // tenues quedan en un solo píxel (camino rápido de `draw_point`)
+            let radius = STAR_BASE_RADIUS + STAR_RADIUS_SCALE * star.size as f32 * star.brightness;
+            framebuffer.draw_point(x, y, SKYBOX_DEPTH, color, radius, STAR_SOFTNESS);
+        }
+    }
+}
+
+// Pinta cada píxel de fondo (sin profundidad escrita) según la dirección de su
+// rayo. Un punto de pantalla se lleva al mundo con la inversa de
+// viewport * proyección * vista; como en coordenadas homogéneas eso es lineal en
+// x e y, basta transformar las esquinas e interpolar, y la dirección es la resta
+// de dos profundidades (vale también en ortográfica).
+fn render_gradient(framebuffer: &mut Framebuffer, uniforms: &Uniforms) {
+    let Some(inverse) =
+        (uniforms.viewport_matrix * uniforms.projection_matrix * uniforms.view_matrix)
+            .try_inverse()
+    else {
+        return;
+    };
+    let (width, height) = (framebuffer.width, framebuffer.height);
+    let unproject = |x: f32, y: f32, z: f32| inverse * Vec4::new(x, y, z, 1.0);
+    let corners = |z: f32| {
+        [
+            unproject(0.0, 0.0, z),
+            unproject(width as f32, 0.0, z),
+            unproject(0.0, height as f32, z),
+            unproject(width as f32, height as f32, z),
+        ]
+    };
+    let (near, far) = (corners(0.0), corners(0.5));
+    let band_normal = galactic_band_normal();
+
+    for y in 0..height {
+        let v = (y as f32 + 0.5) / height as f32;
+        let row = |c: &[Vec4; 4]| (c[0].lerp(&c[2], v), c[1].lerp(&c[3], v));
+        let (near_left, near_right) = row(&near);
+        let (far_left, far_right) = row(&far);
+        for x in 0..width {
+            let index = y * width + x;
+            if framebuffer.zbuffer[index] != f32::INFINITY {
+                continue;
+            }
+            let u = (x as f32 + 0.5) / width as f32;
+            let near_point = near_left.lerp(&near_right, u);
+            let far_point = far_left.lerp(&far_right, u);
+            let direction = far_point.xyz() / far_point.w - near_point.xyz() / near_point.w;
+            let color = sky_color(direction, band_normal);
+            framebuffer.set_pixel(
+                x,
+                y,
+                (color.x as u32) << 16 | (color.y as u32) << 8 | color.z as u32,
+            );
+        }
+    }
+}
+
+// Normal del plano de la banda galáctica (ver `galactic_band_position`)
+fn galactic_band_normal() -> Vec3 {
+    let (sin, cos) = GALACTIC_BAND_TILT.sin_cos();
+    Vec3::new(0.0, cos, sin)
+}
+
+// Color del cielo en la dirección `direction` (sin normalizar), de 0 a 255 por canal
+fn sky_color(direction: Vec3, band_normal: Vec3) -> Vec3 {
+    let latitude = direction
+        .normalize()
+        .dot(&band_normal)
+        .clamp(-1.0, 1.0)
+        .asin();
+    let glow = (-(latitude / SKY_GLOW_WIDTH).powi(2)).exp();
+    SKY_BASE_COLOR + SKY_GLOW_COLOR * glow
+}
+
+// Punto de la esfera unitaria cerca del ecuador de la banda galáctica: acimut
+// dado, latitud concentrada hacia el centro (suma de dos uniformes) y el
+// plano de la banda inclinado alrededor del eje X
+fn galactic_band_position<R: Rng>(azimuth: f32, rng: &mut R) -> Vec3 {
+    let latitude = (rng.gen::<f32>() + rng.gen::<f32>() - 1.0) * GALACTIC_BAND_HALF_WIDTH;
+    let point = Vec3::new(
+        latitude.cos() * azimuth.cos(),
+        latitude.sin(),
+        latitude.cos() * azimuth.sin(),
+    );
+    let (sin, cos) = GALACTIC_BAND_TILT.sin_cos();
+    Vec3::new(
+        point.x,
+        point.y * cos - point.z * sin,
+        point.y * sin + point.z * cos,
+    )
+}
+
+// Número fijo en [0, 1) para una estrella (mezcla splitmix64 del índice), así el
+// titileo no depende del generador ni cambia de una ejecución a otra
+fn star_hash(index: usize, salt: u64) -> f32 {
+    let mut z = (index as u64)
+        .wrapping_mul(2)
+        .wrapping_add(salt)
+        .wrapping_add(1)
+        .wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    (z >> 40) as f32 / (1u64 << 24) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn twinkle_is_deterministic_and_bounded() {
+        let config = SkyboxConfig {
+            star_count: 200,
+            ..SkyboxConfig::default()
+        };
+        let first = Skybox::with_config(&config, Some(7));
+        let second = Skybox::with_config(&config, Some(7));
+
+        for time in [0.0, 1234.5, 98765.0] {
+            for (a, b) in first.stars.iter().zip(&second.stars) {
+                let brightness =
+                    a.brightness_at(time, config.twinkle_amplitude, config.twinkle_speed);
+                assert_eq!(
+                    brightness,
+                    b.brightness_at(time, config.twinkle_amplitude, config.twinkle_speed)
+                );
+                assert!(
+                    (brightness - a.brightness).abs()
+                        <= a.brightness * config.twinkle_amplitude + 1e-6
+                );
             }
         }
+        // Fases distintas: no titilan todas a la vez
+        let phases: Vec<f32> = first.stars.iter().map(|star| star.twinkle_phase).collect();
+        assert!(phases.windows(2).any(|pair| pair[0] != pair[1]));
+    }
+
+    #[test]
+    fn galactic_band_gathers_stars_near_its_plane() {
+        let config = SkyboxConfig {
+            star_count: 500,
+            galactic_band: 1.0,
+            ..SkyboxConfig::default()
+        };
+        let skybox = Skybox::with_config(&config, Some(3));
+        let band_normal = galactic_band_normal();
+        for star in &skybox.stars {
+            let latitude = (star.position.normalize().dot(&band_normal)).asin();
+            assert!(latitude.abs() <= GALACTIC_BAND_HALF_WIDTH + 1e-4);
+        }
+    }
+
+    #[test]
+    fn sky_gradient_is_brightest_on_the_galactic_plane() {
+        let band_normal = galactic_band_normal();
+        let on_band = sky_color(Vec3::new(1.0, 0.0, 0.0), band_normal);
+        let off_band = sky_color(band_normal, band_normal);
+        assert_eq!(on_band, SKY_BASE_COLOR + SKY_GLOW_COLOR);
+        assert!((off_band - SKY_BASE_COLOR).magnitude() < 0.01);
+        // Tenue: nunca se acerca al brillo de las estrellas
+        assert!(on_band.max() < 40.0);
     }
 }
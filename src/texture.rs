@@ -0,0 +1,187 @@
+// texture.rs
+// Texturas en memoria muestreadas con coordenadas UV. Por ahora se generan por
+// código (no se cargan imágenes): los mapas de normales salen de una función de altura.
+
+use crate::color::{srgb_to_linear, Color};
+use nalgebra_glm::{Vec2, Vec3};
+
+// Cómo están guardados los texels. Los colores (albedo) van en sRGB, como las
+// imágenes de 8 bits; los datos (normales, máscaras) ya son lineales y no se
+// deben decodificar.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ColorSpace {
+    #[default]
+    Srgb,
+    Linear,
+}
+
+pub struct Texture {
+    pub width: usize,
+    pub height: usize,
+    pub color_space: ColorSpace,
+    texels: Vec<Color>,
+}
+
+impl Texture {
+    // Evalúa `texel(u, v)` en el centro de cada texel
+    pub fn from_fn<F: Fn(f32, f32) -> Color>(width: usize, height: usize, texel: F) -> Self {
+        let width = width.max(1);
+        let height = height.max(1);
+        let mut texels = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let u = (x as f32 + 0.5) / width as f32;
+                let v = (y as f32 + 0.5) / height as f32;
+                texels.push(texel(u, v));
+            }
+        }
+        Texture {
+            width,
+            height,
+            color_space: ColorSpace::Srgb,
+            texels,
+        }
+    }
+
+    // Para texturas de color generadas que en realidad son datos lineales
+    pub fn with_color_space(mut self, color_space: ColorSpace) -> Self {
+        self.color_space = color_space;
+        self
+    }
+
+    // Mapa de normales sin relieve: todas las normales apuntan a +Z del espacio tangente
+    pub fn flat_normal_map(width: usize, height: usize) -> Self {
+        Self::from_fn(width, height, |_, _| {
+            encode_normal(Vec3::new(0.0, 0.0, 1.0))
+        })
+        .with_color_space(ColorSpace::Linear)
+    }
+
+    // Mapa de normales a partir de la altura `height_at(u, v)`. Las pendientes se
+    // miden con diferencias centrales entre texels vecinos (u se repite, v no) y
+    // `strength` las exagera o suaviza.
+    pub fn normal_map_from_height<F: Fn(f32, f32) -> f32>(
+        width: usize,
+        height: usize,
+        strength: f32,
+        height_at: F,
+    ) -> Self {
+        let width = width.max(1);
+        let height = height.max(1);
+        let mut samples = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let u = (x as f32 + 0.5) / width as f32;
+                let v = (y as f32 + 0.5) / height as f32;
+                samples.push(height_at(u, v));
+            }
+        }
+
+        let sample = |x: usize, y: usize| samples[y * width + x];
+        let mut texels = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let left = sample((x + width - 1) % width, y);
+                let right = sample((x + 1) % width, y);
+                let up = sample(x, y.saturating_sub(1));
+                let down = sample(x, (y + 1).min(height - 1));
+                let slope_u = (right - left) * 0.5 * strength;
+                let slope_v = (down - up) * 0.5 * strength;
+                texels.push(encode_normal(
+                    Vec3::new(-slope_u, -slope_v, 1.0).normalize(),
+                ));
+            }
+        }
+        Texture {
+            width,
+            height,
+            color_space: ColorSpace::Linear,
+            texels,
+        }
+    }
+
+    // Muestreo bilineal en valores lineales (0..1 por canal), lo que debe entrar
+    // a la iluminación. Los texels sRGB se decodifican antes de filtrar: mezclar
+    // los valores codificados oscurece los bordes entre colores.
+    pub fn sample_linear(&self, uv: Vec2) -> Vec3 {
+        let decode = |color: Color| {
+            let channel = |value: u8| match self.color_space {
+                ColorSpace::Srgb => srgb_to_linear(value as f32 / 255.0),
+                ColorSpace::Linear => value as f32 / 255.0,
+            };
+            Vec3::new(channel(color.r), channel(color.g), channel(color.b))
+        };
+        self.filter(uv, decode)
+    }
+
+    // Muestreo bilineal como color guardado (sRGB o lineal, según la textura)
+    pub fn sample(&self, uv: Vec2) -> Color {
+        let linear = self.sample_linear(uv);
+        match self.color_space {
+            ColorSpace::Srgb => Color::from_linear(linear),
+            ColorSpace::Linear => {
+                let channel = |value: f32| (value * 255.0).round().clamp(0.0, 255.0) as u8;
+                Color::new(channel(linear.x), channel(linear.y), channel(linear.z))
+            }
+        }
+    }
+
+    // Normal en espacio tangente (x a lo largo de u, y a lo largo de v, z hacia afuera).
+    // Se filtran las normales ya decodificadas: pasar por un `Color` intermedio
+    // volvería a cuantizar el resultado a 8 bits.
+    pub fn sample_normal(&self, uv: Vec2) -> Vec3 {
+        debug_assert_eq!(self.color_space, ColorSpace::Linear);
+        self.filter(uv, decode_normal).normalize()
+    }
+
+    // Interpolación bilineal de `value(texel)`; u se repite (costura de la
+    // esfera) y v se limita a [0, 1]
+    fn filter<F: Fn(Color) -> Vec3>(&self, uv: Vec2, value: F) -> Vec3 {
+        let x = uv.x.rem_euclid(1.0) * self.width as f32 - 0.5;
+        let y = uv.y.clamp(0.0, 1.0) * self.height as f32 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = (x - x0, y - y0);
+
+        let column = |offset: f32| (x0 + offset).rem_euclid(self.width as f32) as usize;
+        let row = |offset: f32| ((y0 + offset).max(0.0) as usize).min(self.height - 1);
+        let texel = |column: usize, row: usize| value(self.texels[row * self.width + column]);
+
+        let top = texel(column(0.0), row(0.0)).lerp(&texel(column(1.0), row(0.0)), tx);
+        let bottom = texel(column(0.0), row(1.0)).lerp(&texel(column(1.0), row(1.0)), tx);
+        top.lerp(&bottom, ty)
+    }
+}
+
+// Cada componente de [-1, 1] se guarda centrada en 128, así la normal plana
+// (0, 0, 1) se codifica y decodifica sin error
+fn encode_normal(normal: Vec3) -> Color {
+    let channel = |value: f32| (value * 127.0 + 128.0).round().clamp(0.0, 255.0) as u8;
+    Color::new(channel(normal.x), channel(normal.y), channel(normal.z))
+}
+
+fn decode_normal(color: Color) -> Vec3 {
+    let channel = |value: u8| ((value as f32 - 128.0) / 127.0).clamp(-1.0, 1.0);
+    Vec3::new(channel(color.r), channel(color.g), channel(color.b)).normalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normals_are_filtered_after_decoding() {
+        // Dos columnas inclinadas en sentidos distintos; entre sus centros la normal
+        // es la mezcla de las decodificadas, sin el error de volver a 8 bits
+        let left = encode_normal(Vec3::new(0.6, 0.1, 0.8).normalize());
+        let right = encode_normal(Vec3::new(-0.3, 0.5, 0.8).normalize());
+        let map = Texture::from_fn(2, 1, |u, _| if u < 0.5 { left } else { right })
+            .with_color_space(ColorSpace::Linear);
+
+        // u = 0.3 queda a un 10% del centro de la columna izquierda hacia la derecha
+        let sampled = map.sample_normal(Vec2::new(0.3, 0.5));
+        let expected = decode_normal(left)
+            .lerp(&decode_normal(right), 0.1)
+            .normalize();
+        assert!((sampled - expected).magnitude() < 1e-5);
+    }
+}
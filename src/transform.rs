@@ -0,0 +1,59 @@
+use nalgebra_glm::{self as glm, Mat4, Vec3};
+
+// Builds a body's model matrix as translate-to-orbit * axial-tilt * spin * scale,
+// so each part can be animated on its own.
+#[derive(Debug, Clone, Copy)]
+pub struct ModelTransform {
+    translation: Vec3,
+    axial_tilt: f32,
+    spin: f32,
+    scale: f32,
+}
+
+impl ModelTransform {
+    pub fn new() -> Self {
+        ModelTransform {
+            translation: Vec3::zeros(),
+            axial_tilt: 0.0,
+            spin: 0.0,
+            scale: 1.0,
+        }
+    }
+
+    pub fn translation(mut self, translation: Vec3) -> Self {
+        self.translation = translation;
+        self
+    }
+
+    // Tilt of the spin axis away from +Y, in radians (around the Z axis)
+    pub fn axial_tilt(mut self, angle: f32) -> Self {
+        self.axial_tilt = angle;
+        self
+    }
+
+    // Rotation of the body around its own (tilted) axis, in radians
+    pub fn spin(mut self, angle: f32) -> Self {
+        self.spin = angle;
+        self
+    }
+
+    pub fn scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn build(&self) -> Mat4 {
+        let translation = glm::translation(&self.translation);
+        let tilt = glm::rotation(self.axial_tilt, &Vec3::new(0.0, 0.0, 1.0));
+        let spin = glm::rotation(self.spin, &Vec3::new(0.0, 1.0, 0.0));
+        let scale = glm::scaling(&Vec3::new(self.scale, self.scale, self.scale));
+
+        translation * tilt * spin * scale
+    }
+}
+
+impl Default for ModelTransform {
+    fn default() -> Self {
+        ModelTransform::new()
+    }
+}
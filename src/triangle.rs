@@ -3,7 +3,44 @@ use crate::fragment::Fragment;
 use crate::vertex::{self, Vertex};
 use nalgebra_glm::{dot, Vec2, Vec3};
 
+// Área (doble, en píxeles²) por debajo de la cual el triángulo se considera
+// degenerado: no cubre centros de píxel de forma fiable y las coordenadas
+// baricéntricas se vuelven ruido numérico (manchas en los polos de la esfera)
+const MIN_TRIANGLE_AREA: f32 = 1e-4;
+
+// Sin límite de píxeles para `triangle_in_rect`
+pub const FULL_RECT: (i32, i32, i32, i32) = (i32::MIN, i32::MIN, i32::MAX, i32::MAX);
+
+// Qué píxeles cubre un triángulo. Las pruebas de píxeles exactos (las de este
+// archivo y `tiled_rasterizer_matches_simple_rasterizer`) usan `TopLeft`, el modo
+// por defecto, que es determinista: un píxel depende solo de su centro.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum FillRule {
+    // El centro del píxel dentro del triángulo; sobre una arista decide la regla
+    // arriba-izquierda
+    #[default]
+    TopLeft,
+    // Todo píxel que el triángulo toque, aunque no cubra su centro. Los
+    // triángulos de menos de un píxel de ancho (anillos vistos casi de canto) no
+    // desaparecen, a cambio de engordar los bordes hasta medio píxel.
+    Conservative,
+}
+
 pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex) -> Vec<Fragment> {
+    triangle_in_rect(v1, v2, v3, FULL_RECT, FillRule::TopLeft)
+}
+
+// Igual que `triangle`, pero solo los píxeles dentro de `rect` (x e y mínimos y
+// máximos, inclusivos) y con la regla de relleno dada. Cada fragmento es idéntico
+// al de la versión sin límite, así un triángulo partido en bloques de pantalla se
+// dibuja igual que entero.
+pub fn triangle_in_rect(
+    v1: &Vertex,
+    v2: &Vertex,
+    v3: &Vertex,
+    rect: (i32, i32, i32, i32),
+    fill_rule: FillRule,
+) -> Vec<Fragment> {
     let mut fragments = Vec::new();
     let (a, b, c) = (
         v1.transformed_position,
@@ -11,11 +48,39 @@ pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex) -> Vec<Fragment> {
         v3.transformed_position,
     );
 
+    // Vértices sin proyección válida (p. ej. NaN por w = 0) o triángulos colineales
+    if !(is_finite(&a) && is_finite(&b) && is_finite(&c)) {
+        return fragments;
+    }
+    let triangle_area = edge_function(&a, &b, &c);
+    if triangle_area.abs() < MIN_TRIANGLE_AREA {
+        return fragments;
+    }
+
     let (min_x, min_y, max_x, max_y) = calculate_bounding_box(&a, &b, &c);
+    let (min_x, min_y) = (min_x.max(rect.0), min_y.max(rect.1));
+    let (max_x, max_y) = (max_x.min(rect.2), max_y.min(rect.3));
 
     let light_dir = Vec3::new(0.0, 0.0, 1.0);
 
-    let triangle_area = edge_function(&a, &b, &c);
+    // Regla de relleno arriba-izquierda: un centro de píxel que cae justo sobre una
+    // arista solo cuenta si es arista superior o izquierda, así dos triángulos que
+    // comparten una arista no dibujan dos veces ni dejan huecos en la costura.
+    // Cada peso w_i es cero sobre la arista opuesta a su vértice.
+    let orientation = triangle_area.signum();
+    let top_left = [
+        is_top_left_edge(&b, &c, orientation),
+        is_top_left_edge(&c, &a, orientation),
+        is_top_left_edge(&a, &b, orientation),
+    ];
+    // En modo conservador cada peso puede bajar de cero hasta lo que cambia entre
+    // el centro y la esquina del píxel más adentro de esa arista
+    let slack = [(&b, &c), (&c, &a), (&a, &b)].map(|(from, to)| match fill_rule {
+        FillRule::TopLeft => 0.0,
+        FillRule::Conservative => {
+            0.5 * ((to.x - from.x).abs() + (to.y - from.y).abs()) / triangle_area.abs()
+        }
+    });
 
     // Iterate over each pixel in the bounding box
     for y in min_y..=max_y {
@@ -26,7 +91,16 @@ pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex) -> Vec<Fragment> {
             let (w1, w2, w3) = barycentric_coordinates(&point, &a, &b, &c, triangle_area);
 
             // Check if the point is inside the triangle
-            if w1 >= 0.0 && w1 <= 1.0 && w2 >= 0.0 && w2 <= 1.0 && w3 >= 0.0 && w3 <= 1.0 {
+            let inside = match fill_rule {
+                FillRule::TopLeft => {
+                    covers(w1, top_left[0]) && covers(w2, top_left[1]) && covers(w3, top_left[2])
+                }
+                FillRule::Conservative => w1 >= -slack[0] && w2 >= -slack[1] && w3 >= -slack[2],
+            };
+            if inside {
+                // Fuera del triángulo (solo en modo conservador) se interpola en el
+                // punto más cercano de adentro para no extrapolar profundidad ni color
+                let (w1, w2, w3) = clamp_weights(w1, w2, w3);
                 // Interpolate normal
                 let normal = v1.transformed_normal * w1
                     + v2.transformed_normal * w2
@@ -45,14 +119,23 @@ pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex) -> Vec<Fragment> {
                 // Positions of the original vertex
                 let vertex_position = v1.position * w1 + v2.position * w2 + v3.position * w3;
 
-                fragments.push(Fragment::new(
-                    Vec2::new(x as f32, y as f32),
-                    color,
-                    depth,
-                    normal,
-                    intensity,
-                    vertex_position,
-                ));
+                let tex_coords = v1.tex_coords * w1 + v2.tex_coords * w2 + v3.tex_coords * w3;
+                let tangent = v1.transformed_tangent * w1
+                    + v2.transformed_tangent * w2
+                    + v3.transformed_tangent * w3;
+
+                fragments.push(Fragment {
+                    tex_coords,
+                    tangent,
+                    ..Fragment::new(
+                        Vec2::new(x as f32, y as f32),
+                        color,
+                        depth,
+                        normal,
+                        intensity,
+                        vertex_position,
+                    )
+                });
             }
         }
     }
@@ -60,6 +143,32 @@ pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex) -> Vec<Fragment> {
     fragments
 }
 
+fn clamp_weights(w1: f32, w2: f32, w3: f32) -> (f32, f32, f32) {
+    if w1 >= 0.0 && w2 >= 0.0 && w3 >= 0.0 {
+        return (w1, w2, w3);
+    }
+    let (w1, w2, w3) = (w1.max(0.0), w2.max(0.0), w3.max(0.0));
+    let sum = w1 + w2 + w3;
+    (w1 / sum, w2 / sum, w3 / sum)
+}
+
+fn covers(weight: f32, top_left: bool) -> bool {
+    weight > 0.0 || (weight == 0.0 && top_left)
+}
+
+// En pantalla y crece hacia abajo. Con la arista en el sentido en que los pesos
+// son positivos dentro (orientation corrige el sentido de giro del triángulo),
+// es izquierda si baja y superior si es horizontal y va hacia -x.
+fn is_top_left_edge(from: &Vec3, to: &Vec3, orientation: f32) -> bool {
+    let dx = (to.x - from.x) * orientation;
+    let dy = (to.y - from.y) * orientation;
+    dy > 0.0 || (dy == 0.0 && dx < 0.0)
+}
+
+fn is_finite(v: &Vec3) -> bool {
+    v.x.is_finite() && v.y.is_finite() && v.z.is_finite()
+}
+
 fn calculate_bounding_box(v1: &Vec3, v2: &Vec3, v3: &Vec3) -> (i32, i32, i32, i32) {
     let min_x = v1.x.min(v2.x).min(v3.x).floor() as i32;
     let min_y = v1.y.min(v2.y).min(v3.y).floor() as i32;
@@ -80,3 +189,156 @@ fn barycentric_coordinates(p: &Vec3, a: &Vec3, b: &Vec3, c: &Vec3, area: f32) ->
 fn edge_function(a: &Vec3, b: &Vec3, c: &Vec3) -> f32 {
     (c.x - a.x) * (b.y - a.y) - (c.y - a.y) * (b.x - a.x)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn screen_vertex(x: f32, y: f32) -> Vertex {
+        Vertex::new(
+            Vec3::new(x, y, 0.5),
+            Vec3::new(0.0, 0.0, 1.0),
+            Vec2::new(0.0, 0.0),
+        )
+    }
+
+    #[test]
+    fn collinear_triangle_produces_no_fragments() {
+        let fragments = triangle(
+            &screen_vertex(10.0, 10.0),
+            &screen_vertex(20.0, 20.0),
+            &screen_vertex(40.0, 40.0),
+        );
+        assert!(fragments.is_empty());
+
+        // Los tres vértices en el mismo punto (polo de la esfera)
+        let pole = screen_vertex(15.5, 15.5);
+        assert!(triangle(&pole, &pole, &pole).is_empty());
+    }
+
+    #[test]
+    fn triangle_with_nan_vertex_produces_no_fragments() {
+        let fragments = triangle(
+            &screen_vertex(10.0, 10.0),
+            &screen_vertex(f32::NAN, 20.0),
+            &screen_vertex(40.0, 10.0),
+        );
+        assert!(fragments.is_empty());
+    }
+
+    // Cuántas veces se escribe cada píxel de una cuadrícula de `size` x `size`
+    fn coverage(triangles: &[[Vertex; 3]], size: usize) -> Vec<u32> {
+        let mut counts = vec![0; size * size];
+        for [a, b, c] in triangles {
+            for fragment in triangle(a, b, c) {
+                let (x, y) = (fragment.position.x as usize, fragment.position.y as usize);
+                counts[y * size + x] += 1;
+            }
+        }
+        counts
+    }
+
+    #[test]
+    fn shared_edges_are_drawn_exactly_once() {
+        // Cuadrado partido por la diagonal: los centros de píxel caen sobre la arista
+        let diagonal = [
+            [
+                screen_vertex(0.0, 0.0),
+                screen_vertex(16.0, 0.0),
+                screen_vertex(16.0, 16.0),
+            ],
+            [
+                screen_vertex(0.0, 0.0),
+                screen_vertex(16.0, 16.0),
+                screen_vertex(0.0, 16.0),
+            ],
+        ];
+        assert!(coverage(&diagonal, 16).iter().all(|&count| count == 1));
+
+        // Arista vertical en x = 8.5 (centros de la columna 8) y sentidos de giro opuestos
+        let vertical = [
+            [
+                screen_vertex(0.0, 0.0),
+                screen_vertex(8.5, 0.0),
+                screen_vertex(8.5, 16.0),
+            ],
+            [
+                screen_vertex(0.0, 0.0),
+                screen_vertex(0.0, 16.0),
+                screen_vertex(8.5, 16.0),
+            ],
+            [
+                screen_vertex(8.5, 0.0),
+                screen_vertex(16.0, 0.0),
+                screen_vertex(8.5, 16.0),
+            ],
+            [
+                screen_vertex(16.0, 0.0),
+                screen_vertex(16.0, 16.0),
+                screen_vertex(8.5, 16.0),
+            ],
+        ];
+        assert!(coverage(&vertical, 16).iter().all(|&count| count == 1));
+    }
+
+    #[test]
+    fn sub_pixel_thin_triangle_is_kept_in_conservative_mode() {
+        // Una décima de píxel de ancho, entre dos columnas de centros de píxel
+        let (a, b, c) = (
+            screen_vertex(10.1, 2.0),
+            screen_vertex(10.2, 2.0),
+            screen_vertex(10.15, 30.0),
+        );
+        assert!(triangle(&a, &b, &c).is_empty());
+
+        let fragments = triangle_in_rect(&a, &b, &c, FULL_RECT, FillRule::Conservative);
+        assert!(fragments.iter().all(|fragment| fragment.position.x == 10.0));
+        // Sin huecos a lo largo de la aguja
+        for y in 2..30 {
+            assert!(fragments
+                .iter()
+                .any(|fragment| fragment.position.y == y as f32));
+        }
+        assert!(fragments
+            .iter()
+            .all(|fragment| (fragment.depth - 0.5).abs() < 1e-6));
+    }
+
+    #[test]
+    fn conservative_mode_covers_at_least_the_top_left_pixels() {
+        let (a, b, c) = (
+            screen_vertex(1.3, 0.7),
+            screen_vertex(14.2, 3.1),
+            screen_vertex(5.6, 12.9),
+        );
+        let pixels = |fragments: Vec<Fragment>| -> Vec<(i32, i32)> {
+            fragments
+                .iter()
+                .map(|fragment| (fragment.position.x as i32, fragment.position.y as i32))
+                .collect()
+        };
+        let top_left = pixels(triangle(&a, &b, &c));
+        let conservative = pixels(triangle_in_rect(
+            &a,
+            &b,
+            &c,
+            FULL_RECT,
+            FillRule::Conservative,
+        ));
+        assert!(conservative.len() > top_left.len());
+        assert!(top_left.iter().all(|pixel| conservative.contains(pixel)));
+    }
+
+    #[test]
+    fn regular_triangle_still_rasterizes() {
+        let fragments = triangle(
+            &screen_vertex(0.0, 0.0),
+            &screen_vertex(10.0, 0.0),
+            &screen_vertex(0.0, 10.0),
+        );
+        assert!(!fragments.is_empty());
+        assert!(fragments
+            .iter()
+            .all(|fragment| fragment.position.x.is_finite() && fragment.depth.is_finite()));
+    }
+}
@@ -6,9 +6,13 @@ pub struct Vertex {
     pub position: Vec3,
     pub normal: Vec3,
     pub tex_coords: Vec2,
+    // Dirección en la que crece u sobre la superficie, para los mapas de normales.
+    // Cero si la malla no tiene coordenadas de textura.
+    pub tangent: Vec3,
     pub color: Color,
     pub transformed_position: Vec3,
     pub transformed_normal: Vec3,
+    pub transformed_tangent: Vec3,
 }
 
 impl Vertex {
@@ -17,9 +21,11 @@ impl Vertex {
             position,
             normal,
             tex_coords,
+            tangent: Vec3::zeros(),
             color: Color::black(),
             transformed_position: position,
             transformed_normal: normal,
+            transformed_tangent: Vec3::zeros(),
         }
     }
 
@@ -28,9 +34,11 @@ impl Vertex {
             position,
             normal: Vec3::new(0.0, 0.0, 0.0),
             tex_coords: Vec2::new(0.0, 0.0),
+            tangent: Vec3::new(0.0, 0.0, 0.0),
             color,
             transformed_position: Vec3::new(0.0, 0.0, 0.0),
             transformed_normal: Vec3::new(0.0, 0.0, 0.0),
+            transformed_tangent: Vec3::new(0.0, 0.0, 0.0),
         }
     }
 
@@ -46,9 +54,11 @@ impl Default for Vertex {
             position: Vec3::new(0.0, 0.0, 0.0),
             normal: Vec3::new(0.0, 1.0, 0.0),
             tex_coords: Vec2::new(0.0, 0.0),
+            tangent: Vec3::new(0.0, 0.0, 0.0),
             color: Color::black(),
             transformed_position: Vec3::new(0.0, 0.0, 0.0),
             transformed_normal: Vec3::new(0.0, 1.0, 0.0),
+            transformed_tangent: Vec3::new(0.0, 0.0, 0.0),
         }
     }
 }
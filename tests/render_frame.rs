@@ -0,0 +1,147 @@
+// Usa el renderizador como biblioteca, sin ventana
+use nalgebra_glm::Vec3;
+use solar_system::camera::Camera;
+use solar_system::color::Color;
+use solar_system::fragment::Fragment;
+use solar_system::mesh;
+use solar_system::obj::IndexedMesh;
+use solar_system::render::{create_model_matrix, order_mismatch, render_scene_into};
+use solar_system::{Framebuffer, Renderer, Scene, SceneBody, Uniforms};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+fn red_shader(_fragment: &Fragment, _uniforms: &Uniforms) -> Option<Color> {
+    Some(Color::new(255, 0, 0))
+}
+
+// Cámara sobre el eje Z mirando al origen
+fn front_camera() -> Camera {
+    Camera::new(
+        Vec3::new(0.0, 0.0, 5.0),
+        Vec3::zeros(),
+        Vec3::new(0.0, 1.0, 0.0),
+    )
+}
+
+// Una esfera roja de radio 1 en el origen
+fn red_sphere_scene(sphere: &IndexedMesh) -> Scene<'_> {
+    Scene {
+        bodies: vec![SceneBody::new(
+            sphere,
+            create_model_matrix(Vec3::zeros(), 1.0, Vec3::zeros()),
+            red_shader,
+            vec![],
+        )],
+        ..Scene::default()
+    }
+}
+
+#[test]
+fn render_frame_draws_scene_bodies() {
+    let mut renderer = Renderer::new(200, 200);
+    let camera = front_camera();
+    let sphere = mesh::uv_sphere(8, 16);
+    let scene = red_sphere_scene(&sphere);
+
+    renderer.render_frame(&scene, &camera, 0.0);
+
+    let center = 100 * renderer.framebuffer.width + 100;
+    assert_eq!(renderer.framebuffer.buffer[center], 0xFF0000);
+    assert_eq!(renderer.framebuffer.buffer[0], 0x000000);
+}
+
+fn blue_shader(_fragment: &Fragment, _uniforms: &Uniforms) -> Option<Color> {
+    Some(Color::new(0, 0, 255))
+}
+
+#[test]
+fn opaque_bodies_render_the_same_in_any_order() {
+    let camera = Camera::new(
+        Vec3::new(0.0, 1.0, 6.0),
+        Vec3::zeros(),
+        Vec3::new(0.0, 1.0, 0.0),
+    );
+    let sphere = mesh::uv_sphere(12, 24);
+    // Dos esferas que se cruzan y una más atrás que ambas tapan en parte
+    let body = |position: Vec3, scale: f32, shader| {
+        SceneBody::new(
+            &sphere,
+            create_model_matrix(position, scale, Vec3::zeros()),
+            shader,
+            vec![],
+        )
+    };
+    let scene = Scene {
+        bodies: vec![
+            body(Vec3::new(-0.5, 0.0, 0.0), 1.0, red_shader),
+            body(Vec3::new(0.6, 0.2, 0.3), 0.9, blue_shader),
+            body(Vec3::new(0.0, 0.0, -3.0), 2.0, red_shader),
+        ],
+        ..Scene::default()
+    };
+
+    assert_eq!(order_mismatch(160, 120, &scene, &camera, 0.0, 0), 0);
+
+    // La comparación sí nota cuando falta un cuerpo
+    let without_front = Scene {
+        bodies: scene.bodies[1..].to_vec(),
+        ..Scene::default()
+    };
+    let mut full = Framebuffer::new(160, 120);
+    let mut partial = Framebuffer::new(160, 120);
+    render_scene_into(&mut full, &scene, &camera, 0.0);
+    render_scene_into(&mut partial, &without_front, &camera, 0.0);
+    assert!(full.count_differences(&partial, 0) > 0);
+}
+
+#[test]
+fn scene_renders_offscreen_and_blits_into_a_corner() {
+    let camera = front_camera();
+    let sphere = mesh::uv_sphere(8, 16);
+    let scene = red_sphere_scene(&sphere);
+
+    let mut minimap = Framebuffer::new(40, 40);
+    render_scene_into(&mut minimap, &scene, &camera, 0.0);
+    assert_eq!(minimap.buffer[20 * 40 + 20], 0xFF0000);
+
+    // Pegado en la esquina inferior derecha: lo que sobra se recorta
+    let mut target = Framebuffer::new(100, 100);
+    target.clear();
+    target.blit(&minimap, 70, 70);
+    assert_eq!(target.buffer[90 * 100 + 90], 0xFF0000);
+    assert_eq!(target.buffer[50 * 100 + 50], 0x000000);
+}
+
+#[test]
+fn renderer_reports_selection_changes_and_finished_frames() {
+    let mut renderer = Renderer::new(40, 40);
+    let selections = Rc::new(RefCell::new(Vec::new()));
+    let frames = Rc::new(RefCell::new(Vec::new()));
+    let seen = Rc::clone(&selections);
+    renderer.on_select(Box::new(move |index| seen.borrow_mut().push(index)));
+    let seen = Rc::clone(&frames);
+    renderer.on_frame(Box::new(move |framebuffer, time| {
+        seen.borrow_mut()
+            .push((framebuffer.buffer[20 * 40 + 20], time))
+    }));
+
+    // Repetir la misma selección no vuelve a avisar
+    renderer.select(Some(0));
+    renderer.select(Some(0));
+    renderer.select(None);
+    assert_eq!(*selections.borrow(), vec![Some(0), None]);
+    assert_eq!(renderer.selected(), None);
+
+    let camera = front_camera();
+    let sphere = mesh::uv_sphere(8, 16);
+    let scene = red_sphere_scene(&sphere);
+    renderer.render_frame(&scene, &camera, 3.0);
+    // El aviso llega con el cuadro ya dibujado
+    assert_eq!(*frames.borrow(), vec![(0xFF0000, 3.0)]);
+
+    // Dibujando por partes, el aviso espera a `finish_frame`
+    renderer.render_scene(&scene, &camera, 4.0);
+    assert_eq!(frames.borrow().len(), 1);
+    renderer.finish_frame(4.0);
+    assert_eq!(*frames.borrow(), vec![(0xFF0000, 3.0), (0xFF0000, 4.0)]);
+}